@@ -1,9 +1,16 @@
 use {Error, Result, NixPath};
 use errno::Errno;
 use libc::{self, c_int, c_uint, c_char, size_t, ssize_t};
-use sys::stat::Mode;
-use std::os::unix::io::RawFd;
-use std::ffi::OsStr;
+use sys::stat::{fchmod, lstat, Mode, SFlag};
+use unistd::{fsync, getpid, unlink, write};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::mem;
+use std::marker::PhantomData;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use fd::OwnedFd;
+use std::ffi::{OsStr, OsString};
 use std::os::unix::ffi::OsStrExt;
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
@@ -152,6 +159,101 @@ pub fn openat<P: ?Sized + NixPath>(dirfd: RawFd, path: &P, oflag: OFlag, mode: M
     Errno::result(fd)
 }
 
+/// Like [`open`](fn.open.html), but return an [`OwnedFd`](../fd/struct.OwnedFd.html).
+pub fn open_owned<P: ?Sized + NixPath>(path: &P, oflag: OFlag, mode: Mode) -> Result<OwnedFd> {
+    open(path, oflag, mode).map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// Like [`openat`](fn.openat.html), but return an [`OwnedFd`](../fd/struct.OwnedFd.html).
+pub fn openat_owned<P: ?Sized + NixPath>(dirfd: RawFd, path: &P, oflag: OFlag, mode: Mode) -> Result<OwnedFd> {
+    openat(dirfd, path, oflag, mode).map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+#[cfg(target_os = "linux")]
+libc_bitflags!(
+    /// Restrictions on how [`openat2`](fn.openat2.html) resolves paths.
+    pub struct ResolveFlags: u64 {
+        /// Don't cross mount points, including bind mounts.
+        RESOLVE_NO_XDEV;
+        /// Don't follow "magic links" such as `/proc/[pid]/fd/*`.
+        RESOLVE_NO_MAGICLINKS;
+        /// Don't follow any symbolic links.
+        RESOLVE_NO_SYMLINKS;
+        /// Fail if resolution would leave the directory tree below `dirfd`,
+        /// whether by `..`, absolute paths or symbolic links.
+        RESOLVE_BENEATH;
+        /// Resolve the path as if `dirfd` were the root directory, so that `..`
+        /// and absolute symbolic links can't escape it.
+        RESOLVE_IN_ROOT;
+    }
+);
+
+/// How to open a file with [`openat2`](fn.openat2.html).
+///
+/// Wraps a `struct open_how`, built up from the default of no flags, no mode
+/// and no resolution restrictions.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub struct OpenHow(libc::open_how);
+
+#[cfg(target_os = "linux")]
+impl OpenHow {
+    /// Create an `OpenHow` with no flags, mode or resolution restrictions.
+    pub fn new() -> OpenHow {
+        OpenHow(unsafe { mem::zeroed() })
+    }
+
+    /// Set the flags to open the file with, as for [`open`](fn.open.html).
+    pub fn flags(mut self, flags: OFlag) -> OpenHow {
+        self.0.flags = flags.bits() as c_uint as u64;
+        self
+    }
+
+    /// Set the mode of a newly created file.
+    ///
+    /// Unlike for `open`, this must be empty unless `flags` contains
+    /// `O_CREAT` or `O_TMPFILE`.
+    pub fn mode(mut self, mode: Mode) -> OpenHow {
+        self.0.mode = mode.bits() as u64;
+        self
+    }
+
+    /// Set restrictions on how the path is resolved.
+    pub fn resolve(mut self, resolve: ResolveFlags) -> OpenHow {
+        self.0.resolve = resolve.bits();
+        self
+    }
+}
+
+/// Open a file relative to `dirfd` like [`openat`](fn.openat.html), with
+/// additional control over how `path` is resolved (see
+/// [openat2(2)](http://man7.org/linux/man-pages/man2/openat2.2.html)).
+///
+/// Fails with `EXDEV` if resolving `path` would violate one of the
+/// restrictions of `how`, such as a symbolic link leading out of `dirfd` with
+/// `RESOLVE_BENEATH`, and with `ELOOP` if `RESOLVE_NO_SYMLINKS` or
+/// `RESOLVE_NO_MAGICLINKS` prevent following a link.  Unknown or invalid
+/// flags are rejected with `EINVAL` rather than ignored.  Kernels older than
+/// 5.6 fail with `ENOSYS`.
+///
+/// The kernel is passed the size of libc's `struct open_how` along with it.
+/// If that is larger than the kernel's own, because libc knows about fields
+/// added by a newer kernel, the call fails with `E2BIG` unless all of the
+/// fields the kernel doesn't know about are zero.  `OpenHow` leaves any
+/// fields other than the flags, mode and resolution restrictions zeroed, so
+/// this can't happen through this function.
+#[cfg(target_os = "linux")]
+pub fn openat2<P: ?Sized + NixPath>(dirfd: RawFd, path: &P, how: OpenHow) -> Result<RawFd> {
+    let mut how = how;
+    let fd = try!(path.with_nix_path(|cstr| unsafe {
+        libc::syscall(libc::SYS_openat2, dirfd, cstr.as_ptr(), &mut how.0 as *mut libc::open_how,
+                      mem::size_of::<libc::open_how>())
+    }));
+
+    Errno::result(fd).map(|fd| fd as RawFd)
+}
+
 fn wrap_readlink_result(buffer: &mut[u8], res: ssize_t) -> Result<&OsStr> {
     match Errno::result(res) {
         Err(err) => Err(err),
@@ -250,6 +352,12 @@ pub fn fcntl(fd: RawFd, arg: FcntlArg) -> Result<c_int> {
             F_SETLK(flock) => libc::fcntl(fd, libc::F_SETLK, flock),
             F_SETLKW(flock) => libc::fcntl(fd, libc::F_SETLKW, flock),
             F_GETLK(flock) => libc::fcntl(fd, libc::F_GETLK, flock),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_OFD_SETLK(flock) => libc::fcntl(fd, libc::F_OFD_SETLK, flock),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_OFD_SETLKW(flock) => libc::fcntl(fd, libc::F_OFD_SETLKW, flock),
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            F_OFD_GETLK(flock) => libc::fcntl(fd, libc::F_OFD_GETLK, flock),
             #[cfg(any(target_os = "android", target_os = "linux"))]
             F_ADD_SEALS(flag) => libc::fcntl(fd, libc::F_ADD_SEALS, flag.bits()),
             #[cfg(any(target_os = "android", target_os = "linux"))]
@@ -260,14 +368,46 @@ pub fn fcntl(fd: RawFd, arg: FcntlArg) -> Result<c_int> {
             F_GETPIPE_SZ => libc::fcntl(fd, libc::F_GETPIPE_SZ),
             #[cfg(any(target_os = "linux", target_os = "android"))]
             F_SETPIPE_SZ(size) => libc::fcntl(fd, libc::F_SETPIPE_SZ, size),
-            #[cfg(any(target_os = "linux", target_os = "android"))]
-            _ => unimplemented!()
         }
     };
 
     Errno::result(res)
 }
 
+/// Set or clear the close-on-exec flag of `fd`, leaving its other file
+/// descriptor flags untouched.
+pub fn set_cloexec(fd: RawFd, cloexec: bool) -> Result<()> {
+    let flags = try!(fcntl(fd, F_GETFD));
+    let new_flags = if cloexec {
+        flags | libc::FD_CLOEXEC
+    } else {
+        flags & !libc::FD_CLOEXEC
+    };
+    if new_flags == flags {
+        return Ok(());
+    }
+
+    let res = unsafe { libc::fcntl(fd, libc::F_SETFD, new_flags) };
+    Errno::result(res).map(drop)
+}
+
+/// Set or clear the `O_NONBLOCK` file status flag of `fd`, leaving its other
+/// file status flags untouched.
+pub fn set_nonblocking(fd: RawFd, nonblocking: bool) -> Result<()> {
+    let flags = try!(fcntl(fd, F_GETFL));
+    let new_flags = if nonblocking {
+        flags | libc::O_NONBLOCK
+    } else {
+        flags & !libc::O_NONBLOCK
+    };
+    if new_flags == flags {
+        return Ok(());
+    }
+
+    let res = unsafe { libc::fcntl(fd, libc::F_SETFL, new_flags) };
+    Errno::result(res).map(drop)
+}
+
 #[derive(Clone, Copy)]
 #[allow(missing_debug_implementations)]
 pub enum FlockArg {
@@ -296,6 +436,324 @@ pub fn flock(fd: RawFd, arg: FlockArg) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// A lock on a file, which is released when dropped.
+///
+/// Whole-file locks are taken with [`flock`](fn.flock.html), byte ranges are
+/// locked with open file description locks (`F_OFD_SETLKW`).  Both belong to
+/// the open file description, so they conflict with locks taken through
+/// other opens of the file, even in the same process, but not with those
+/// taken through duplicates of the file descriptor.
+///
+/// The guard borrows the file descriptor, so it can't be closed while locked.
+///
+/// ```no_run
+/// # use std::fs::File;
+/// # use nix::fcntl::FileLock;
+/// let file = File::create("/tmp/nix-filelock").unwrap();
+/// {
+///     let _lock = FileLock::exclusive(&file).unwrap();
+///     // Only this process writes to the file now.
+/// }
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct FileLock<'a> {
+    fd: RawFd,
+    // The locked `(start, len)`, or `None` for the whole file
+    range: Option<(libc::off_t, libc::off_t)>,
+    _fd: PhantomData<&'a ()>,
+}
+
+impl<'a> FileLock<'a> {
+    fn flock<F: AsRawFd>(fd: &'a F, arg: FlockArg) -> Result<FileLock<'a>> {
+        try!(flock(fd.as_raw_fd(), arg));
+
+        Ok(FileLock { fd: fd.as_raw_fd(), range: None, _fd: PhantomData })
+    }
+
+    fn try_flock<F: AsRawFd>(fd: &'a F, arg: FlockArg) -> Result<Option<FileLock<'a>>> {
+        match FileLock::flock(fd, arg) {
+            Ok(lock) => Ok(Some(lock)),
+            Err(Error::Sys(Errno::EAGAIN)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lock the whole file exclusively, waiting for other locks on it to be
+    /// released.
+    pub fn exclusive<F: AsRawFd>(fd: &'a F) -> Result<FileLock<'a>> {
+        FileLock::flock(fd, FlockArg::LockExclusive)
+    }
+
+    /// Take a shared lock on the whole file, waiting for exclusive locks on
+    /// it to be released.
+    pub fn shared<F: AsRawFd>(fd: &'a F) -> Result<FileLock<'a>> {
+        FileLock::flock(fd, FlockArg::LockShared)
+    }
+
+    /// Like [`exclusive`](#method.exclusive), but return `None` instead of
+    /// waiting if the file is locked already.
+    pub fn try_exclusive<F: AsRawFd>(fd: &'a F) -> Result<Option<FileLock<'a>>> {
+        FileLock::try_flock(fd, FlockArg::LockExclusiveNonblock)
+    }
+
+    /// Like [`shared`](#method.shared), but return `None` instead of waiting
+    /// if the file is locked exclusively already.
+    pub fn try_shared<F: AsRawFd>(fd: &'a F) -> Result<Option<FileLock<'a>>> {
+        FileLock::try_flock(fd, FlockArg::LockSharedNonblock)
+    }
+
+    /// Lock the `len` bytes at `start` exclusively, waiting for other locks
+    /// on them to be released.  A `len` of zero locks up to the end of the
+    /// file, however far it grows.
+    ///
+    /// Byte-range locks don't interact with whole-file locks.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn exclusive_range<F: AsRawFd>(fd: &'a F, start: libc::off_t,
+                                       len: libc::off_t) -> Result<FileLock<'a>> {
+        let lock = ofd_flock(libc::F_WRLCK, start, len);
+        try!(fcntl(fd.as_raw_fd(), F_OFD_SETLKW(&lock)));
+
+        Ok(FileLock { fd: fd.as_raw_fd(), range: Some((start, len)), _fd: PhantomData })
+    }
+
+    /// Release the lock, reporting any error.
+    pub fn unlock(self) -> Result<()> {
+        let res = self.release();
+        ::std::mem::forget(self);
+
+        res
+    }
+
+    fn release(&self) -> Result<()> {
+        match self.range {
+            None => flock(self.fd, FlockArg::Unlock),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            Some((start, len)) => {
+                let lock = ofd_flock(libc::F_UNLCK, start, len);
+                fcntl(self.fd, F_OFD_SETLK(&lock)).map(drop)
+            },
+            #[cfg(not(any(target_os = "android", target_os = "linux")))]
+            Some(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a> Drop for FileLock<'a> {
+    fn drop(&mut self) {
+        // The lock is released when the file is closed anyway
+        let _ = self.release();
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn ofd_flock(ty: c_int, start: libc::off_t, len: libc::off_t) -> libc::flock {
+    let mut lock: libc::flock = unsafe { mem::zeroed() };
+    lock.l_type = ty as _;
+    lock.l_whence = libc::SEEK_SET as _;
+    lock.l_start = start;
+    lock.l_len = len;
+    // Must be zero for OFD locks
+    lock.l_pid = 0;
+    lock
+}
+
+/// Flush the directory containing `path` to disk, so that creating, renaming
+/// or removing the file at `path` survives a crash.
+///
+/// `path` needn't exist, and a symbolic link at `path` isn't followed.
+pub fn fsync_parent_dir<P: ?Sized + NixPath>(path: &P) -> Result<()> {
+    let path = try!(to_path_buf(path));
+
+    fsync_dir(parent_dir(&path))
+}
+
+/// Replace the contents of the file at `path` with `contents` so that, even
+/// after a crash, the file is found either with its old or its new content.
+///
+/// The new content is written to a temporary file, which is flushed to disk
+/// and renamed over `path` before the directory is flushed as well.  The
+/// temporary file is unnamed if the filesystem supports `O_TMPFILE` and
+/// `/proc` is mounted, and a hidden sibling of `path` otherwise.  It is
+/// removed if writing fails, but if the process dies while the temporary file
+/// has a name, it is left behind as `.<name>.<pid>.<n>.tmp` next to `path`.
+///
+/// If `path` is a symbolic link, the file it finally points to is replaced
+/// and the links are kept, and the temporary file is created in the
+/// target's directory so that it can be renamed over it.  The new file gets
+/// exactly the permissions in `mode`, regardless of the umask.
+pub fn atomic_write<P: ?Sized + NixPath>(path: &P, contents: &[u8], mode: Mode) -> Result<()> {
+    let path = try!(resolve_symlinks(&try!(to_path_buf(path))));
+    if path.file_name().is_none() {
+        return Err(Error::invalid_argument());
+    }
+
+    let mut temp = None;
+    let res = atomic_replace(&path, contents, mode, &mut temp);
+    if let Some(temp) = temp {
+        let _ = unlink(&temp);
+    }
+
+    res
+}
+
+// Sets `temp` while a named temporary file exists.
+fn atomic_replace(path: &Path, contents: &[u8], mode: Mode, temp: &mut Option<PathBuf>) -> Result<()> {
+    let dir = parent_dir(path);
+    let tmpfile = match try!(open_tmpfile(dir, mode)) {
+        Some(fd) => {
+            try!(write_synced(fd.as_raw_fd(), contents, mode));
+            let name = temp_sibling(path);
+            // Linking goes through /proc, which may not be mounted, so treat
+            // any failure like missing `O_TMPFILE` support.
+            match link_tmpfile(fd.as_raw_fd(), &name) {
+                Ok(()) => {
+                    *temp = Some(name);
+                    Some(fd)
+                },
+                Err(_) => None,
+            }
+        },
+        None => None,
+    };
+    let fd = match tmpfile {
+        Some(fd) => fd,
+        None => {
+            let (fd, name) = try!(create_sibling(path, mode));
+            *temp = Some(name);
+            try!(write_synced(fd.as_raw_fd(), contents, mode));
+            fd
+        },
+    };
+
+    try!(rename(temp.as_ref().unwrap(), path));
+    *temp = None;
+    drop(fd);
+
+    fsync_dir(dir)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn open_tmpfile(dir: &Path, mode: Mode) -> Result<Option<OwnedFd>> {
+    match open_owned(dir, OFlag::O_TMPFILE | OFlag::O_WRONLY | OFlag::O_CLOEXEC, mode) {
+        Ok(fd) => Ok(Some(fd)),
+        // Unsupported by the filesystem, or by kernels before 3.11
+        Err(Error::Sys(Errno::EOPNOTSUPP)) |
+        Err(Error::Sys(Errno::EISDIR)) |
+        Err(Error::Sys(Errno::EINVAL)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+fn open_tmpfile(_dir: &Path, _mode: Mode) -> Result<Option<OwnedFd>> {
+    Ok(None)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn link_tmpfile(fd: RawFd, name: &Path) -> Result<()> {
+    // Linking the file descriptor itself with AT_EMPTY_PATH needs
+    // CAP_DAC_READ_SEARCH
+    let proc_path = format!("/proc/self/fd/{}", fd);
+    let res = try!(try!(proc_path.as_str().with_nix_path(|proc_path| {
+        name.with_nix_path(|name| unsafe {
+            libc::linkat(libc::AT_FDCWD, proc_path.as_ptr(), libc::AT_FDCWD, name.as_ptr(),
+                         libc::AT_SYMLINK_FOLLOW)
+        })
+    })));
+
+    Errno::result(res).map(drop)
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+fn link_tmpfile(_fd: RawFd, _name: &Path) -> Result<()> {
+    unreachable!()
+}
+
+fn create_sibling(path: &Path, mode: Mode) -> Result<(OwnedFd, PathBuf)> {
+    loop {
+        let name = temp_sibling(path);
+        match open_owned(&name, OFlag::O_WRONLY | OFlag::O_CREAT | OFlag::O_EXCL | OFlag::O_CLOEXEC,
+                         mode) {
+            Ok(fd) => return Ok((fd, name)),
+            // Left behind by a process with the same ID
+            Err(Error::Sys(Errno::EEXIST)) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn temp_sibling(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or(OsStr::new("")));
+    name.push(format!(".{}.{}.tmp", getpid(), COUNTER.fetch_add(1, Ordering::Relaxed)));
+    parent_dir(path).join(name)
+}
+
+fn write_synced(fd: RawFd, contents: &[u8], mode: Mode) -> Result<()> {
+    try!(fchmod(fd, mode));
+
+    let mut buf = contents;
+    while !buf.is_empty() {
+        match write(fd, buf) {
+            Ok(n) => buf = &buf[n..],
+            Err(Error::Sys(Errno::EINTR)) => {},
+            Err(e) => return Err(e),
+        }
+    }
+
+    fsync(fd)
+}
+
+fn rename(from: &Path, to: &Path) -> Result<()> {
+    let res = try!(try!(from.with_nix_path(|from| {
+        to.with_nix_path(|to| unsafe { libc::rename(from.as_ptr(), to.as_ptr()) })
+    })));
+
+    Errno::result(res).map(drop)
+}
+
+fn fsync_dir(dir: &Path) -> Result<()> {
+    let fd = try!(open_owned(dir, OFlag::O_RDONLY | OFlag::O_DIRECTORY | OFlag::O_CLOEXEC,
+                             Mode::empty()));
+
+    fsync(fd.as_raw_fd())
+}
+
+/// Follow symbolic links from `path` to a path that isn't one, which may not
+/// exist.
+fn resolve_symlinks(path: &Path) -> Result<PathBuf> {
+    let mut path = path.to_path_buf();
+    let mut buf = vec![0u8; libc::PATH_MAX as usize];
+    // As many links as Linux follows
+    for _ in 0..40 {
+        match lstat(&path) {
+            Ok(ref st) if SFlag::from_bits_truncate(st.st_mode) & SFlag::S_IFMT == SFlag::S_IFLNK => {},
+            Ok(_) | Err(Error::Sys(Errno::ENOENT)) => return Ok(path),
+            Err(e) => return Err(e),
+        }
+        // Relative targets are relative to the directory of the link
+        let target = Path::new(try!(readlink(&path, &mut buf))).to_path_buf();
+        path = parent_dir(&path).join(target);
+    }
+
+    Err(Error::Sys(Errno::ELOOP))
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        // The root is its own parent
+        None => path,
+    }
+}
+
+fn to_path_buf<P: ?Sized + NixPath>(path: &P) -> Result<PathBuf> {
+    path.with_nix_path(|cstr| PathBuf::from(OsStr::from_bytes(cstr.to_bytes())))
+}
+
 #[cfg(any(target_os = "android", target_os = "linux"))]
 libc_bitflags! {
     /// Additional flags to `splice` and friends.
@@ -383,3 +841,77 @@ pub fn fallocate(fd: RawFd, mode: FallocateFlags, offset: libc::off_t, len: libc
     let res = unsafe { libc::fallocate(fd, mode.bits(), offset, len) };
     Errno::result(res)
 }
+
+#[cfg(target_os = "linux")]
+libc_bitflags!(
+    /// Flags for `sync_file_range`.
+    pub struct SyncFileRangeFlags: c_uint {
+        /// Wait for writeback of already submitted pages in the range before
+        /// doing anything else.
+        SYNC_FILE_RANGE_WAIT_BEFORE;
+        /// Start writeback of all dirty pages in the range.
+        SYNC_FILE_RANGE_WRITE;
+        /// Wait for writeback of the range to finish.
+        SYNC_FILE_RANGE_WAIT_AFTER;
+    }
+);
+
+/// Initiates or waits for writeback of part of a file.
+///
+/// The range starts at `offset` and covers `nbytes` bytes, or everything up
+/// to the end of the file if `nbytes` is zero.
+///
+/// # Warning
+///
+/// This is not a durability guarantee: it neither writes out file metadata
+/// nor flushes the disk's write cache.  Use `fsync` or `fdatasync` when the
+/// data must survive a crash.
+#[cfg(target_os = "linux")]
+pub fn sync_file_range(fd: RawFd, offset: libc::off64_t, nbytes: libc::off64_t,
+                       flags: SyncFileRangeFlags) -> Result<()> {
+    let res = unsafe { libc::sync_file_range(fd, offset, nbytes, flags.bits()) };
+
+    Errno::result(res).map(drop)
+}
+
+/// Read `count` bytes of `fd` starting at `offset` into the page cache, so
+/// that subsequent reads of the range don't block on disk.
+///
+/// Fails with `EBADF` if `fd` isn't open for reading and with `EINVAL` if it
+/// doesn't refer to a regular file.
+#[cfg(target_os = "linux")]
+pub fn readahead(fd: RawFd, offset: libc::off64_t, count: usize) -> Result<()> {
+    let res = unsafe { libc::readahead(fd, offset, count as size_t) };
+
+    Errno::result(res).map(drop)
+}
+
+/// Prime the page cache with `len` bytes of `fd` starting at `offset`.
+///
+/// This calls [`readahead`](fn.readahead.html) and additionally advises the
+/// kernel with `POSIX_FADV_WILLNEED` that the range will be accessed soon.
+/// Errors are the same as for `readahead`, except that it fails with `EINVAL`
+/// if `offset` or `len` doesn't fit in an `off_t`, as can happen where that is
+/// 32 bits wide.
+#[cfg(target_os = "linux")]
+pub fn preload_file_range(fd: RawFd, offset: libc::off64_t, len: usize) -> Result<()> {
+    let max = libc::off_t::max_value() as libc::off64_t;
+    if offset > max || len as u64 > max as u64 {
+        return Err(Error::invalid_argument());
+    }
+
+    // readahead reports unsuitable descriptors more precisely than
+    // posix_fadvise, which fails with ESPIPE for pipes, so call it first.
+    try!(readahead(fd, offset, len));
+
+    // posix_fadvise returns an error number instead of setting errno.
+    let res = unsafe {
+        libc::posix_fadvise(fd, offset as libc::off_t, len as libc::off_t,
+                            libc::POSIX_FADV_WILLNEED)
+    };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(Error::Sys(Errno::from_i32(res)))
+    }
+}
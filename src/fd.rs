@@ -0,0 +1,69 @@
+//! Owned file descriptors
+//!
+//! Most functions in this crate return bare `RawFd`s, which have to be closed
+//! by hand.  The `_owned` variants of the functions creating file descriptors,
+//! like [`fcntl::open_owned`](../fcntl/fn.open_owned.html), instead return an
+//! [`OwnedFd`](struct.OwnedFd.html) which closes the file descriptor when it
+//! is dropped.
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+use Result;
+use fcntl::{fcntl, FcntlArg};
+use unistd::close;
+
+/// A file descriptor that is closed when dropped.
+///
+/// Errors from closing the file descriptor, including `EBADF`, are ignored
+/// on drop.  Closing the file descriptor by other means while it is owned is
+/// still a bug, though: by the time the `OwnedFd` is dropped the number
+/// may have been reused for another file, which would then be closed
+/// instead.  Use [`into_raw_fd`](#method.into_raw_fd) to take back
+/// ownership of the file descriptor.
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub struct OwnedFd(RawFd);
+
+impl OwnedFd {
+    /// Duplicate the file descriptor.
+    ///
+    /// The new file descriptor shares the open file description, and thus the
+    /// file offset and status flags, with this one.  Unlike this one, it has
+    /// `FD_CLOEXEC` set.
+    pub fn try_clone(&self) -> Result<OwnedFd> {
+        let fd = try!(fcntl(self.0, FcntlArg::F_DUPFD_CLOEXEC(0)));
+
+        Ok(OwnedFd(fd))
+    }
+}
+
+impl AsRawFd for OwnedFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl IntoRawFd for OwnedFd {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.0;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl FromRawFd for OwnedFd {
+    /// Take ownership of `fd`.
+    ///
+    /// Unsafe because `fd` must be an open file descriptor that nothing else
+    /// closes.
+    unsafe fn from_raw_fd(fd: RawFd) -> OwnedFd {
+        OwnedFd(fd)
+    }
+}
+
+impl Drop for OwnedFd {
+    fn drop(&mut self) {
+        // There is no way to report errors from here, and the file descriptor
+        // is deallocated even if close fails with EINTR or EIO on Linux.
+        let _ = close(self.0);
+    }
+}
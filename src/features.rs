@@ -16,39 +16,11 @@ mod os {
     static VERS_2_6_28:  usize = 4;
     static VERS_3:       usize = 5;
 
-    #[inline]
-    fn digit(dst: &mut usize, b: u8) {
-        *dst *= 10;
-        *dst += (b - b'0') as usize;
-    }
-
     fn parse_kernel_version() -> usize {
-        let u = uname();
-
-        let mut curr:  usize = 0;
-        let mut major: usize = 0;
-        let mut minor: usize = 0;
-        let mut patch: usize = 0;
-
-        for b in u.release().bytes() {
-            if curr >= 3 {
-                break;
-            }
-
-            match b {
-                b'.' | b'-' => {
-                    curr += 1;
-                }
-                b'0'...b'9' => {
-                    match curr {
-                        0 => digit(&mut major, b),
-                        1 => digit(&mut minor, b),
-                        _ => digit(&mut patch, b),
-                    }
-                }
-                _ => break,
-            }
-        }
+        let (major, minor, patch) = match uname().ok().and_then(|u| u.kernel_version()) {
+            Some(version) => version,
+            None => return VERS_UNKNOWN,
+        };
 
         if major >= 3 {
             VERS_3
@@ -73,7 +73,34 @@ impl InterfaceAddress {
 ///
 /// Use the function `getifaddrs` to create this Iterator. Note that the
 /// actual list of interfaces can be iterated once and will be freed as
-/// soon as the Iterator goes out of scope.
+/// soon as the Iterator goes out of scope.  Each `InterfaceAddress` it yields
+/// is an owned copy, so nothing refers to the list once it is freed:
+///
+/// ```
+/// # extern crate nix;
+/// # fn main() {
+/// let mut addrs = nix::ifaddrs::getifaddrs().unwrap();
+/// let first = addrs.next();
+/// drop(addrs);
+/// if let Some(ifaddr) = first {
+///     println!("{}", ifaddr.interface_name);
+/// }
+/// # }
+/// ```
+///
+/// The iterator itself can't be used once it has been dropped:
+///
+/// ```compile_fail
+/// # extern crate nix;
+/// # fn main() {
+/// let mut addrs = nix::ifaddrs::getifaddrs().unwrap();
+/// let first = addrs.next();
+/// drop(addrs);
+/// if let Some(ifaddr) = addrs.next() {
+///     println!("{}", ifaddr.interface_name);
+/// }
+/// # }
+/// ```
 #[derive(Debug, Eq, Hash, PartialEq)]
 pub struct InterfaceAddressIterator {
     base: *mut libc::ifaddrs,
@@ -138,9 +165,47 @@ pub fn getifaddrs() -> Result<InterfaceAddressIterator> {
 mod tests {
     use super::*;
 
+    use std::net;
+    use sys::socket::SockAddr;
+
     // Only checks if `getifaddrs` can be invoked without panicking.
     #[test]
     fn test_getifaddrs() {
         let _ = getifaddrs();
     }
+
+    #[test]
+    fn test_getifaddrs_loopback() {
+        let loopback: Vec<_> = getifaddrs().unwrap()
+            .filter(|ifaddr| ifaddr.flags.contains(InterfaceFlags::IFF_LOOPBACK))
+            .collect();
+        assert!(!loopback.is_empty());
+
+        for ifaddr in &loopback {
+            // Link-layer entries have no netmask, so only check IP addresses
+            if let Some(SockAddr::Inet(addr)) = ifaddr.address {
+                assert!(addr.to_std().ip().is_loopback(), "{}", addr);
+            }
+        }
+        let localhost = net::IpAddr::from([127, 0, 0, 1]);
+        assert!(loopback.iter().any(|ifaddr| match ifaddr.address {
+            Some(SockAddr::Inet(addr)) => addr.to_std().ip() == localhost,
+            _ => false,
+        }));
+        // On Linux every interface also shows up with its AF_PACKET entry
+        if cfg!(any(target_os = "android", target_os = "linux")) {
+            assert!(loopback.iter().any(|ifaddr| match ifaddr.address {
+                Some(SockAddr::Link(_)) => true,
+                _ => false,
+            }));
+        }
+    }
+
+    #[test]
+    fn test_getifaddrs_names() {
+        for ifaddr in getifaddrs().unwrap() {
+            assert!(!ifaddr.interface_name.is_empty());
+            assert!(!ifaddr.interface_name.contains('\0'));
+        }
+    }
 }
@@ -0,0 +1,33 @@
+//! Job control, as done by shells
+//!
+//! A shell runs each job in its own process group and lets the terminal's
+//! foreground process group read from it.  Moving a group to the foreground
+//! and back is done with [`give_terminal_to`](fn.give_terminal_to.html); the
+//! other building blocks are [`setpgid`](../unistd/fn.setpgid.html),
+//! [`getpgid`](../unistd/fn.getpgid.html),
+//! [`getsid`](../unistd/fn.getsid.html) and
+//! [`tcgetpgrp`](../unistd/fn.tcgetpgrp.html).
+use std::os::unix::io::RawFd;
+use Result;
+use sys::signal::{pthread_sigmask, SigSet, SigmaskHow, SIGTTOU};
+use unistd::{tcsetpgrp, Pid};
+
+/// Make `pgrp` the foreground process group of the terminal `tty_fd`, which
+/// must be the controlling terminal of the calling process.
+///
+/// Unless the calling process is in the foreground already, `tcsetpgrp`
+/// would make the kernel stop it with `SIGTTOU`, so that signal is blocked
+/// in the calling thread for the duration of the call.  This is how a shell
+/// takes the terminal back once a job stops or exits.
+pub fn give_terminal_to(pgrp: Pid, tty_fd: RawFd) -> Result<()> {
+    let mut ttou = SigSet::empty();
+    ttou.add(SIGTTOU);
+    let mut oldmask = SigSet::empty();
+    try!(pthread_sigmask(SigmaskHow::SIG_BLOCK, Some(&ttou), Some(&mut oldmask)));
+
+    let res = tcsetpgrp(tty_fd, pgrp);
+    // Restoring the mask can't fail, as it was valid before
+    let _ = pthread_sigmask(SigmaskHow::SIG_SETMASK, Some(&oldmask), None);
+
+    res
+}
@@ -34,6 +34,8 @@ pub mod errno;
 pub mod features;
 pub mod fcntl;
 #[deny(missing_docs)]
+pub mod fd;
+#[deny(missing_docs)]
 #[cfg(any(target_os = "dragonfly",
           target_os = "freebsd",
           target_os = "ios",
@@ -42,6 +44,8 @@ pub mod fcntl;
           target_os = "netbsd",
           target_os = "openbsd"))]
 pub mod ifaddrs;
+#[deny(missing_docs)]
+pub mod job;
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub mod mount;
 #[cfg(any(target_os = "dragonfly",
@@ -51,14 +55,25 @@ pub mod mount;
           target_os = "netbsd"))]
 pub mod mqueue;
 #[deny(missing_docs)]
-pub mod net;
-#[deny(missing_docs)]
 pub mod poll;
 #[deny(missing_docs)]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod process;
+#[deny(missing_docs)]
 pub mod pty;
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub mod sched;
+#[deny(missing_docs)]
+#[cfg(any(target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos"))]
+pub mod spawn;
+#[macro_use]
 pub mod sys;
+// Uses the ioctl macros, so it has to come after `sys`.
+#[deny(missing_docs)]
+pub mod net;
 // This can be implemented for other platforms as soon as libc
 // provides bindings for them.
 #[cfg(all(target_os = "linux",
@@ -74,7 +89,7 @@ pub mod unistd;
 
 use libc::{c_char, PATH_MAX};
 
-use std::{error, fmt, ptr, result};
+use std::{error, fmt, io, ptr, result};
 use std::ffi::{CStr, OsStr};
 use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
@@ -120,6 +135,14 @@ impl Error {
         Error::Sys(Errno::EINVAL)
     }
 
+    /// Get the errno this error was created from, if any
+    pub fn as_errno(&self) -> Option<Errno> {
+        match *self {
+            Error::Sys(errno) => Some(errno),
+            _ => None,
+        }
+    }
+
 }
 
 impl From<Errno> for Error {
@@ -130,6 +153,17 @@ impl From<std::string::FromUtf8Error> for Error {
     fn from(_: std::string::FromUtf8Error) -> Error { Error::InvalidUtf8 }
 }
 
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        match err {
+            Error::Sys(errno) => io::Error::from(errno),
+            Error::InvalidPath => io::Error::new(io::ErrorKind::InvalidInput, err),
+            Error::InvalidUtf8 => io::Error::new(io::ErrorKind::InvalidData, err),
+            Error::UnsupportedOperation => io::Error::new(io::ErrorKind::Other, err),
+        }
+    }
+}
+
 impl error::Error for Error {
     fn description(&self) -> &str {
         match *self {
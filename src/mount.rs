@@ -1,7 +1,8 @@
 use libc::{c_ulong, c_int};
 use libc;
-use {Result, NixPath};
+use {Error, Result, NixPath};
 use errno::Errno;
+use sys::statvfs::{statvfs, FsFlags};
 
 libc_bitflags!(
     pub struct MsFlags: c_ulong {
@@ -98,3 +99,76 @@ pub fn umount2<P: ?Sized + NixPath>(target: &P, flags: MntFlags) -> Result<()> {
 
     Errno::result(res).map(drop)
 }
+
+const NONE: Option<&'static [u8]> = None;
+
+/// Make the mount at `target` and all mounts below it private, so that mount
+/// and unmount events no longer propagate to or from other mounts.
+///
+/// This is usually the first thing to do on `/` after creating a new mount
+/// namespace, so that mounts made inside it don't leak out.
+pub fn make_private_recursive<P: ?Sized + NixPath>(target: &P) -> Result<()> {
+    mount(NONE, target, NONE, MsFlags::MS_REC | MsFlags::MS_PRIVATE, NONE)
+}
+
+/// Make the mount at `target` and all mounts below it shared, so that mount
+/// and unmount events propagate between them and their peers.
+pub fn make_rshared<P: ?Sized + NixPath>(target: &P) -> Result<()> {
+    mount(NONE, target, NONE, MsFlags::MS_REC | MsFlags::MS_SHARED, NONE)
+}
+
+/// Make the file or directory `source` also visible at `target`.
+///
+/// If `recursive` is set, mounts below `source` are bound as well.
+pub fn bind<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(source: &P1, target: &P2,
+                                                       recursive: bool) -> Result<()> {
+    let flags = if recursive {
+        MsFlags::MS_BIND | MsFlags::MS_REC
+    } else {
+        MsFlags::MS_BIND
+    };
+
+    mount(Some(source), target, NONE, flags, NONE)
+}
+
+/// Make the file or directory `source` also visible at `target`, but
+/// read-only there.
+///
+/// Passing `MS_RDONLY` along with `MS_BIND` has no effect, so this first binds
+/// `source` and then remounts the new mount read-only, keeping its other
+/// flags.  Mounts below `source` aren't bound.  If the new mount can't be made
+/// read-only it is unmounted again, and if it doesn't end up read-only this
+/// fails with `EPERM`.
+pub fn bind_readonly<P1: ?Sized + NixPath, P2: ?Sized + NixPath>(source: &P1, target: &P2) -> Result<()> {
+    try!(bind(source, target, false));
+
+    let res = remount_readonly(target);
+    if res.is_err() {
+        let _ = umount2(target, MntFlags::MNT_DETACH);
+    }
+
+    res
+}
+
+fn remount_readonly<P: ?Sized + NixPath>(target: &P) -> Result<()> {
+    // Unprivileged users can't clear the flags of mounts inherited from a
+    // more privileged mount namespace, so keep them.
+    let fs_flags = try!(statvfs(target)).flags();
+    let mut flags = MsFlags::MS_REMOUNT | MsFlags::MS_BIND | MsFlags::MS_RDONLY;
+    for &(fs_flag, flag) in &[(FsFlags::ST_NOSUID, MsFlags::MS_NOSUID),
+                              (FsFlags::ST_NODEV, MsFlags::MS_NODEV),
+                              (FsFlags::ST_NOEXEC, MsFlags::MS_NOEXEC),
+                              (FsFlags::ST_NOATIME, MsFlags::MS_NOATIME),
+                              (FsFlags::ST_NODIRATIME, MsFlags::MS_NODIRATIME)] {
+        if fs_flags.contains(fs_flag) {
+            flags.insert(flag);
+        }
+    }
+    try!(mount(NONE, target, NONE, flags, NONE));
+
+    if !try!(statvfs(target)).flags().contains(FsFlags::ST_RDONLY) {
+        return Err(Error::Sys(Errno::EPERM));
+    }
+
+    Ok(())
+}
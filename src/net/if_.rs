@@ -3,8 +3,9 @@
 //! Uses Linux and/or POSIX functions to resolve interface names like "eth0"
 //! or "socan1" into device numbers.
 
+use std::ffi::CStr;
 use libc;
-use libc::c_uint;
+use libc::{c_char, c_uint};
 use {Result, Error, NixPath};
 
 /// Resolve an interface into a interface number.
@@ -18,6 +19,149 @@ pub fn if_nametoindex<P: ?Sized + NixPath>(name: &P) -> Result<c_uint> {
     }
 }
 
+/// Resolve an interface number into the name of the interface.
+pub fn if_indextoname(index: c_uint) -> Result<String> {
+    let mut buf = [0 as c_char; libc::IF_NAMESIZE];
+    let ptr = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+
+    if ptr.is_null() {
+        Err(Error::last())
+    } else {
+        let name = unsafe { CStr::from_ptr(buf.as_ptr()) };
+        name.to_str().map(|s| s.to_owned()).map_err(|_| Error::InvalidUtf8)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod ifreq {
+    use std::{mem, ptr};
+    use std::os::unix::io::RawFd;
+    use libc::{self, c_char, c_int, c_short};
+    use {Error, Result, NixPath};
+    use errno::Errno;
+    use sys::socket::{socket, AddressFamily, SockFlag, SockType};
+    use unistd::close;
+    use super::InterfaceFlags;
+
+    mod ffi {
+        use libc;
+
+        ioctl_read_bad!(siocgifmtu, libc::SIOCGIFMTU, libc::ifreq);
+        ioctl_read_bad!(siocgifhwaddr, libc::SIOCGIFHWADDR, libc::ifreq);
+        ioctl_read_bad!(siocgifflags, libc::SIOCGIFFLAGS, libc::ifreq);
+        ioctl_write_ptr_bad!(siocsifflags, libc::SIOCSIFFLAGS, libc::ifreq);
+    }
+
+    /// Create an `ifreq` for the interface `name`.
+    ///
+    /// Fails with `EINVAL` if `name` is too long to be an interface name.
+    fn new_ifreq<P: ?Sized + NixPath>(name: &P) -> Result<libc::ifreq> {
+        // The kernel would silently truncate longer names, which might then
+        // refer to another interface.
+        if name.len() >= libc::IFNAMSIZ {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+
+        let mut ifr: libc::ifreq = unsafe { mem::zeroed() };
+        try!(name.with_nix_path(|cstr| unsafe {
+            ptr::copy_nonoverlapping(cstr.as_ptr(), ifr.ifr_name.as_mut_ptr(), name.len());
+        }));
+
+        Ok(ifr)
+    }
+
+    /// Call `f` with a socket to perform an interface ioctl on.
+    fn with_ifreq_socket<F>(f: F) -> Result<()>
+        where F: FnOnce(RawFd) -> Result<c_int>
+    {
+        let fd = try!(socket(AddressFamily::Inet, SockType::Datagram, SockFlag::SOCK_CLOEXEC, None));
+        let res = f(fd);
+        try!(close(fd));
+
+        res.map(drop)
+    }
+
+    /// Get the MTU of the interface `name`, using the `SIOCGIFMTU` ioctl.
+    ///
+    /// Fails with `ENODEV` if there is no such interface, and with `EINVAL` if
+    /// `name` is too long to be an interface name.
+    pub fn get_interface_mtu<P: ?Sized + NixPath>(name: &P) -> Result<c_int> {
+        let mut ifr = try!(new_ifreq(name));
+        try!(with_ifreq_socket(|fd| unsafe { ffi::siocgifmtu(fd, &mut ifr) }));
+
+        Ok(unsafe { ifr.ifr_ifru.ifru_mtu })
+    }
+
+    /// Get the hardware address of the interface `name`, using the
+    /// `SIOCGIFHWADDR` ioctl.
+    ///
+    /// Fails like [`get_interface_mtu`](fn.get_interface_mtu.html).
+    pub fn get_interface_hwaddr<P: ?Sized + NixPath>(name: &P) -> Result<[u8; 6]> {
+        let mut ifr = try!(new_ifreq(name));
+        try!(with_ifreq_socket(|fd| unsafe { ffi::siocgifhwaddr(fd, &mut ifr) }));
+        let data: [c_char; 14] = unsafe { ifr.ifr_ifru.ifru_hwaddr.sa_data };
+
+        let mut hwaddr = [0u8; 6];
+        for (dst, src) in hwaddr.iter_mut().zip(data.iter()) {
+            *dst = *src as u8;
+        }
+        Ok(hwaddr)
+    }
+
+    /// Get the flags of the interface `name`, using the `SIOCGIFFLAGS` ioctl.
+    ///
+    /// Fails with `ENODEV` if there is no such interface, and with `EINVAL` if
+    /// `name` is too long to be an interface name.
+    pub fn get_flags<P: ?Sized + NixPath>(name: &P) -> Result<InterfaceFlags> {
+        let mut ifr = try!(new_ifreq(name));
+        try!(with_ifreq_socket(|fd| unsafe { ffi::siocgifflags(fd, &mut ifr) }));
+        // The flags are a `short` in the `ifreq`, don't sign-extend them.
+        let flags = unsafe { ifr.ifr_ifru.ifru_flags } as u16 as c_int;
+
+        Ok(InterfaceFlags::from_bits_truncate(flags))
+    }
+
+    /// Set the flags of the interface `name`, using the `SIOCSIFFLAGS` ioctl.
+    ///
+    /// Only the flags which fit into the `short` of an `ifreq` can be set, and
+    /// changing most of them requires `CAP_NET_ADMIN`.  Fails like
+    /// [`get_flags`](fn.get_flags.html).
+    pub fn set_flags<P: ?Sized + NixPath>(name: &P, flags: InterfaceFlags) -> Result<()> {
+        let mut ifr = try!(new_ifreq(name));
+        ifr.ifr_ifru.ifru_flags = flags.bits() as c_short;
+
+        with_ifreq_socket(|fd| unsafe { ffi::siocsifflags(fd, &ifr) })
+    }
+
+    /// Change some of the flags of the interface `name`, leaving the others as
+    /// they are.
+    fn update_flags<P: ?Sized + NixPath>(name: &P, flags: InterfaceFlags, set: bool) -> Result<()> {
+        let mut current = try!(get_flags(name));
+        current.set(flags, set);
+
+        set_flags(name, current)
+    }
+
+    /// Bring the interface `name` up by setting `IFF_UP`.
+    pub fn bring_up<P: ?Sized + NixPath>(name: &P) -> Result<()> {
+        update_flags(name, InterfaceFlags::IFF_UP, true)
+    }
+
+    /// Bring the interface `name` down by clearing `IFF_UP`.
+    pub fn bring_down<P: ?Sized + NixPath>(name: &P) -> Result<()> {
+        update_flags(name, InterfaceFlags::IFF_UP, false)
+    }
+
+    /// Enable or disable promiscuous mode on the interface `name` by setting
+    /// or clearing `IFF_PROMISC`.
+    pub fn set_promiscuous<P: ?Sized + NixPath>(name: &P, promiscuous: bool) -> Result<()> {
+        update_flags(name, InterfaceFlags::IFF_PROMISC, promiscuous)
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use self::ifreq::*;
+
 libc_bitflags!(
     /// Standard interface flags, used by `getifaddrs`
     pub struct InterfaceFlags: libc::c_int {
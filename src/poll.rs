@@ -3,7 +3,7 @@
 use sys::time::TimeSpec;
 #[cfg(any(target_os = "android", target_os = "dragonfly", target_os = "freebsd", target_os = "linux"))]
 use sys::signal::SigSet;
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::fmt;
 
 use libc;
@@ -37,6 +37,15 @@ impl PollFd {
         }
     }
 
+    /// Creates a new `PollFd` for any type owning a file descriptor, such as
+    /// a `std::net::TcpListener`.
+    ///
+    /// The `PollFd` doesn't borrow `fd`, so it must be kept open for as long
+    /// as the `PollFd` is used.
+    pub fn from_fd<T: AsRawFd>(fd: &T, events: EventFlags) -> PollFd {
+        PollFd::new(fd.as_raw_fd(), events)
+    }
+
     /// Returns the events that occured in the last call to `poll` or `ppoll`.
     pub fn revents(&self) -> Option<EventFlags> {
         EventFlags::from_bits(self.pollfd.revents)
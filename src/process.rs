@@ -0,0 +1,96 @@
+//! Process attributes shown by tools like `ps`
+use std::{cmp, ptr};
+use std::os::unix::io::AsRawFd;
+use std::sync::{Once, ONCE_INIT};
+use std::sync::atomic::{AtomicBool, Ordering, ATOMIC_BOOL_INIT};
+use libc::{self, c_ulong};
+use {Error, Result};
+use fcntl::{open_owned, OFlag};
+use sys::stat::Mode;
+use unistd::read;
+
+static INIT: Once = ONCE_INIT;
+// The original argument area, as `(start, end)`, once it has been found.
+static mut ARGS: Option<Result<(usize, usize)>> = None;
+// Serializes writes to the argument area.
+static WRITING: AtomicBool = ATOMIC_BOOL_INIT;
+
+/// Set the title of the process, as shown as its command line in
+/// `/proc/<pid>/cmdline` and by `ps`.
+///
+/// The title overwrites the memory holding the original command line
+/// arguments and is truncated to fit into it, after which the rest of the
+/// memory is filled with NUL bytes.  If the process has `CAP_SYS_RESOURCE`,
+/// the end of the command line is also moved to the end of the title with
+/// `PR_SET_MM`, so that the padding doesn't show up as empty arguments.
+///
+/// The area is found from `/proc/self/stat` on the first call.  Fails with
+/// `EINVAL` if `title` contains a NUL byte, and with
+/// `Error::UnsupportedOperation` on kernels older than 3.5, which don't
+/// report the area there.
+///
+/// Since the original arguments are overwritten, `std::env::args` returns
+/// the title afterwards, so collect the arguments before calling this.
+pub fn set_proc_title(title: &str) -> Result<()> {
+    if title.as_bytes().contains(&0) {
+        return Err(Error::invalid_argument());
+    }
+
+    INIT.call_once(|| unsafe { ARGS = Some(find_args()) });
+    let (start, end) = match unsafe { ARGS } {
+        Some(Ok(args)) => args,
+        Some(Err(e)) => return Err(e),
+        None => unreachable!(),
+    };
+
+    // Leave room for the terminating NUL, without splitting a character.
+    let mut len = cmp::min(title.len(), end - start - 1);
+    while !title.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    while WRITING.compare_and_swap(false, true, Ordering::Acquire) {}
+    unsafe {
+        let area = start as *mut u8;
+        ptr::copy_nonoverlapping(title.as_ptr(), area, len);
+        ptr::write_bytes(area.offset(len as isize), 0, end - start - len);
+        // Moving the end of the command line requires privileges, so failing
+        // to do so is fine.
+        libc::prctl(libc::PR_SET_MM, libc::PR_SET_MM_ARG_END as c_ulong,
+                    (start + len + 1) as c_ulong, 0 as c_ulong, 0 as c_ulong);
+    }
+    WRITING.store(false, Ordering::Release);
+
+    Ok(())
+}
+
+// Find the argument area from the `arg_start` and `arg_end` fields of
+// `/proc/self/stat`.
+fn find_args() -> Result<(usize, usize)> {
+    let fd = try!(open_owned("/proc/self/stat", OFlag::O_RDONLY | OFlag::O_CLOEXEC, Mode::empty()));
+    let mut stat = Vec::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        match try!(read(fd.as_raw_fd(), &mut buf)) {
+            0 => break,
+            n => stat.extend_from_slice(&buf[..n]),
+        }
+    }
+
+    // The fields following the command name, which may contain spaces and
+    // parentheses itself, start with the third field.
+    let fields = match stat.iter().rposition(|&b| b == b')') {
+        Some(i) => &stat[i + 1..],
+        None => return Err(Error::UnsupportedOperation),
+    };
+    let mut fields = fields.split(|&b| b == b' ').filter(|f| !f.is_empty()).skip(48 - 3);
+    let mut next = || {
+        fields.next()
+            .and_then(|f| ::std::str::from_utf8(f).ok())
+            .and_then(|f| f.trim().parse::<usize>().ok())
+    };
+    match (next(), next()) {
+        (Some(start), Some(end)) if start != 0 && start < end => Ok((start, end)),
+        _ => Err(Error::UnsupportedOperation),
+    }
+}
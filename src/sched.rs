@@ -1,10 +1,17 @@
-use std::mem;
-use std::os::unix::io::RawFd;
+use std::{error, fmt, mem, ptr, slice};
+use std::marker::PhantomData;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::option::Option;
 use libc::{self, c_int, c_void};
 use {Error, Result};
 use errno::Errno;
-use ::unistd::Pid;
+use fcntl::{open_owned, OFlag};
+use fd::OwnedFd;
+use sys::mman::{mmap, munmap, MapFlags, ProtFlags};
+use sys::stat::Mode;
+use sys::wait::waitpid;
+use ::unistd::{close, getgid, getuid, pipe2_owned, read, sethostname, sysconf, write, Pid,
+               SysconfVar};
 
 // For some functions taking with a parameter of type CloneFlags,
 // only a subset of these flags have an effect.
@@ -85,6 +92,76 @@ pub fn sched_setaffinity(pid: Pid, cpuset: &CpuSet) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// Get the set of CPUs thread `pid` may run on, or the calling thread's if
+/// `pid` is zero.
+pub fn sched_getaffinity(pid: Pid) -> Result<CpuSet> {
+    let mut cpuset = CpuSet::new();
+    let res = unsafe {
+        libc::sched_getaffinity(pid.into(),
+                                mem::size_of::<CpuSet>() as libc::size_t,
+                                &mut cpuset.cpu_set)
+    };
+
+    Errno::result(res).map(|_| cpuset)
+}
+
+/// Restrict thread `tid` to the CPUs in `cpuset`.
+///
+/// Affinity applies to single threads, so for a thread other than the
+/// calling one, `tid` is the ID it gets from
+/// [`gettid`](../unistd/fn.gettid.html).  Threads it creates afterwards
+/// inherit the affinity.
+pub fn set_thread_affinity(tid: Pid, cpuset: &CpuSet) -> Result<()> {
+    sched_setaffinity(tid, cpuset)
+}
+
+/// Get the CPU the calling thread is running on.
+///
+/// The thread may have been moved to another CPU by the time this returns,
+/// unless its affinity only allows this one.
+pub fn sched_getcpu() -> Result<usize> {
+    let res = unsafe { libc::sched_getcpu() };
+
+    Errno::result(res).map(|cpu| cpu as usize)
+}
+
+/// Restrict the calling thread to run on CPU `cpu` only, until the returned
+/// guard is dropped.
+///
+/// Dropping the guard restores the affinity the thread had before, ignoring
+/// errors.  Since affinity applies to single threads, the guard can't be
+/// sent to another thread.
+pub fn pin_to_cpu(cpu: usize) -> Result<AffinityGuard> {
+    let this = Pid::from_raw(0);
+    let original = try!(sched_getaffinity(this));
+    let mut cpuset = CpuSet::new();
+    try!(cpuset.set(cpu));
+    try!(sched_setaffinity(this, &cpuset));
+
+    Ok(AffinityGuard { original: original, _not_send: PhantomData })
+}
+
+/// Restores the affinity of the calling thread when dropped, as returned by
+/// [`pin_to_cpu`](fn.pin_to_cpu.html).
+#[allow(missing_debug_implementations)]
+pub struct AffinityGuard {
+    original: CpuSet,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl AffinityGuard {
+    /// The affinity that will be restored.
+    pub fn original(&self) -> &CpuSet {
+        &self.original
+    }
+}
+
+impl Drop for AffinityGuard {
+    fn drop(&mut self) {
+        let _ = sched_setaffinity(Pid::from_raw(0), &self.original);
+    }
+}
+
 pub fn clone(mut cb: CloneCb,
              stack: &mut [u8],
              flags: CloneFlags,
@@ -108,6 +185,106 @@ pub fn clone(mut cb: CloneCb,
     Errno::result(res).map(Pid::from_raw)
 }
 
+/// A stack for a child created with [`clone`](fn.clone.html), with a guard
+/// page below it so that a child overflowing it dies with `SIGSEGV` rather
+/// than overwriting whatever memory lies below.
+///
+/// Dropping the stack unmaps it.  Starting a child on it with
+/// [`spawn`](#method.spawn) turns it into a [`SpawnedStack`], which can no
+/// longer be accessed or reused while the child may still be running.
+///
+/// [`SpawnedStack`]: struct.SpawnedStack.html
+#[allow(missing_debug_implementations)]
+pub struct CloneStack {
+    // The start of the mapping, including the guard page
+    base: *mut c_void,
+    len: usize,
+    page_size: usize,
+}
+
+impl CloneStack {
+    /// Map a stack of `size` bytes, rounded up to the page size, and a guard
+    /// page below it.
+    pub fn new(size: usize) -> Result<CloneStack> {
+        let page_size = match try!(sysconf(SysconfVar::PAGE_SIZE)) {
+            Some(page_size) => page_size as usize,
+            None => return Err(Error::Sys(Errno::EINVAL)),
+        };
+        let size = match size.checked_add(page_size - 1) {
+            Some(size) if size >= page_size => size / page_size * page_size,
+            _ => return Err(Error::Sys(Errno::EINVAL)),
+        };
+        let len = match size.checked_add(page_size) {
+            Some(len) => len,
+            None => return Err(Error::Sys(Errno::ENOMEM)),
+        };
+
+        let base = try!(unsafe {
+            mmap(ptr::null_mut(), len, ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+                 MapFlags::MAP_PRIVATE | MapFlags::MAP_ANONYMOUS | MapFlags::MAP_STACK, -1, 0)
+        });
+        // The stack grows down, towards the guard page at the bottom
+        let res = unsafe { libc::mprotect(base, page_size, libc::PROT_NONE) };
+        if let Err(e) = Errno::result(res) {
+            let _ = unsafe { munmap(base, len) };
+            return Err(e);
+        }
+
+        Ok(CloneStack { base: base, len: len, page_size: page_size })
+    }
+
+    /// The usable part of the stack, above the guard page.
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe {
+            slice::from_raw_parts_mut((self.base as *mut u8).offset(self.page_size as isize),
+                                      self.len - self.page_size)
+        }
+    }
+
+    /// Start a child running `cb` on this stack, like [`clone`](fn.clone.html).
+    ///
+    /// Returns the child's pid and the stack, which is leaked if dropped from
+    /// now on.  If the child can't be started, the stack is unmapped.
+    pub fn spawn(mut self, cb: CloneCb, flags: CloneFlags, signal: Option<c_int>)
+        -> Result<(Pid, SpawnedStack)>
+    {
+        let pid = try!(clone(cb, self.as_mut_slice(), flags, signal));
+        let stack = SpawnedStack { base: self.base, len: self.len };
+        mem::forget(self);
+
+        Ok((pid, stack))
+    }
+}
+
+impl Drop for CloneStack {
+    fn drop(&mut self) {
+        let _ = unsafe { munmap(self.base, self.len) };
+    }
+}
+
+/// A [`CloneStack`](struct.CloneStack.html) that a child was started on.
+///
+/// The child may still be running on the stack, so dropping it leaks the
+/// stack instead of unmapping it.  Once the child has been reaped with
+/// [`waitpid`](../sys/wait/fn.waitpid.html), unmap the stack with
+/// [`release`](#method.release).
+#[allow(missing_debug_implementations)]
+pub struct SpawnedStack {
+    base: *mut c_void,
+    len: usize,
+}
+
+impl SpawnedStack {
+    /// Unmap the stack.
+    ///
+    /// Unsafe because no child started on it may still be running.  If the
+    /// child shares the memory of the caller, by `CLONE_VM`, the stack memory
+    /// may be reused for something else while the child still writes to it.
+    pub unsafe fn release(self) -> Result<()> {
+        munmap(self.base, self.len)
+    }
+}
+
 pub fn unshare(flags: CloneFlags) -> Result<()> {
     let res = unsafe { libc::unshare(flags.bits()) };
 
@@ -119,3 +296,243 @@ pub fn setns(fd: RawFd, nstype: CloneFlags) -> Result<()> {
 
     Errno::result(res).map(drop)
 }
+
+/// Spawns a child process in new namespaces, taking care of the setup
+/// commonly needed for containers.
+///
+/// ```no_run
+/// use nix::sched::NamespaceBuilder;
+///
+/// let child = NamespaceBuilder::new()
+///     .user(true)
+///     .mount()
+///     .uts(Some("container"))
+///     .spawn(Box::new(|| 0), 1024 * 1024)
+///     .expect("failed to spawn the child");
+/// ```
+#[derive(Clone, Debug)]
+pub struct NamespaceBuilder {
+    flags: CloneFlags,
+    map_root: bool,
+    hostname: Option<String>,
+}
+
+impl NamespaceBuilder {
+    /// Create a builder that doesn't create any new namespace.
+    pub fn new() -> NamespaceBuilder {
+        NamespaceBuilder {
+            flags: CloneFlags::empty(),
+            map_root: false,
+            hostname: None,
+        }
+    }
+
+    /// Create a new user namespace.
+    ///
+    /// The child has all capabilities in the new namespace, including over
+    /// the other namespaces it creates.  If `map_root_to_current` is set, root
+    /// in the new namespace is mapped to the current user and group, and
+    /// `setgroups` is denied, as unprivileged processes are required to.
+    /// Otherwise the child runs as the overflow user until a mapping is
+    /// written.
+    pub fn user(mut self, map_root_to_current: bool) -> NamespaceBuilder {
+        self.flags.insert(CloneFlags::CLONE_NEWUSER);
+        self.map_root = map_root_to_current;
+        self
+    }
+
+    /// Create a new mount namespace.
+    pub fn mount(mut self) -> NamespaceBuilder {
+        self.flags.insert(CloneFlags::CLONE_NEWNS);
+        self
+    }
+
+    /// Create a new network namespace.
+    pub fn net(mut self) -> NamespaceBuilder {
+        self.flags.insert(CloneFlags::CLONE_NEWNET);
+        self
+    }
+
+    /// Create a new PID namespace, in which the child has PID 1.
+    pub fn pid(mut self) -> NamespaceBuilder {
+        self.flags.insert(CloneFlags::CLONE_NEWPID);
+        self
+    }
+
+    /// Create a new UTS namespace, setting its hostname to `hostname` if
+    /// given.
+    pub fn uts(mut self, hostname: Option<&str>) -> NamespaceBuilder {
+        self.flags.insert(CloneFlags::CLONE_NEWUTS);
+        self.hostname = hostname.map(|h| h.to_owned());
+        self
+    }
+
+    /// The flags the child will be cloned with, apart from the exit signal.
+    pub fn flags(&self) -> CloneFlags {
+        self.flags
+    }
+
+    /// Clone a child process into the new namespaces and run `cb` in it once
+    /// they have been set up, on a new stack of `stack_size` bytes.
+    ///
+    /// The parent writes the user and group mappings of the child, after
+    /// which the child sets its hostname.  `spawn` only returns once the
+    /// child is set up; if a step fails, the child is reaped and the error
+    /// says which step it was.  The child's exit status is that returned by
+    /// `cb`, and `SIGCHLD` is sent to the parent when it terminates.
+    ///
+    /// The restrictions of [`fork`](../unistd/fn.fork.html) on multithreaded
+    /// programs apply to `cb`.
+    pub fn spawn(&self, mut cb: CloneCb, stack_size: usize) -> ::std::result::Result<Pid, NamespaceError> {
+        let (go_r, go_w) = try!(step(NamespaceStep::Pipe, pipe2_owned(OFlag::O_CLOEXEC)));
+        let (err_r, err_w) = try!(step(NamespaceStep::Pipe, pipe2_owned(OFlag::O_CLOEXEC)));
+        let (go_r_fd, go_w_fd) = (go_r.as_raw_fd(), go_w.as_raw_fd());
+        let (err_r_fd, err_w_fd) = (err_r.as_raw_fd(), err_w.as_raw_fd());
+        let hostname = self.hostname.clone();
+
+        let child: CloneCb = Box::new(move || {
+            // The child has its own copy of the file descriptors, of which it
+            // only needs its ends of the pipes.
+            let _ = close(go_w_fd);
+            let _ = close(err_r_fd);
+
+            // Wait for the parent to write the mappings.  It closes the pipe
+            // without writing to it if that failed.
+            let mut buf = [0u8; 1];
+            match read(go_r_fd, &mut buf) {
+                Ok(1) => (),
+                _ => return 1,
+            }
+            let _ = close(go_r_fd);
+
+            if let Some(ref hostname) = hostname {
+                if let Err(e) = sethostname(hostname) {
+                    // Setting the hostname is the only step done by the child,
+                    // so only the errno is sent back.
+                    let errno = match e {
+                        Error::Sys(errno) => errno as i32,
+                        _ => libc::EINVAL,
+                    };
+                    let buf: [u8; 4] = unsafe { mem::transmute(errno) };
+                    let _ = write(err_w_fd, &buf);
+                    return 1;
+                }
+            }
+            let _ = close(err_w_fd);
+
+            cb()
+        });
+
+        let mut stack = vec![0u8; stack_size];
+        let pid = try!(step(NamespaceStep::Clone,
+                            clone(child, &mut stack, self.flags, Some(libc::SIGCHLD))));
+        drop(go_r);
+        drop(err_w);
+
+        match self.setup_child(pid, go_w, &err_r) {
+            Ok(()) => Ok(pid),
+            Err(e) => {
+                let _ = waitpid(pid, None);
+                Err(e)
+            }
+        }
+    }
+
+    fn setup_child(&self, pid: Pid, go_w: OwnedFd, err_r: &OwnedFd) -> ::std::result::Result<(), NamespaceError> {
+        if self.map_root {
+            try!(step(NamespaceStep::UidMap,
+                      write_proc_file(pid, "uid_map", &format!("0 {} 1\n", getuid()))));
+            // Unprivileged processes may only write gid_map once setgroups is
+            // denied.
+            try!(step(NamespaceStep::SetGroups, write_proc_file(pid, "setgroups", "deny")));
+            try!(step(NamespaceStep::GidMap,
+                      write_proc_file(pid, "gid_map", &format!("0 {} 1\n", getgid()))));
+        }
+
+        try!(step(NamespaceStep::Sync, write(go_w.as_raw_fd(), &[0])));
+        drop(go_w);
+
+        // The child closes its end of the error pipe once it is set up, or
+        // writes an errno to it first if setting it up failed.
+        let mut buf = [0u8; 4];
+        match try!(step(NamespaceStep::Sync, read(err_r.as_raw_fd(), &mut buf))) {
+            0 => Ok(()),
+            4 => {
+                let errno: i32 = unsafe { mem::transmute(buf) };
+                step(NamespaceStep::Hostname, Err(Error::Sys(Errno::from_i32(errno))))
+            }
+            _ => step(NamespaceStep::Sync, Err(Error::Sys(Errno::EIO))),
+        }
+    }
+}
+
+fn step<T>(step: NamespaceStep, res: Result<T>) -> ::std::result::Result<T, NamespaceError> {
+    res.map_err(|error| NamespaceError { step: step, error: error })
+}
+
+fn write_proc_file(pid: Pid, name: &str, contents: &str) -> Result<()> {
+    let path = format!("/proc/{}/{}", pid, name);
+    let fd = try!(open_owned(path.as_str(), OFlag::O_WRONLY | OFlag::O_CLOEXEC, Mode::empty()));
+    let len = try!(write(fd.as_raw_fd(), contents.as_bytes()));
+    if len != contents.len() {
+        return Err(Error::Sys(Errno::EIO));
+    }
+
+    Ok(())
+}
+
+/// The step of [`NamespaceBuilder::spawn`](struct.NamespaceBuilder.html#method.spawn)
+/// that failed
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NamespaceStep {
+    /// Creating the pipes used to synchronize with the child
+    Pipe,
+    /// Cloning the child into its new namespaces
+    Clone,
+    /// Writing the child's `uid_map`
+    UidMap,
+    /// Denying `setgroups` to the child
+    SetGroups,
+    /// Writing the child's `gid_map`
+    GidMap,
+    /// Letting the child proceed and waiting for it to be set up
+    Sync,
+    /// Setting the hostname in the child
+    Hostname,
+}
+
+/// Error returned by [`NamespaceBuilder::spawn`](struct.NamespaceBuilder.html#method.spawn)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NamespaceError {
+    /// The step that failed
+    pub step: NamespaceStep,
+    /// Why it failed
+    pub error: Error,
+}
+
+impl fmt::Display for NamespaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let step = match self.step {
+            NamespaceStep::Pipe => "creating the synchronization pipes",
+            NamespaceStep::Clone => "clone",
+            NamespaceStep::UidMap => "writing uid_map",
+            NamespaceStep::SetGroups => "writing setgroups",
+            NamespaceStep::GidMap => "writing gid_map",
+            NamespaceStep::Sync => "synchronizing with the child",
+            NamespaceStep::Hostname => "setting the hostname",
+        };
+        write!(f, "{} failed: {}", step, self.error)
+    }
+}
+
+impl error::Error for NamespaceError {
+    fn description(&self) -> &str {
+        "setting up namespaces failed"
+    }
+}
+
+impl From<NamespaceError> for Error {
+    fn from(err: NamespaceError) -> Error {
+        err.error
+    }
+}
@@ -0,0 +1,277 @@
+//! Create new processes with `posix_spawn`
+//!
+//! Unlike `fork` followed by `exec`, `posix_spawn` doesn't need to duplicate
+//! the address space of the calling process and is safe to use from
+//! multithreaded programs.  What happens in the child between its creation
+//! and the `exec` is described by a [`PosixSpawnFileActions`] and a
+//! [`PosixSpawnAttr`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::ffi::CString;
+//! # use nix::spawn::{PosixSpawnAttr, PosixSpawnFileActions, posix_spawnp};
+//! # use nix::sys::wait::waitpid;
+//! let file_actions = PosixSpawnFileActions::new().unwrap();
+//! let attr = PosixSpawnAttr::new().unwrap();
+//! let args = [CString::new("true").unwrap()];
+//! let pid = posix_spawnp(&args[0], &file_actions, &attr, &args, &[]).unwrap();
+//! waitpid(pid, None).unwrap();
+//! ```
+//!
+//! [`PosixSpawnFileActions`]: struct.PosixSpawnFileActions.html
+//! [`PosixSpawnAttr`]: struct.PosixSpawnAttr.html
+use std::{mem, ptr};
+use std::ffi::{CStr, CString};
+use std::os::unix::io::RawFd;
+use libc::{self, c_char, c_int, c_short};
+use {Error, Result, NixPath};
+use errno::Errno;
+use fcntl::OFlag;
+use sys::signal::SigSet;
+use sys::stat::Mode;
+use unistd::Pid;
+
+/// The `posix_spawn*` functions return an error number instead of setting
+/// `errno`.
+fn spawn_result(res: c_int) -> Result<()> {
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(Error::Sys(Errno::from_i32(res)))
+    }
+}
+
+libc_bitflags!{
+    /// Flags selecting which attributes of a [`PosixSpawnAttr`] take effect.
+    ///
+    /// [`PosixSpawnAttr`]: struct.PosixSpawnAttr.html
+    pub struct PosixSpawnFlags: c_int {
+        /// Reset the effective user and group IDs to the real ones.
+        POSIX_SPAWN_RESETIDS;
+        /// Put the child into the process group set with
+        /// [`set_pgroup`](struct.PosixSpawnAttr.html#method.set_pgroup).
+        POSIX_SPAWN_SETPGROUP;
+        /// Reset the signals set with
+        /// [`set_sigdefault`](struct.PosixSpawnAttr.html#method.set_sigdefault)
+        /// to their default dispositions.
+        POSIX_SPAWN_SETSIGDEF;
+        /// Set the signal mask of the child to the one set with
+        /// [`set_sigmask`](struct.PosixSpawnAttr.html#method.set_sigmask).
+        POSIX_SPAWN_SETSIGMASK;
+        /// Set the scheduling parameters set with
+        /// [`set_schedparam`](struct.PosixSpawnAttr.html#method.set_schedparam).
+        POSIX_SPAWN_SETSCHEDPARAM;
+        /// Set the scheduling policy set with
+        /// [`set_schedpolicy`](struct.PosixSpawnAttr.html#method.set_schedpolicy).
+        POSIX_SPAWN_SETSCHEDULER;
+        /// Make the child the leader of a new session.
+        #[cfg(target_os = "linux")]
+        POSIX_SPAWN_SETSID;
+    }
+}
+
+/// Attributes of a process created by [`posix_spawn`](fn.posix_spawn.html).
+///
+/// Wraps a `posix_spawnattr_t`, which is destroyed when this is dropped.
+#[allow(missing_debug_implementations)]
+pub struct PosixSpawnAttr {
+    attr: libc::posix_spawnattr_t,
+}
+
+impl PosixSpawnAttr {
+    /// Create a new set of attributes with the default values.
+    pub fn new() -> Result<PosixSpawnAttr> {
+        let mut attr: libc::posix_spawnattr_t = unsafe { mem::uninitialized() };
+        try!(spawn_result(unsafe { libc::posix_spawnattr_init(&mut attr) }));
+
+        Ok(PosixSpawnAttr { attr: attr })
+    }
+
+    /// Get the flags selecting which attributes take effect.
+    pub fn flags(&self) -> Result<PosixSpawnFlags> {
+        let mut flags: c_short = 0;
+        try!(spawn_result(unsafe {
+            libc::posix_spawnattr_getflags(&self.attr, &mut flags)
+        }));
+
+        Ok(PosixSpawnFlags::from_bits_truncate(flags as c_int))
+    }
+
+    /// Set the flags selecting which attributes take effect.
+    pub fn set_flags(&mut self, flags: PosixSpawnFlags) -> Result<()> {
+        spawn_result(unsafe {
+            libc::posix_spawnattr_setflags(&mut self.attr, flags.bits() as c_short)
+        })
+    }
+
+    /// Set the process group the child joins if `POSIX_SPAWN_SETPGROUP` is
+    /// set.  A `pgroup` of zero creates a new group led by the child.
+    pub fn set_pgroup(&mut self, pgroup: Pid) -> Result<()> {
+        spawn_result(unsafe {
+            libc::posix_spawnattr_setpgroup(&mut self.attr, pgroup.into())
+        })
+    }
+
+    /// Set the signal mask of the child if `POSIX_SPAWN_SETSIGMASK` is set.
+    pub fn set_sigmask(&mut self, sigmask: &SigSet) -> Result<()> {
+        spawn_result(unsafe {
+            libc::posix_spawnattr_setsigmask(&mut self.attr, sigmask.as_ref())
+        })
+    }
+
+    /// Set the signals that are reset to their default dispositions in the
+    /// child if `POSIX_SPAWN_SETSIGDEF` is set.
+    pub fn set_sigdefault(&mut self, sigdefault: &SigSet) -> Result<()> {
+        spawn_result(unsafe {
+            libc::posix_spawnattr_setsigdefault(&mut self.attr, sigdefault.as_ref())
+        })
+    }
+
+    /// Set the scheduling policy of the child if `POSIX_SPAWN_SETSCHEDULER`
+    /// is set.
+    pub fn set_schedpolicy(&mut self, policy: c_int) -> Result<()> {
+        spawn_result(unsafe {
+            libc::posix_spawnattr_setschedpolicy(&mut self.attr, policy)
+        })
+    }
+
+    /// Set the scheduling parameters of the child if
+    /// `POSIX_SPAWN_SETSCHEDPARAM` is set.
+    pub fn set_schedparam(&mut self, param: &libc::sched_param) -> Result<()> {
+        spawn_result(unsafe {
+            libc::posix_spawnattr_setschedparam(&mut self.attr, param)
+        })
+    }
+}
+
+impl Drop for PosixSpawnAttr {
+    fn drop(&mut self) {
+        unsafe { libc::posix_spawnattr_destroy(&mut self.attr) };
+    }
+}
+
+/// File descriptor operations performed in the child created by
+/// [`posix_spawn`](fn.posix_spawn.html), in the order they were added.
+///
+/// Wraps a `posix_spawn_file_actions_t`, which is destroyed when this is
+/// dropped.
+#[allow(missing_debug_implementations)]
+pub struct PosixSpawnFileActions {
+    actions: libc::posix_spawn_file_actions_t,
+}
+
+impl PosixSpawnFileActions {
+    /// Create an empty list of file actions.
+    pub fn new() -> Result<PosixSpawnFileActions> {
+        let mut actions: libc::posix_spawn_file_actions_t = unsafe { mem::uninitialized() };
+        try!(spawn_result(unsafe { libc::posix_spawn_file_actions_init(&mut actions) }));
+
+        Ok(PosixSpawnFileActions { actions: actions })
+    }
+
+    /// Open `path` as file descriptor `fd` in the child, as if by `open(2)`.
+    pub fn add_open<P: ?Sized + NixPath>(&mut self, fd: RawFd, path: &P,
+                                         oflag: OFlag, mode: Mode) -> Result<()> {
+        // The path is copied, so it needn't outlive this call.
+        let res = try!(path.with_nix_path(|cstr| unsafe {
+            libc::posix_spawn_file_actions_addopen(&mut self.actions, fd, cstr.as_ptr(),
+                                                   oflag.bits(), mode.bits())
+        }));
+
+        spawn_result(res)
+    }
+
+    /// Duplicate `fd` onto `newfd` in the child, as if by `dup2(2)`.
+    pub fn add_dup2(&mut self, fd: RawFd, newfd: RawFd) -> Result<()> {
+        spawn_result(unsafe {
+            libc::posix_spawn_file_actions_adddup2(&mut self.actions, fd, newfd)
+        })
+    }
+
+    /// Close `fd` in the child.
+    pub fn add_close(&mut self, fd: RawFd) -> Result<()> {
+        spawn_result(unsafe {
+            libc::posix_spawn_file_actions_addclose(&mut self.actions, fd)
+        })
+    }
+
+    /// Change the working directory of the child to `path`, as if by
+    /// `chdir(2)`.
+    ///
+    /// Relative paths of later `add_open` actions and a relative program
+    /// path are resolved against the new directory.  Fails with `ENOSYS` if
+    /// the C library is older than glibc 2.29, which added
+    /// `posix_spawn_file_actions_addchdir_np`.
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    pub fn add_chdir<P: ?Sized + NixPath>(&mut self, path: &P) -> Result<()> {
+        // Look the function up at runtime so that linking doesn't fail with
+        // older versions of glibc.
+        let sym = unsafe {
+            libc::dlsym(libc::RTLD_DEFAULT,
+                        b"posix_spawn_file_actions_addchdir_np\0".as_ptr() as *const c_char)
+        };
+        if sym.is_null() {
+            return Err(Error::Sys(Errno::ENOSYS));
+        }
+        let addchdir: AddChdirFn = unsafe { mem::transmute(sym) };
+
+        // The path is copied, so it needn't outlive this call.
+        let res = try!(path.with_nix_path(|cstr| unsafe {
+            addchdir(&mut self.actions, cstr.as_ptr())
+        }));
+
+        spawn_result(res)
+    }
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type AddChdirFn = unsafe extern "C" fn(*mut libc::posix_spawn_file_actions_t, *const c_char)
+                                       -> c_int;
+
+impl Drop for PosixSpawnFileActions {
+    fn drop(&mut self) {
+        unsafe { libc::posix_spawn_file_actions_destroy(&mut self.actions) };
+    }
+}
+
+fn to_exec_array(args: &[CString]) -> Vec<*mut c_char> {
+    let mut args_p: Vec<*mut c_char> = args.iter().map(|s| s.as_ptr() as *mut c_char).collect();
+    args_p.push(ptr::null_mut());
+    args_p
+}
+
+/// Create a child process executing the program at `path` (see
+/// [posix_spawn(3)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/posix_spawn.html)).
+///
+/// `args` and `env` become the argument list and environment of the new
+/// program, like for [`execve`](../unistd/fn.execve.html).
+pub fn posix_spawn(path: &CStr, file_actions: &PosixSpawnFileActions, attr: &PosixSpawnAttr,
+                   args: &[CString], env: &[CString]) -> Result<Pid> {
+    let args_p = to_exec_array(args);
+    let env_p = to_exec_array(env);
+    let mut pid: libc::pid_t = 0;
+
+    try!(spawn_result(unsafe {
+        libc::posix_spawn(&mut pid, path.as_ptr(), &file_actions.actions, &attr.attr,
+                          args_p.as_ptr(), env_p.as_ptr())
+    }));
+
+    Ok(Pid::from_raw(pid))
+}
+
+/// Like [`posix_spawn`](fn.posix_spawn.html), but search `PATH` for `file`
+/// if it doesn't contain a slash.
+pub fn posix_spawnp(file: &CStr, file_actions: &PosixSpawnFileActions, attr: &PosixSpawnAttr,
+                    args: &[CString], env: &[CString]) -> Result<Pid> {
+    let args_p = to_exec_array(args);
+    let env_p = to_exec_array(env);
+    let mut pid: libc::pid_t = 0;
+
+    try!(spawn_result(unsafe {
+        libc::posix_spawnp(&mut pid, file.as_ptr(), &file_actions.actions, &attr.attr,
+                           args_p.as_ptr(), env_p.as_ptr())
+    }));
+
+    Ok(Pid::from_raw(pid))
+}
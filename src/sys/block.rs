@@ -0,0 +1,56 @@
+//! Query and control block devices.
+//!
+//! All of these fail with `ENOTTY` if the file descriptor doesn't refer to a
+//! block device.
+use std::os::unix::io::RawFd;
+use libc::c_int;
+use Result;
+
+const BLKIO: u8 = 0x12;
+
+mod ffi {
+    use libc::c_int;
+    use super::BLKIO;
+
+    // BLKGETSIZE64 is encoded with the size of a `size_t`, but the kernel
+    // always writes a 64-bit value.
+    ioctl_read_bad!(blkgetsize64, request_code_read!(BLKIO, 114, ::std::mem::size_of::<usize>()), u64);
+    ioctl_read_bad!(blksszget, request_code_none!(BLKIO, 104), c_int);
+    ioctl_none!(blkflsbuf, BLKIO, 97);
+    ioctl_write_ptr_bad!(blkdiscard, request_code_none!(BLKIO, 119), [u64; 2]);
+}
+
+/// Get the size of the block device in bytes (see `BLKGETSIZE64`).
+pub fn blk_getsize64(fd: RawFd) -> Result<u64> {
+    let mut size: u64 = 0;
+    try!(unsafe { ffi::blkgetsize64(fd, &mut size) });
+
+    Ok(size)
+}
+
+/// Get the logical sector size of the block device in bytes (see
+/// `BLKSSZGET`).
+pub fn blk_sszget(fd: RawFd) -> Result<u32> {
+    let mut size: c_int = 0;
+    try!(unsafe { ffi::blksszget(fd, &mut size) });
+
+    Ok(size as u32)
+}
+
+/// Flush the buffer cache of the block device (see `BLKFLSBUF`).
+///
+/// Requires the `CAP_SYS_ADMIN` capability.
+pub fn blk_flsbuf(fd: RawFd) -> Result<()> {
+    unsafe { ffi::blkflsbuf(fd) }.map(drop)
+}
+
+/// Discard the `len` bytes of the block device starting at `offset`, for
+/// example to trim them on an SSD (see `BLKDISCARD`).
+///
+/// Both must be multiples of the logical sector size.  The device has to be
+/// open for writing, and devices without discard support fail with
+/// `EOPNOTSUPP`.
+pub fn blk_discard(fd: RawFd, offset: u64, len: u64) -> Result<()> {
+    let range = [offset, len];
+    unsafe { ffi::blkdiscard(fd, &range) }.map(drop)
+}
@@ -1,10 +1,11 @@
 use Result;
 use errno::Errno;
 use libc::{self, c_int};
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
 use std::ptr;
 use std::mem;
 use ::Error;
+use fd::OwnedFd;
 
 libc_bitflags!(
     pub struct EpollFlags: c_int {
@@ -81,6 +82,12 @@ pub fn epoll_create1(flags: EpollCreateFlags) -> Result<RawFd> {
     Errno::result(res)
 }
 
+/// Like `epoll_create1`, but return an [`OwnedFd`](../../fd/struct.OwnedFd.html).
+#[inline]
+pub fn epoll_create1_owned(flags: EpollCreateFlags) -> Result<OwnedFd> {
+    epoll_create1(flags).map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
 #[inline]
 pub fn epoll_ctl<'a, T>(epfd: RawFd, op: EpollOp, fd: RawFd, event: T) -> Result<()>
     where T: Into<Option<&'a mut EpollEvent>>
@@ -100,6 +107,16 @@ pub fn epoll_ctl<'a, T>(epfd: RawFd, op: EpollOp, fd: RawFd, event: T) -> Result
     }
 }
 
+/// Like [`epoll_ctl`](fn.epoll_ctl.html), but for any type owning a file
+/// descriptor, such as a `std::net::TcpListener`.
+#[inline]
+pub fn epoll_ctl_fd<'a, F, T>(epfd: RawFd, op: EpollOp, fd: &F, event: T) -> Result<()>
+    where F: AsRawFd,
+          T: Into<Option<&'a mut EpollEvent>>
+{
+    epoll_ctl(epfd, op, fd.as_raw_fd(), event)
+}
+
 #[inline]
 pub fn epoll_wait(epfd: RawFd, events: &mut [EpollEvent], timeout_ms: isize) -> Result<usize> {
     let res = unsafe {
@@ -108,3 +125,59 @@ pub fn epoll_wait(epfd: RawFd, events: &mut [EpollEvent], timeout_ms: isize) ->
 
     Errno::result(res).map(|r| r as usize)
 }
+
+/// An epoll instance, which is closed when dropped.
+///
+/// Its methods wrap [`epoll_ctl`](fn.epoll_ctl.html) and
+/// [`epoll_wait`](fn.epoll_wait.html) on the instance.  Errors are passed on
+/// as is, so that adding a file descriptor twice fails with `EEXIST`, while
+/// modifying or deleting one that was never added fails with `ENOENT`.
+#[derive(Debug)]
+pub struct Epoll(OwnedFd);
+
+impl Epoll {
+    /// Create a new epoll instance.
+    pub fn new(flags: EpollCreateFlags) -> Result<Epoll> {
+        epoll_create1_owned(flags).map(Epoll)
+    }
+
+    /// Start watching `fd` for the events in `event`, reporting them with the
+    /// data of `event`.
+    pub fn add(&self, fd: RawFd, mut event: EpollEvent) -> Result<()> {
+        epoll_ctl(self.0.as_raw_fd(), EpollOp::EpollCtlAdd, fd, &mut event)
+    }
+
+    /// Change the events and data `fd` is watched with.
+    pub fn modify(&self, fd: RawFd, mut event: EpollEvent) -> Result<()> {
+        epoll_ctl(self.0.as_raw_fd(), EpollOp::EpollCtlMod, fd, &mut event)
+    }
+
+    /// Re-enable `fd` after an event for it was reported, if it was added with
+    /// `EPOLLONESHOT`.
+    ///
+    /// This is the same as [`modify`](#method.modify); `event` should contain
+    /// `EPOLLONESHOT` again to keep it that way.
+    pub fn rearm(&self, fd: RawFd, event: EpollEvent) -> Result<()> {
+        self.modify(fd, event)
+    }
+
+    /// Stop watching `fd`.
+    pub fn delete(&self, fd: RawFd) -> Result<()> {
+        epoll_ctl(self.0.as_raw_fd(), EpollOp::EpollCtlDel, fd, None)
+    }
+
+    /// Wait for events, for at most `timeout_ms` milliseconds or forever if
+    /// it is -1.
+    ///
+    /// Returns the number of events stored at the start of `events`, which
+    /// is zero if the timeout expired.
+    pub fn wait(&self, events: &mut [EpollEvent], timeout_ms: isize) -> Result<usize> {
+        epoll_wait(self.0.as_raw_fd(), events, timeout_ms)
+    }
+}
+
+impl AsRawFd for Epoll {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
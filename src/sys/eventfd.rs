@@ -1,6 +1,7 @@
 use libc;
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{FromRawFd, RawFd};
 use Result;
+use fd::OwnedFd;
 use errno::Errno;
 
 libc_bitflags! {
@@ -16,3 +17,8 @@ pub fn eventfd(initval: libc::c_uint, flags: EfdFlags) -> Result<RawFd> {
 
     Errno::result(res).map(|r| r as RawFd)
 }
+
+/// Like `eventfd`, but return an [`OwnedFd`](../../fd/struct.OwnedFd.html).
+pub fn eventfd_owned(initval: libc::c_uint, flags: EfdFlags) -> Result<OwnedFd> {
+    eventfd(initval, flags).map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+}
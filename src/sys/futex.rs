@@ -0,0 +1,236 @@
+//! Fast user-space locking primitives.
+//!
+//! A futex is a 32-bit word in memory, possibly shared between processes, on
+//! which threads can sleep until another thread wakes them.  See
+//! [`futex(2)`](http://man7.org/linux/man-pages/man2/futex.2.html) for the
+//! semantics of each operation.
+//!
+//! The futex word is taken as an `AtomicUsize` so that it can be safely shared
+//! between the threads operating on it, as `AtomicU32` needs a more recent
+//! Rust than nix supports.  Only 32 bits of it, at
+//! [`FUTEX_WORD_OFFSET`](constant.FUTEX_WORD_OFFSET.html), form the futex
+//! word, so the values stored in it must fit in a `u32`.
+//!
+//! Locks built on futexes can be made robust against their owner dying by
+//! registering them on the owner's [robust list](fn.set_robust_list.html).
+use std::ptr;
+use std::sync::atomic::AtomicUsize;
+use libc::{self, c_int, c_long, size_t};
+use Result;
+use errno::Errno;
+use sys::time::TimeSpec;
+use unistd::Pid;
+
+/// Set in a futex word by a thread about to wait on it, so that the owner
+/// knows to wake it.
+pub const FUTEX_WAITERS: u32 = 0x8000_0000;
+/// Set in a futex word by the kernel when its owner died while holding it.
+pub const FUTEX_OWNER_DIED: u32 = 0x4000_0000;
+/// The bits of a futex word holding the thread ID of its owner.
+pub const FUTEX_TID_MASK: u32 = 0x3fff_ffff;
+
+/// The offset of the futex word within the `AtomicUsize` holding it.
+///
+/// The futex word holds the low 32 bits of the value, which come last on
+/// 64-bit big-endian targets.
+#[cfg(all(target_pointer_width = "64", target_endian = "big"))]
+pub const FUTEX_WORD_OFFSET: usize = 4;
+/// The offset of the futex word within the `AtomicUsize` holding it.
+///
+/// The futex word holds the low 32 bits of the value, which come last on
+/// 64-bit big-endian targets.
+#[cfg(not(all(target_pointer_width = "64", target_endian = "big")))]
+pub const FUTEX_WORD_OFFSET: usize = 0;
+
+fn word_ptr(addr: &AtomicUsize) -> *const u32 {
+    unsafe {
+        (addr as *const AtomicUsize as *const u8).offset(FUTEX_WORD_OFFSET as isize) as *const u32
+    }
+}
+
+fn futex(addr: &AtomicUsize, op: c_int, private: bool, val: u32,
+         timeout: *const libc::timespec, addr2: *const u32, val3: u32) -> Result<c_long> {
+    let op = if private { op | libc::FUTEX_PRIVATE_FLAG } else { op };
+    let res = unsafe {
+        libc::syscall(libc::SYS_futex, word_ptr(addr), op, val, timeout, addr2, val3)
+    };
+
+    Errno::result(res)
+}
+
+fn timeout_ptr(timeout: &Option<TimeSpec>) -> *const libc::timespec {
+    match *timeout {
+        Some(ref t) => t.as_ref(),
+        None => ptr::null(),
+    }
+}
+
+/// Sleep until woken by [`futex_wake`](fn.futex_wake.html), provided the
+/// futex word still contains `expected`.
+///
+/// Fails with `EAGAIN` if the word doesn't contain `expected`, and with
+/// `ETIMEDOUT` if `timeout` is given and elapses first.  `timeout` is relative
+/// and measured against `CLOCK_MONOTONIC`.
+///
+/// Set `private` if the word is only shared between threads of this process;
+/// waiters and wakers must agree on this.
+pub fn futex_wait(addr: &AtomicUsize, expected: u32, timeout: Option<TimeSpec>, private: bool) -> Result<()> {
+    futex(addr, libc::FUTEX_WAIT, private, expected, timeout_ptr(&timeout), ptr::null(), 0)
+        .map(drop)
+}
+
+/// Wake up to `n` threads waiting on the futex word.
+///
+/// Returns the number of threads woken.
+pub fn futex_wake(addr: &AtomicUsize, n: u32, private: bool) -> Result<usize> {
+    futex(addr, libc::FUTEX_WAKE, private, n, ptr::null(), ptr::null(), 0)
+        .map(|r| r as usize)
+}
+
+/// Wake up to `n_wake` threads waiting on `addr` and move up to `n_requeue`
+/// of the remaining waiters to wait on `addr2` instead.
+///
+/// Returns the number of threads woken.
+pub fn futex_requeue(addr: &AtomicUsize, n_wake: u32, addr2: &AtomicUsize, n_requeue: u32,
+                     private: bool) -> Result<usize> {
+    futex(addr, libc::FUTEX_REQUEUE, private, n_wake, n_requeue as usize as *const libc::timespec,
+          word_ptr(addr2), 0)
+        .map(|r| r as usize)
+}
+
+/// Like [`futex_requeue`](fn.futex_requeue.html), but fail with `EAGAIN`
+/// unless the word at `addr` still contains `expected`.
+///
+/// Returns the number of threads woken or requeued.
+pub fn futex_cmp_requeue(addr: &AtomicUsize, n_wake: u32, addr2: &AtomicUsize, n_requeue: u32,
+                         expected: u32, private: bool) -> Result<usize> {
+    futex(addr, libc::FUTEX_CMP_REQUEUE, private, n_wake,
+          n_requeue as usize as *const libc::timespec,
+          word_ptr(addr2), expected)
+        .map(|r| r as usize)
+}
+
+/// Like [`futex_wait`](fn.futex_wait.html), but only be woken by
+/// [`futex_wake_bitset`](fn.futex_wake_bitset.html) calls whose bitset
+/// intersects `bitset`.
+///
+/// Unlike for `futex_wait`, `timeout` is an absolute `CLOCK_MONOTONIC` time.
+pub fn futex_wait_bitset(addr: &AtomicUsize, expected: u32, timeout: Option<TimeSpec>, bitset: u32,
+                         private: bool) -> Result<()> {
+    futex(addr, libc::FUTEX_WAIT_BITSET, private, expected, timeout_ptr(&timeout), ptr::null(),
+          bitset)
+        .map(drop)
+}
+
+/// Wake up to `n` threads waiting on the futex word whose bitset intersects
+/// `bitset`.
+///
+/// Returns the number of threads woken.
+pub fn futex_wake_bitset(addr: &AtomicUsize, n: u32, bitset: u32, private: bool) -> Result<usize> {
+    futex(addr, libc::FUTEX_WAKE_BITSET, private, n, ptr::null(), ptr::null(), bitset)
+        .map(|r| r as usize)
+}
+
+/// An entry of a robust list (`struct robust_list`), which is embedded in the
+/// lock it belongs to.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RobustList {
+    /// The next entry, or the `list` of the head for the last one.
+    pub next: *mut RobustList,
+}
+
+/// The head of a thread's robust list (`struct robust_list_head`).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RobustListHead {
+    /// The first entry of the circular list, or `list` itself if it is
+    /// empty.
+    pub list: RobustList,
+    /// The offset of the futex word of a lock relative to its entry,
+    /// including [`FUTEX_WORD_OFFSET`](constant.FUTEX_WORD_OFFSET.html) if
+    /// the word is held in an `AtomicUsize`.
+    pub futex_offset: c_long,
+    /// The entry of a lock being acquired or released, which may or may not
+    /// be on the list yet.
+    pub list_op_pending: *mut RobustList,
+}
+
+/// Register `head` as the robust list of the calling thread.
+///
+/// When the thread exits, the kernel walks the list, and for each futex word
+/// whose [`FUTEX_TID_MASK`](constant.FUTEX_TID_MASK.html) bits hold the
+/// thread's ID, it sets [`FUTEX_OWNER_DIED`](constant.FUTEX_OWNER_DIED.html),
+/// clears the thread ID and wakes a waiter if
+/// [`FUTEX_WAITERS`](constant.FUTEX_WAITERS.html) was set.  `len` must be
+/// `mem::size_of::<RobustListHead>()`.
+///
+/// The C library usually registers a list for its robust mutexes already,
+/// and a thread only has one, so this replaces that list.
+///
+/// A lock is acquired by pointing `list_op_pending` at its entry, storing
+/// the thread ID in its futex word, linking the entry into the list and
+/// clearing `list_op_pending` again.  It is released the same way, unlinking
+/// the entry before clearing the word.  `list_op_pending` covers the thread
+/// dying halfway through either.
+///
+/// ```no_run
+/// use std::mem;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use nix::sys::futex::{set_robust_list, RobustList, RobustListHead, FUTEX_WORD_OFFSET};
+/// use nix::unistd::gettid;
+///
+/// // A lock is a list entry followed by its futex word.  Shared between
+/// // processes, it would live in shared memory.
+/// #[repr(C)]
+/// struct Lock {
+///     entry: RobustList,
+///     word: AtomicUsize,
+/// }
+///
+/// // The head has to stay valid until the thread exits.
+/// let head = Box::into_raw(Box::new(RobustListHead {
+///     list: RobustList { next: 0 as *mut RobustList },
+///     futex_offset: (mem::size_of::<RobustList>() + FUTEX_WORD_OFFSET) as _,
+///     list_op_pending: 0 as *mut RobustList,
+/// }));
+/// let lock = Box::into_raw(Box::new(Lock {
+///     entry: RobustList { next: 0 as *mut RobustList },
+///     word: AtomicUsize::new(0),
+/// }));
+/// unsafe {
+///     (*head).list.next = &mut (*head).list;
+///     set_robust_list(head, mem::size_of::<RobustListHead>()).unwrap();
+///
+///     // Acquire the lock.
+///     let tid: i32 = gettid().into();
+///     (*head).list_op_pending = &mut (*lock).entry;
+///     while (*lock).word.compare_and_swap(0, tid as usize, Ordering::SeqCst) != 0 {
+///         // Wait for the owner with `futex_wait`, setting FUTEX_WAITERS.
+///     }
+///     (*lock).entry.next = (*head).list.next;
+///     (*head).list.next = &mut (*lock).entry;
+///     (*head).list_op_pending = 0 as *mut RobustList;
+/// }
+/// ```
+pub unsafe fn set_robust_list(head: *mut RobustListHead, len: usize) -> Result<()> {
+    let res = libc::syscall(libc::SYS_set_robust_list, head, len as size_t);
+
+    Errno::result(res).map(drop)
+}
+
+/// Get the robust list registered by thread `pid`, or by the calling thread
+/// if `None`, and the `len` it was registered with.
+///
+/// Getting the list of another thread requires permission to trace it.
+pub fn get_robust_list(pid: Option<Pid>) -> Result<(*mut RobustListHead, usize)> {
+    let mut head: *mut RobustListHead = ptr::null_mut();
+    let mut len: size_t = 0;
+    let pid: libc::pid_t = pid.map_or(0, Into::into);
+    let res = unsafe {
+        libc::syscall(libc::SYS_get_robust_list, pid,
+                      &mut head as *mut *mut RobustListHead, &mut len as *mut size_t)
+    };
+
+    Errno::result(res).map(|_| (head, len as usize))
+}
@@ -0,0 +1,429 @@
+//! The raw system calls of the io_uring asynchronous I/O interface.
+//!
+//! [`setup`](fn.setup.html) creates a ring and fills in the
+//! [`IoUringParams`](struct.IoUringParams.html) with the offsets needed to
+//! `mmap` its submission and completion queues, with which submissions and
+//! completions are then exchanged without system calls.  [`enter`](fn.enter.html)
+//! submits new entries and waits for completions, and
+//! [`register`](fn.register.html) registers resources with the ring.  Building
+//! and parsing the queue entries themselves is left to higher-level crates.
+//!
+//! See [`io_uring(7)`](http://man7.org/linux/man-pages/man7/io_uring.7.html).
+use std::{mem, ptr};
+use std::os::unix::io::RawFd;
+use libc::{self, c_int, c_uint, c_void, off_t};
+use Result;
+use errno::Errno;
+use sys::signal::SigSet;
+use sys::uio::IoVec;
+
+/// The `mmap` offset of the submission queue ring.
+pub const IORING_OFF_SQ_RING: off_t = 0;
+/// The `mmap` offset of the completion queue ring.
+pub const IORING_OFF_CQ_RING: off_t = 0x800_0000;
+/// The `mmap` offset of the array of submission queue entries.
+pub const IORING_OFF_SQES: off_t = 0x1000_0000;
+
+/// The size of a submission queue entry.
+pub const SQE_SIZE: usize = 64;
+/// The size of a completion queue entry.
+pub const CQE_SIZE: usize = 16;
+
+bitflags!{
+    /// Flags for [`IoUringParams::set_flags`](struct.IoUringParams.html#method.set_flags).
+    pub struct SetupFlags: u32 {
+        /// Busy-wait for completions instead of relying on interrupts.
+        const IORING_SETUP_IOPOLL = 1 << 0;
+        /// Have a kernel thread poll the submission queue.
+        const IORING_SETUP_SQPOLL = 1 << 1;
+        /// Bind the polling thread to the CPU set by
+        /// [`set_sq_thread_cpu`](struct.IoUringParams.html#method.set_sq_thread_cpu).
+        const IORING_SETUP_SQ_AFF = 1 << 2;
+        /// Use the completion queue size set by
+        /// [`set_cq_entries`](struct.IoUringParams.html#method.set_cq_entries).
+        const IORING_SETUP_CQSIZE = 1 << 3;
+        /// Clamp too large queue sizes instead of failing with `EINVAL`.
+        const IORING_SETUP_CLAMP = 1 << 4;
+        /// Share the asynchronous workers of the ring set by
+        /// [`set_wq_fd`](struct.IoUringParams.html#method.set_wq_fd).
+        const IORING_SETUP_ATTACH_WQ = 1 << 5;
+        /// Create the ring disabled, to be enabled with
+        /// `IORING_REGISTER_ENABLE_RINGS`.
+        const IORING_SETUP_R_DISABLED = 1 << 6;
+    }
+}
+
+bitflags!{
+    /// Features supported by the running kernel, as reported by
+    /// [`IoUringParams::features`](struct.IoUringParams.html#method.features).
+    pub struct Features: u32 {
+        /// Both queue rings can be mapped with a single `mmap` at
+        /// `IORING_OFF_SQ_RING`.
+        const IORING_FEAT_SINGLE_MMAP = 1 << 0;
+        /// Completions are never dropped when the completion queue overflows.
+        const IORING_FEAT_NODROP = 1 << 1;
+        /// Data for submissions is consumed once they are submitted.
+        const IORING_FEAT_SUBMIT_STABLE = 1 << 2;
+        /// An offset of -1 reads or writes at the current file position.
+        const IORING_FEAT_RW_CUR_POS = 1 << 3;
+        /// Requests are executed with the credentials of the submitter.
+        const IORING_FEAT_CUR_PERSONALITY = 1 << 4;
+        /// Internal polling is used for files that aren't ready.
+        const IORING_FEAT_FAST_POLL = 1 << 5;
+        /// Poll requests take 32-bit event masks.
+        const IORING_FEAT_POLL_32BITS = 1 << 6;
+    }
+}
+
+bitflags!{
+    /// Flags for [`enter`](fn.enter.html).
+    pub struct EnterFlags: u32 {
+        /// Wait for `min_complete` completions.
+        const IORING_ENTER_GETEVENTS = 1 << 0;
+        /// Wake up the submission queue polling thread.
+        const IORING_ENTER_SQ_WAKEUP = 1 << 1;
+        /// Wait until the submission queue has free entries.
+        const IORING_ENTER_SQ_WAIT = 1 << 2;
+    }
+}
+
+// The opcodes of io_uring_register
+const IORING_REGISTER_BUFFERS: c_uint = 0;
+const IORING_UNREGISTER_BUFFERS: c_uint = 1;
+const IORING_REGISTER_FILES: c_uint = 2;
+const IORING_UNREGISTER_FILES: c_uint = 3;
+const IORING_REGISTER_EVENTFD: c_uint = 4;
+const IORING_UNREGISTER_EVENTFD: c_uint = 5;
+const IORING_REGISTER_PROBE: c_uint = 8;
+
+const IO_URING_OP_SUPPORTED: u16 = 1 << 0;
+
+/// The number of operations a [`Probe`](struct.Probe.html) has room for.
+const PROBE_OPS: usize = 256;
+
+/// The size of the kernel's `sigset_t`, which is smaller than the C library's.
+#[cfg(not(any(target_arch = "mips", target_arch = "mips64")))]
+const KERNEL_SIGSET_SIZE: usize = 8;
+#[cfg(any(target_arch = "mips", target_arch = "mips64"))]
+const KERNEL_SIGSET_SIZE: usize = 16;
+
+/// The offsets of the fields of the submission queue ring
+/// (`struct io_sqring_offsets`), relative to `IORING_OFF_SQ_RING`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct SqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    flags: u32,
+    dropped: u32,
+    array: u32,
+    resv1: u32,
+    resv2: u64,
+}
+
+impl SqringOffsets {
+    /// The offset of the index of the first entry the kernel hasn't consumed.
+    pub fn head(&self) -> u32 {
+        self.head
+    }
+
+    /// The offset of the index after the last submitted entry.
+    pub fn tail(&self) -> u32 {
+        self.tail
+    }
+
+    /// The offset of the mask to apply to the head and tail.
+    pub fn ring_mask(&self) -> u32 {
+        self.ring_mask
+    }
+
+    /// The offset of the number of entries.
+    pub fn ring_entries(&self) -> u32 {
+        self.ring_entries
+    }
+
+    /// The offset of the ring flags.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// The offset of the number of invalid entries the kernel dropped.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// The offset of the array of indices into the submission queue entries.
+    pub fn array(&self) -> u32 {
+        self.array
+    }
+}
+
+/// The offsets of the fields of the completion queue ring
+/// (`struct io_cqring_offsets`), relative to `IORING_OFF_CQ_RING`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    overflow: u32,
+    cqes: u32,
+    flags: u32,
+    resv1: u32,
+    resv2: u64,
+}
+
+impl CqringOffsets {
+    /// The offset of the index of the first completion not consumed yet.
+    pub fn head(&self) -> u32 {
+        self.head
+    }
+
+    /// The offset of the index after the last completion.
+    pub fn tail(&self) -> u32 {
+        self.tail
+    }
+
+    /// The offset of the mask to apply to the head and tail.
+    pub fn ring_mask(&self) -> u32 {
+        self.ring_mask
+    }
+
+    /// The offset of the number of entries.
+    pub fn ring_entries(&self) -> u32 {
+        self.ring_entries
+    }
+
+    /// The offset of the number of completions lost to overflows.
+    pub fn overflow(&self) -> u32 {
+        self.overflow
+    }
+
+    /// The offset of the array of completion queue entries.
+    pub fn cqes(&self) -> u32 {
+        self.cqes
+    }
+
+    /// The offset of the ring flags.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+}
+
+/// The parameters of a ring (`struct io_uring_params`), passed to
+/// [`setup`](fn.setup.html) and filled in by it.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct IoUringParams {
+    sq_entries: u32,
+    cq_entries: u32,
+    flags: u32,
+    sq_thread_cpu: u32,
+    sq_thread_idle: u32,
+    features: u32,
+    wq_fd: u32,
+    resv: [u32; 3],
+    sq_off: SqringOffsets,
+    cq_off: CqringOffsets,
+}
+
+impl IoUringParams {
+    /// Create parameters with no flags set.
+    pub fn new() -> IoUringParams {
+        unsafe { mem::zeroed() }
+    }
+
+    /// Set the flags the ring is set up with.
+    pub fn set_flags(&mut self, flags: SetupFlags) {
+        self.flags = flags.bits();
+    }
+
+    /// Set the size of the completion queue, used with `IORING_SETUP_CQSIZE`.
+    pub fn set_cq_entries(&mut self, entries: u32) {
+        self.cq_entries = entries;
+    }
+
+    /// Set the CPU of the polling thread, used with `IORING_SETUP_SQ_AFF`.
+    pub fn set_sq_thread_cpu(&mut self, cpu: u32) {
+        self.sq_thread_cpu = cpu;
+    }
+
+    /// Set how many milliseconds the polling thread keeps polling while idle,
+    /// used with `IORING_SETUP_SQPOLL`.
+    pub fn set_sq_thread_idle(&mut self, millis: u32) {
+        self.sq_thread_idle = millis;
+    }
+
+    /// Set the ring whose workers to share, used with
+    /// `IORING_SETUP_ATTACH_WQ`.
+    pub fn set_wq_fd(&mut self, fd: RawFd) {
+        self.wq_fd = fd as u32;
+    }
+
+    /// The flags the ring is set up with.
+    pub fn flags(&self) -> SetupFlags {
+        SetupFlags::from_bits_truncate(self.flags)
+    }
+
+    /// The number of submission queue entries.
+    pub fn sq_entries(&self) -> u32 {
+        self.sq_entries
+    }
+
+    /// The number of completion queue entries.
+    pub fn cq_entries(&self) -> u32 {
+        self.cq_entries
+    }
+
+    /// The features supported by the running kernel.
+    pub fn features(&self) -> Features {
+        Features::from_bits_truncate(self.features)
+    }
+
+    /// The offsets of the fields of the submission queue ring.
+    pub fn sq_off(&self) -> &SqringOffsets {
+        &self.sq_off
+    }
+
+    /// The offsets of the fields of the completion queue ring.
+    pub fn cq_off(&self) -> &CqringOffsets {
+        &self.cq_off
+    }
+
+    /// The size to `mmap` at `IORING_OFF_SQ_RING`.
+    pub fn sq_ring_size(&self) -> usize {
+        self.sq_off.array as usize + self.sq_entries as usize * mem::size_of::<u32>()
+    }
+
+    /// The size to `mmap` at `IORING_OFF_CQ_RING`.
+    pub fn cq_ring_size(&self) -> usize {
+        self.cq_off.cqes as usize + self.cq_entries as usize * CQE_SIZE
+    }
+
+    /// The size to `mmap` at `IORING_OFF_SQES`.
+    pub fn sqes_size(&self) -> usize {
+        self.sq_entries as usize * SQE_SIZE
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct ProbeOp {
+    op: u8,
+    resv: u8,
+    flags: u16,
+    resv2: u32,
+}
+
+/// The operations supported by the running kernel (`struct io_uring_probe`),
+/// filled in by [`RegisterOp::Probe`](enum.RegisterOp.html).
+#[repr(C)]
+#[allow(missing_debug_implementations)]
+pub struct Probe {
+    last_op: u8,
+    ops_len: u8,
+    resv: u16,
+    resv2: [u32; 3],
+    ops: [ProbeOp; PROBE_OPS],
+}
+
+impl Probe {
+    /// Create an empty probe.
+    pub fn new() -> Probe {
+        unsafe { mem::zeroed() }
+    }
+
+    /// The highest opcode known to the running kernel.
+    pub fn last_op(&self) -> u8 {
+        self.last_op
+    }
+
+    /// Whether the operation with opcode `op` is supported.
+    pub fn is_supported(&self, op: u8) -> bool {
+        op <= self.last_op && (op as usize) < self.ops_len as usize &&
+            self.ops[op as usize].flags & IO_URING_OP_SUPPORTED != 0
+    }
+}
+
+/// A resource to register with a ring, for [`register`](fn.register.html).
+#[allow(missing_debug_implementations)]
+pub enum RegisterOp<'a> {
+    /// Register fixed buffers (`IORING_REGISTER_BUFFERS`).
+    Buffers(&'a [IoVec<&'a mut [u8]>]),
+    /// Unregister the fixed buffers (`IORING_UNREGISTER_BUFFERS`).
+    UnregisterBuffers,
+    /// Register fixed files (`IORING_REGISTER_FILES`).
+    Files(&'a [RawFd]),
+    /// Unregister the fixed files (`IORING_UNREGISTER_FILES`).
+    UnregisterFiles,
+    /// Register an eventfd to be signalled on completions
+    /// (`IORING_REGISTER_EVENTFD`).
+    Eventfd(RawFd),
+    /// Unregister the eventfd (`IORING_UNREGISTER_EVENTFD`).
+    UnregisterEventfd,
+    /// Find out which operations are supported (`IORING_REGISTER_PROBE`).
+    Probe(&'a mut Probe),
+}
+
+/// Create a ring with at least `entries` submission queue entries (see
+/// [`io_uring_setup(2)`](http://man7.org/linux/man-pages/man2/io_uring_setup.2.html)).
+///
+/// The flags and settings in `params` are applied, and the actual sizes of the
+/// queues and the offsets to `mmap` them are filled in.  Fails with `ENOSYS`
+/// on kernels without io_uring and with `EPERM` if it has been disabled.
+pub fn setup(entries: u32, params: &mut IoUringParams) -> Result<RawFd> {
+    let res = unsafe {
+        libc::syscall(libc::SYS_io_uring_setup, entries, params as *mut IoUringParams)
+    };
+
+    Errno::result(res).map(|r| r as RawFd)
+}
+
+/// Register or unregister a resource with the ring `fd` (see
+/// [`io_uring_register(2)`](http://man7.org/linux/man-pages/man2/io_uring_register.2.html)).
+pub fn register(fd: RawFd, op: RegisterOp) -> Result<()> {
+    let eventfd: c_int;
+    let (opcode, arg, nr_args) = match op {
+        RegisterOp::Buffers(iovs) => {
+            (IORING_REGISTER_BUFFERS, iovs.as_ptr() as *const c_void, iovs.len())
+        },
+        RegisterOp::UnregisterBuffers => (IORING_UNREGISTER_BUFFERS, ptr::null(), 0),
+        RegisterOp::Files(fds) => {
+            (IORING_REGISTER_FILES, fds.as_ptr() as *const c_void, fds.len())
+        },
+        RegisterOp::UnregisterFiles => (IORING_UNREGISTER_FILES, ptr::null(), 0),
+        RegisterOp::Eventfd(fd) => {
+            eventfd = fd;
+            (IORING_REGISTER_EVENTFD, &eventfd as *const c_int as *const c_void, 1)
+        },
+        RegisterOp::UnregisterEventfd => (IORING_UNREGISTER_EVENTFD, ptr::null(), 0),
+        RegisterOp::Probe(probe) => {
+            (IORING_REGISTER_PROBE, probe as *mut Probe as *const c_void, PROBE_OPS)
+        },
+    };
+    let res = unsafe {
+        libc::syscall(libc::SYS_io_uring_register, fd, opcode, arg, nr_args as c_uint)
+    };
+
+    Errno::result(res).map(drop)
+}
+
+/// Submit `to_submit` entries from the submission queue of the ring `fd`, and
+/// with `IORING_ENTER_GETEVENTS` wait for `min_complete` completions (see
+/// [`io_uring_enter(2)`](http://man7.org/linux/man-pages/man2/io_uring_enter.2.html)).
+///
+/// If `sigmask` is given, it replaces the signal mask while waiting.  Returns
+/// the number of entries submitted.
+pub fn enter(fd: RawFd, to_submit: u32, min_complete: u32, flags: EnterFlags,
+             sigmask: Option<&SigSet>) -> Result<usize> {
+    let sigmask = sigmask.map_or(ptr::null(), |s| s.as_ref() as *const libc::sigset_t);
+    let res = unsafe {
+        libc::syscall(libc::SYS_io_uring_enter, fd, to_submit, min_complete, flags.bits(),
+                      sigmask, KERNEL_SIGSET_SIZE)
+    };
+
+    Errno::result(res).map(|r| r as usize)
+}
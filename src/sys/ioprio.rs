@@ -0,0 +1,112 @@
+//! Query and set the I/O scheduling class and priority of processes.
+//!
+//! See [`ioprio_set(2)`](http://man7.org/linux/man-pages/man2/ioprio_set.2.html).
+//! I/O priorities are only honored by I/O schedulers that support them, such
+//! as BFQ and the former CFQ.
+use libc::{self, c_int};
+use {Error, Result};
+use errno::Errno;
+use unistd::{Pid, Uid};
+
+const IOPRIO_CLASS_SHIFT: c_int = 13;
+const IOPRIO_PRIO_MASK: c_int = (1 << IOPRIO_CLASS_SHIFT) - 1;
+
+const IOPRIO_CLASS_NONE: c_int = 0;
+const IOPRIO_CLASS_RT: c_int = 1;
+const IOPRIO_CLASS_BE: c_int = 2;
+const IOPRIO_CLASS_IDLE: c_int = 3;
+
+const IOPRIO_WHO_PROCESS: c_int = 1;
+const IOPRIO_WHO_PGRP: c_int = 2;
+const IOPRIO_WHO_USER: c_int = 3;
+
+/// Number of priority levels within the real-time and best-effort classes.
+pub const IOPRIO_LEVELS: u8 = 8;
+
+/// The processes whose I/O priority is queried or set.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Target {
+    /// A single thread, or the calling thread if the `Pid` is zero.
+    Process(Pid),
+    /// All members of a process group, or of the calling process's group if
+    /// the `Pid` is zero.
+    ProcessGroup(Pid),
+    /// All processes whose real user ID matches.
+    User(Uid),
+}
+
+impl Target {
+    fn as_raw(&self) -> (c_int, c_int) {
+        match *self {
+            Target::Process(pid) => (IOPRIO_WHO_PROCESS, libc::pid_t::from(pid)),
+            Target::ProcessGroup(pid) => (IOPRIO_WHO_PGRP, libc::pid_t::from(pid)),
+            Target::User(uid) => (IOPRIO_WHO_USER, libc::uid_t::from(uid) as c_int),
+        }
+    }
+}
+
+/// An I/O scheduling class and the priority within it.
+///
+/// Levels range from 0, the highest priority, to 7.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IoPriority {
+    /// No class has been set, so the best-effort class is used with a level
+    /// derived from the CPU nice value.
+    None,
+    /// Get the disk first regardless of other processes.  Setting this class
+    /// requires the `CAP_SYS_ADMIN` capability.
+    RealTime(u8),
+    /// Share the disk with other best-effort processes.
+    BestEffort(u8),
+    /// Only get the disk when no other process needs it.
+    Idle,
+}
+
+impl IoPriority {
+    fn to_raw(&self) -> Result<c_int> {
+        let (class, level) = match *self {
+            IoPriority::None => (IOPRIO_CLASS_NONE, 0),
+            IoPriority::RealTime(level) => (IOPRIO_CLASS_RT, level),
+            IoPriority::BestEffort(level) => (IOPRIO_CLASS_BE, level),
+            IoPriority::Idle => (IOPRIO_CLASS_IDLE, 0),
+        };
+        if level >= IOPRIO_LEVELS {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+
+        Ok(class << IOPRIO_CLASS_SHIFT | level as c_int)
+    }
+
+    fn from_raw(ioprio: c_int) -> IoPriority {
+        let level = (ioprio & IOPRIO_PRIO_MASK) as u8;
+        match ioprio >> IOPRIO_CLASS_SHIFT {
+            IOPRIO_CLASS_RT => IoPriority::RealTime(level),
+            IOPRIO_CLASS_BE => IoPriority::BestEffort(level),
+            IOPRIO_CLASS_IDLE => IoPriority::Idle,
+            _ => IoPriority::None,
+        }
+    }
+}
+
+/// Get the I/O priority of `target`.
+///
+/// If `target` covers several processes, the highest priority among them is
+/// returned.
+pub fn get(target: Target) -> Result<IoPriority> {
+    let (which, who) = target.as_raw();
+    let res = unsafe { libc::syscall(libc::SYS_ioprio_get, which, who) };
+
+    Errno::result(res).map(|ioprio| IoPriority::from_raw(ioprio as c_int))
+}
+
+/// Set the I/O priority of `target`.
+///
+/// Fails with `EINVAL` without making the system call if the level of
+/// `priority` is out of range.
+pub fn set(target: Target, priority: IoPriority) -> Result<()> {
+    let ioprio = try!(priority.to_raw());
+    let (which, who) = target.as_raw();
+    let res = unsafe { libc::syscall(libc::SYS_ioprio_set, which, who, ioprio) };
+
+    Errno::result(res).map(drop)
+}
@@ -0,0 +1,186 @@
+//! Manage keys in the kernel key retention service.
+//!
+//! See [`keyrings(7)`](http://man7.org/linux/man-pages/man7/keyrings.7.html),
+//! [`add_key(2)`](http://man7.org/linux/man-pages/man2/add_key.2.html),
+//! [`request_key(2)`](http://man7.org/linux/man-pages/man2/request_key.2.html) and
+//! [`keyctl(2)`](http://man7.org/linux/man-pages/man2/keyctl.2.html).
+use std::ffi::CString;
+use std::{fmt, ptr};
+use libc::{self, c_char, c_long, c_ulong, c_void, size_t};
+use {Error, Result};
+use errno::Errno;
+
+/// Serial number identifying a key or keyring
+///
+/// Newtype pattern around `key_serial_t` (an `i32`).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct KeySerial(i32);
+
+impl KeySerial {
+    /// Creates `KeySerial` from a raw `key_serial_t`.
+    pub fn from_raw(serial: i32) -> Self {
+        KeySerial(serial)
+    }
+}
+
+impl From<KeySerial> for i32 {
+    fn from(serial: KeySerial) -> Self {
+        serial.0
+    }
+}
+
+impl fmt::Display for KeySerial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// A keyring, either one of the special keyrings of the calling thread or
+/// one identified by its serial number.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum KeyringId {
+    /// The thread-specific keyring
+    Thread,
+    /// The process-specific keyring
+    Process,
+    /// The session keyring
+    Session,
+    /// The UID-specific keyring
+    User,
+    /// The UID-specific session keyring
+    UserSession,
+    /// The keyring of a specific key or keyring
+    Serial(KeySerial),
+}
+
+impl KeyringId {
+    fn as_raw(&self) -> i32 {
+        match *self {
+            KeyringId::Thread => libc::KEY_SPEC_THREAD_KEYRING as i32,
+            KeyringId::Process => libc::KEY_SPEC_PROCESS_KEYRING as i32,
+            KeyringId::Session => libc::KEY_SPEC_SESSION_KEYRING as i32,
+            KeyringId::User => libc::KEY_SPEC_USER_KEYRING as i32,
+            KeyringId::UserSession => libc::KEY_SPEC_USER_SESSION_KEYRING as i32,
+            KeyringId::Serial(serial) => serial.0,
+        }
+    }
+}
+
+impl From<KeySerial> for KeyringId {
+    fn from(serial: KeySerial) -> Self {
+        KeyringId::Serial(serial)
+    }
+}
+
+fn to_cstring(s: &str) -> Result<CString> {
+    CString::new(s).map_err(|_| Error::invalid_argument())
+}
+
+fn keyctl(cmd: c_ulong, arg2: c_ulong, arg3: c_ulong, arg4: c_ulong, arg5: c_ulong) -> Result<c_long> {
+    let res = unsafe { libc::syscall(libc::SYS_keyctl, cmd, arg2, arg3, arg4, arg5) };
+
+    Errno::result(res)
+}
+
+/// Create or update a key of type `key_type` with the given `description`
+/// and `payload`, and link it into `keyring`.
+///
+/// If `keyring` already contains a key with the same type and description,
+/// that key is updated instead.
+pub fn add_key(key_type: &str, description: &str, payload: &[u8], keyring: KeyringId) -> Result<KeySerial> {
+    let key_type = try!(to_cstring(key_type));
+    let description = try!(to_cstring(description));
+    let res = unsafe {
+        libc::syscall(libc::SYS_add_key, key_type.as_ptr(), description.as_ptr(),
+                      payload.as_ptr() as *const c_void, payload.len() as size_t,
+                      keyring.as_raw())
+    };
+
+    Errno::result(res).map(|r| KeySerial(r as i32))
+}
+
+/// Find a key of type `key_type` matching `description`, asking user space
+/// to construct it with `callout_info` if it doesn't exist.
+///
+/// A key that is found or constructed is linked into `dest_keyring` if one
+/// is given.  Without `callout_info` no construction is attempted and a
+/// missing key is reported as `ENOKEY`.
+pub fn request_key(key_type: &str, description: &str, callout_info: Option<&str>,
+                   dest_keyring: Option<KeyringId>) -> Result<KeySerial> {
+    let key_type = try!(to_cstring(key_type));
+    let description = try!(to_cstring(description));
+    let callout_info = match callout_info {
+        Some(info) => Some(try!(to_cstring(info))),
+        None => None,
+    };
+    let callout_ptr = callout_info.as_ref().map_or(ptr::null(), |info| info.as_ptr());
+    let res = unsafe {
+        libc::syscall(libc::SYS_request_key, key_type.as_ptr(), description.as_ptr(),
+                      callout_ptr, dest_keyring.map_or(0, |k| k.as_raw()))
+    };
+
+    Errno::result(res).map(|r| KeySerial(r as i32))
+}
+
+/// Get the serial number of `keyring`, creating it first if `create` is set
+/// and it doesn't exist yet.
+pub fn get_keyring_id(keyring: KeyringId, create: bool) -> Result<KeySerial> {
+    keyctl(libc::KEYCTL_GET_KEYRING_ID as c_ulong, keyring.as_raw() as c_ulong,
+           create as c_ulong, 0, 0)
+        .map(|r| KeySerial(r as i32))
+}
+
+/// Read the payload of `key`.
+pub fn read(key: KeySerial) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    loop {
+        // The payload may change between calls, so retry until the buffer is
+        // big enough.
+        let len = try!(keyctl(libc::KEYCTL_READ as c_ulong, key.0 as c_ulong,
+                              buf.as_mut_ptr() as *mut c_char as c_ulong,
+                              buf.len() as c_ulong, 0)) as usize;
+        if len <= buf.len() {
+            buf.truncate(len);
+            return Ok(buf);
+        }
+        buf.resize(len, 0);
+    }
+}
+
+/// Revoke `key`, making it inaccessible.
+pub fn revoke(key: KeySerial) -> Result<()> {
+    keyctl(libc::KEYCTL_REVOKE as c_ulong, key.0 as c_ulong, 0, 0, 0).map(drop)
+}
+
+/// Set `key` to expire `timeout` seconds from now, or clear its expiry if
+/// `timeout` is zero.
+pub fn set_timeout(key: KeySerial, timeout: u32) -> Result<()> {
+    keyctl(libc::KEYCTL_SET_TIMEOUT as c_ulong, key.0 as c_ulong, timeout as c_ulong, 0, 0)
+        .map(drop)
+}
+
+/// Link `key` into `keyring`.
+pub fn link(key: KeySerial, keyring: KeyringId) -> Result<()> {
+    keyctl(libc::KEYCTL_LINK as c_ulong, key.0 as c_ulong, keyring.as_raw() as c_ulong, 0, 0)
+        .map(drop)
+}
+
+/// Unlink `key` from `keyring`.
+pub fn unlink(key: KeySerial, keyring: KeyringId) -> Result<()> {
+    keyctl(libc::KEYCTL_UNLINK as c_ulong, key.0 as c_ulong, keyring.as_raw() as c_ulong, 0, 0)
+        .map(drop)
+}
+
+/// Search `keyring` and the keyrings it links to for a key of type
+/// `key_type` matching `description`.
+///
+/// A key that is found is linked into `dest_keyring` if one is given.
+pub fn search(keyring: KeyringId, key_type: &str, description: &str,
+              dest_keyring: Option<KeyringId>) -> Result<KeySerial> {
+    let key_type = try!(to_cstring(key_type));
+    let description = try!(to_cstring(description));
+    keyctl(libc::KEYCTL_SEARCH as c_ulong, keyring.as_raw() as c_ulong,
+           key_type.as_ptr() as c_ulong, description.as_ptr() as c_ulong,
+           dest_keyring.map_or(0, |k| k.as_raw()) as c_ulong)
+        .map(|r| KeySerial(r as i32))
+}
@@ -0,0 +1,105 @@
+//! Access the kernel message ring buffer.
+//!
+//! See [`syslog(2)`](http://man7.org/linux/man-pages/man2/syslog.2.html), not
+//! to be confused with the `syslog(3)` logging interface.
+//!
+//! Reading the buffer requires the `CAP_SYSLOG` capability if the
+//! `kernel.dmesg_restrict` sysctl is set; otherwise the calls fail with
+//! `EPERM`.
+use std::ptr;
+use libc::{self, c_char, c_int};
+use Result;
+use errno::Errno;
+
+/// Actions that can be performed by [`klogctl`](fn.klogctl.html).
+///
+/// The values are defined in `include/linux/syslog.h`, which libc doesn't
+/// expose.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KlogAction {
+    /// Close the log.  Currently a no-op.
+    Close = 0,
+    /// Open the log.  Currently a no-op.
+    Open = 1,
+    /// Wait until the log is nonempty, then read and consume messages.
+    Read = 2,
+    /// Read all messages remaining in the ring buffer without consuming them.
+    ReadAll = 3,
+    /// Read and clear all messages remaining in the ring buffer.
+    ReadClear = 4,
+    /// Clear the ring buffer.
+    Clear = 5,
+    /// Disable printing messages to the console.
+    ConsoleOff = 6,
+    /// Enable printing messages to the console.
+    ConsoleOn = 7,
+    /// Set the level of messages printed to the console.
+    ConsoleLevel = 8,
+    /// Return the number of unread bytes in the buffer.
+    SizeUnread = 9,
+    /// Return the total size of the ring buffer.
+    SizeBuffer = 10,
+}
+
+/// Perform `action` on the kernel ring buffer, reading into `buf` for the
+/// read actions.
+///
+/// Returns the number of bytes read for the read actions and the requested
+/// size for `SizeUnread` and `SizeBuffer`.  `ConsoleLevel` is better
+/// performed through [`console_level`](fn.console_level.html) since it needs
+/// a level rather than a buffer.
+pub fn klogctl(action: KlogAction, buf: Option<&mut [u8]>) -> Result<usize> {
+    let (ptr, len) = match buf {
+        Some(buf) => (buf.as_mut_ptr() as *mut c_char, buf.len() as c_int),
+        None => (ptr::null_mut(), 0),
+    };
+    let res = unsafe { libc::klogctl(action as c_int, ptr, len) };
+
+    Errno::result(res).map(|r| r as usize)
+}
+
+/// Read all messages currently in the ring buffer into `buf`, replacing its
+/// contents.
+///
+/// The buffer is sized to fit the whole ring buffer first.  Returns the
+/// number of bytes read.
+pub fn read_all(buf: &mut Vec<u8>) -> Result<usize> {
+    let size = try!(klogctl(KlogAction::SizeBuffer, None));
+    buf.clear();
+    buf.resize(size, 0);
+
+    let len = try!(klogctl(KlogAction::ReadAll, Some(&mut buf[..])));
+    buf.truncate(len);
+
+    Ok(len)
+}
+
+/// Set the level of messages printed to the console.
+///
+/// Only messages more severe than `level`, from 1 to 8, are printed.
+pub fn console_level(level: u8) -> Result<()> {
+    let res = unsafe {
+        libc::klogctl(KlogAction::ConsoleLevel as c_int, ptr::null_mut(), level as c_int)
+    };
+
+    Errno::result(res).map(drop)
+}
+
+/// Enable printing messages to the console.
+///
+/// Restores the console level in effect before it was last disabled by
+/// [`console_off`](fn.console_off.html).
+pub fn console_on() -> Result<()> {
+    klogctl(KlogAction::ConsoleOn, None).map(drop)
+}
+
+/// Disable printing messages to the console, except for panics.
+pub fn console_off() -> Result<()> {
+    klogctl(KlogAction::ConsoleOff, None).map(drop)
+}
+
+/// Clear the ring buffer.
+pub fn clear() -> Result<()> {
+    klogctl(KlogAction::Clear, None).map(drop)
+}
@@ -0,0 +1,151 @@
+//! Unprivileged sandboxing with Landlock.
+//!
+//! A process builds a ruleset listing the access rights it wants to restrict
+//! and the file hierarchies in which some of them remain allowed, then applies
+//! it to itself with [`restrict_self`](fn.restrict_self.html).  Restrictions
+//! are inherited by children and can't be lifted again.
+//!
+//! See [`landlock(7)`](http://man7.org/linux/man-pages/man7/landlock.7.html).
+use std::mem;
+use std::os::unix::io::RawFd;
+use libc::{self, c_ulong, c_void, size_t};
+use Result;
+use errno::Errno;
+
+// libc doesn't define the Landlock ABI, so it is taken from
+// `include/uapi/linux/landlock.h`.
+const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+const LANDLOCK_RULE_PATH_BENEATH: u32 = 1;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct landlock_ruleset_attr {
+    handled_access_fs: u64,
+}
+
+#[repr(C, packed)]
+struct landlock_path_beneath_attr {
+    allowed_access: u64,
+    parent_fd: i32,
+}
+
+bitflags!{
+    /// Filesystem access rights handled by a ruleset.
+    pub struct AccessFs: u64 {
+        /// Execute a file.
+        const LANDLOCK_ACCESS_FS_EXECUTE = 1 << 0;
+        /// Open a file with write access.
+        const LANDLOCK_ACCESS_FS_WRITE_FILE = 1 << 1;
+        /// Open a file with read access.
+        const LANDLOCK_ACCESS_FS_READ_FILE = 1 << 2;
+        /// Open a directory or list its content.
+        const LANDLOCK_ACCESS_FS_READ_DIR = 1 << 3;
+        /// Remove an empty directory or rename one.
+        const LANDLOCK_ACCESS_FS_REMOVE_DIR = 1 << 4;
+        /// Unlink or rename a file.
+        const LANDLOCK_ACCESS_FS_REMOVE_FILE = 1 << 5;
+        /// Create, rename or link a character device.
+        const LANDLOCK_ACCESS_FS_MAKE_CHAR = 1 << 6;
+        /// Create or rename a directory.
+        const LANDLOCK_ACCESS_FS_MAKE_DIR = 1 << 7;
+        /// Create, rename or link a regular file.
+        const LANDLOCK_ACCESS_FS_MAKE_REG = 1 << 8;
+        /// Create, rename or link a UNIX domain socket.
+        const LANDLOCK_ACCESS_FS_MAKE_SOCK = 1 << 9;
+        /// Create, rename or link a named pipe.
+        const LANDLOCK_ACCESS_FS_MAKE_FIFO = 1 << 10;
+        /// Create, rename or link a block device.
+        const LANDLOCK_ACCESS_FS_MAKE_BLOCK = 1 << 11;
+        /// Create, rename or link a symbolic link.
+        const LANDLOCK_ACCESS_FS_MAKE_SYM = 1 << 12;
+        /// Link or rename a file from or to a different directory.  Requires
+        /// ABI version 2.
+        const LANDLOCK_ACCESS_FS_REFER = 1 << 13;
+        /// Truncate a file.  Requires ABI version 3.
+        const LANDLOCK_ACCESS_FS_TRUNCATE = 1 << 14;
+    }
+}
+
+/// The access rights a ruleset restricts, as passed to
+/// [`create_ruleset`](fn.create_ruleset.html).
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub struct RulesetAttr(landlock_ruleset_attr);
+
+impl RulesetAttr {
+    /// Restrict the filesystem access rights in `handled_access_fs`.
+    ///
+    /// Rights that aren't handled stay allowed everywhere.  Rights unknown to
+    /// the running kernel make `create_ruleset` fail with `EINVAL`, so check
+    /// [`abi_version`](fn.abi_version.html) before handling newer ones.
+    pub fn new(handled_access_fs: AccessFs) -> RulesetAttr {
+        RulesetAttr(landlock_ruleset_attr { handled_access_fs: handled_access_fs.bits() })
+    }
+
+    /// The filesystem access rights this ruleset restricts.
+    pub fn handled_access_fs(&self) -> AccessFs {
+        AccessFs::from_bits_truncate(self.0.handled_access_fs)
+    }
+}
+
+/// Get the highest Landlock ABI version supported by the running kernel.
+///
+/// Fails with `ENOSYS` if the kernel predates Landlock and with `EOPNOTSUPP`
+/// if it has been disabled at boot.
+pub fn abi_version() -> Result<u32> {
+    let res = unsafe {
+        libc::syscall(libc::SYS_landlock_create_ruleset, 0 as *const c_void, 0 as size_t,
+                      LANDLOCK_CREATE_RULESET_VERSION)
+    };
+
+    Errno::result(res).map(|r| r as u32)
+}
+
+/// Create a new ruleset restricting the access rights selected by `attr`.
+///
+/// Returns a file descriptor for the ruleset, to which rules are added with
+/// [`add_path_beneath_rule`](fn.add_path_beneath_rule.html).
+pub fn create_ruleset(attr: &RulesetAttr) -> Result<RawFd> {
+    let res = unsafe {
+        libc::syscall(libc::SYS_landlock_create_ruleset,
+                      &attr.0 as *const landlock_ruleset_attr,
+                      mem::size_of::<landlock_ruleset_attr>() as size_t, 0u32)
+    };
+
+    Errno::result(res).map(|r| r as RawFd)
+}
+
+/// Allow the `allowed_access` rights within the file hierarchy below
+/// `parent_fd`.
+///
+/// `parent_fd` is usually opened with `O_PATH`.  `allowed_access` must be a
+/// subset of the rights handled by the ruleset.
+pub fn add_path_beneath_rule(ruleset_fd: RawFd, parent_fd: RawFd, allowed_access: AccessFs) -> Result<()> {
+    let attr = landlock_path_beneath_attr {
+        allowed_access: allowed_access.bits(),
+        parent_fd: parent_fd,
+    };
+    let res = unsafe {
+        libc::syscall(libc::SYS_landlock_add_rule, ruleset_fd, LANDLOCK_RULE_PATH_BENEATH,
+                      &attr as *const landlock_path_beneath_attr, 0u32)
+    };
+
+    Errno::result(res).map(drop)
+}
+
+/// Restrict the calling thread with the ruleset `ruleset_fd`.
+///
+/// Unprivileged threads may only do this once they can't gain privileges
+/// anymore, so `PR_SET_NO_NEW_PRIVS` is set on the calling thread first.
+/// Like the ruleset, this is irreversible.  Other threads of the process
+/// aren't affected, but threads and processes created afterwards are.
+pub fn restrict_self(ruleset_fd: RawFd) -> Result<()> {
+    let res = unsafe {
+        libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1 as c_ulong, 0 as c_ulong, 0 as c_ulong, 0 as c_ulong)
+    };
+    try!(Errno::result(res));
+
+    let res = unsafe { libc::syscall(libc::SYS_landlock_restrict_self, ruleset_fd, 0u32) };
+
+    Errno::result(res).map(drop)
+}
@@ -188,8 +188,8 @@ libc_enum!{
 }
 
 libc_bitflags!{
-    /// Configuration flags for `msync`.
-    pub struct MsFlags: c_int {
+    /// Configuration flags for [`msync`](fn.msync.html).
+    pub struct MsyncFlags: c_int {
         /// Schedule an update but return immediately.
         MS_ASYNC;
         /// Invalidate all cached data.
@@ -205,6 +205,11 @@ libc_bitflags!{
     }
 }
 
+/// Former name of [`MsyncFlags`](struct.MsyncFlags.html), which clashed with
+/// the mount flags of the same name.
+#[deprecated(since = "0.12.0", note = "use MsyncFlags instead")]
+pub type MsFlags = MsyncFlags;
+
 libc_bitflags!{
     /// Flags for `mlockall`.
     pub struct MlockAllFlags: c_int {
@@ -258,10 +263,37 @@ pub unsafe fn madvise(addr: *mut c_void, length: size_t, advise: MmapAdvise) ->
     Errno::result(libc::madvise(addr, length, advise as i32)).map(drop)
 }
 
-pub unsafe fn msync(addr: *mut c_void, length: size_t, flags: MsFlags) -> Result<()> {
+/// Flush changes made to the memory mapped at `addr` back to the underlying
+/// file.
+///
+/// `addr` must be aligned to the page size.
+pub unsafe fn msync(addr: *mut c_void, length: size_t, flags: MsyncFlags) -> Result<()> {
     Errno::result(libc::msync(addr, length, flags.bits())).map(drop)
 }
 
+/// Determine which pages of the `length` bytes starting at `addr` are resident
+/// in memory.
+///
+/// Returns one element per page touched by the range, `true` if that page is
+/// resident.  Unlike for the underlying system call, `addr` needn't be aligned
+/// to the page size; the range is extended to whole pages.
+///
+/// Fails with `ENOMEM` if part of the range isn't mapped.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn mincore(addr: *const c_void, length: size_t) -> Result<Vec<bool>> {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let offset = addr as usize % page_size;
+    let start = (addr as usize - offset) as *mut c_void;
+    let length = length + offset;
+    let pages = (length + page_size - 1) / page_size;
+    let mut vec: Vec<libc::c_uchar> = vec![0; pages];
+
+    let res = unsafe { libc::mincore(start, length, vec.as_mut_ptr()) };
+    try!(Errno::result(res));
+
+    Ok(vec.iter().map(|&v| v & 1 != 0).collect())
+}
+
 #[cfg(not(target_os = "android"))]
 pub fn shm_open<P: ?Sized + NixPath>(name: &P, flag: OFlag, mode: Mode) -> Result<RawFd> {
     let ret = try!(name.with_nix_path(|cstr| {
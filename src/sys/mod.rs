@@ -20,6 +20,12 @@ pub mod event;
 #[cfg(target_os = "linux")]
 pub mod eventfd;
 
+#[cfg(target_os = "linux")]
+pub mod futex;
+
+#[cfg(target_os = "linux")]
+pub mod io_uring;
+
 #[cfg(any(target_os = "android",
           target_os = "dragonfly",
           target_os = "freebsd",
@@ -31,11 +37,33 @@ pub mod eventfd;
 #[macro_use]
 pub mod ioctl;
 
+// Uses the ioctl macros, so it has to come after `ioctl`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod block;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod ioprio;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod key;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod klog;
+
+#[cfg(target_os = "linux")]
+pub mod landlock;
+
 #[cfg(target_os = "linux")]
 pub mod memfd;
 
 pub mod mman;
 
+#[cfg(target_os = "linux")]
+pub mod msg;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod personality;
+
 pub mod pthread;
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
@@ -49,6 +77,9 @@ pub mod reboot;
 
 pub mod select;
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod sem;
+
 #[cfg(any(target_os = "android",
           target_os = "freebsd",
           target_os = "ios",
@@ -56,6 +87,9 @@ pub mod select;
           target_os = "macos"))]
 pub mod sendfile;
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod shm;
+
 pub mod signal;
 
 #[cfg(any(target_os = "android", target_os = "linux"))]
@@ -70,6 +104,9 @@ pub mod statfs;
 
 pub mod statvfs;
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod sysinfo;
+
 pub mod termios;
 
 pub mod time;
@@ -79,3 +116,6 @@ pub mod uio;
 pub mod utsname;
 
 pub mod wait;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub mod xattr;
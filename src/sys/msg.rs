@@ -0,0 +1,168 @@
+//! System V message queues.
+//!
+//! See [`msgget(2)`](http://man7.org/linux/man-pages/man2/msgget.2.html),
+//! [`msgop(2)`](http://man7.org/linux/man-pages/man2/msgop.2.html) and
+//! [`msgctl(2)`](http://man7.org/linux/man-pages/man2/msgctl.2.html).
+use std::{mem, ptr};
+use libc::{self, c_int, c_long, c_void, key_t, size_t};
+use Result;
+use errno::Errno;
+use sys::stat::Mode;
+use unistd::{Gid, Uid};
+
+pub use sys::shm::IPC_PRIVATE;
+
+libc_bitflags!{
+    /// Flags for [`msgget`](fn.msgget.html).
+    pub struct MsggetFlag: c_int {
+        /// Create the queue if it doesn't already exist.
+        IPC_CREAT;
+        /// Fail with `EEXIST` if the queue already exists.
+        IPC_EXCL;
+    }
+}
+
+libc_bitflags!{
+    /// Flags for [`msgsnd`](fn.msgsnd.html) and [`msgrcv`](fn.msgrcv.html).
+    pub struct MsgFlag: c_int {
+        /// Fail with `EAGAIN` or `ENOMSG` instead of blocking.
+        IPC_NOWAIT;
+        /// Truncate messages that don't fit into the buffer instead of failing
+        /// with `E2BIG`.
+        MSG_NOERROR;
+        /// Receive the first message whose type is not `msgtyp`.
+        MSG_EXCEPT;
+        /// Copy the message at position `msgtyp` without removing it from the
+        /// queue.
+        MSG_COPY;
+    }
+}
+
+libc_enum!{
+    /// Commands for [`msgctl`](fn.msgctl.html).
+    #[repr(i32)]
+    pub enum MsgctlCmd {
+        /// Remove the queue, waking up any blocked processes.
+        IPC_RMID,
+        /// Write the queue's permissions and size limit from the supplied
+        /// `msqid_ds`.
+        IPC_SET,
+        /// Copy the queue's kernel data structure into the supplied `msqid_ds`.
+        IPC_STAT,
+    }
+}
+
+/// Status information about a message queue, as returned by
+/// [`msg_stat`](fn.msg_stat.html).
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub struct MsqidDs(pub libc::msqid_ds);
+
+impl MsqidDs {
+    /// Number of messages in the queue.
+    pub fn message_count(&self) -> u64 {
+        self.0.msg_qnum as u64
+    }
+
+    /// Maximum number of bytes allowed in the queue.
+    pub fn max_bytes(&self) -> u64 {
+        self.0.msg_qbytes as u64
+    }
+
+    /// Effective user ID of the owner.
+    pub fn uid(&self) -> Uid {
+        Uid::from_raw(self.0.msg_perm.uid)
+    }
+
+    /// Effective group ID of the owner.
+    pub fn gid(&self) -> Gid {
+        Gid::from_raw(self.0.msg_perm.gid)
+    }
+
+    /// Effective user ID of the creator.
+    pub fn creator_uid(&self) -> Uid {
+        Uid::from_raw(self.0.msg_perm.cuid)
+    }
+
+    /// Permission bits of the queue.
+    pub fn mode(&self) -> Mode {
+        Mode::from_bits_truncate(self.0.msg_perm.mode as libc::mode_t)
+    }
+}
+
+/// Size of the `mtype` field that precedes the message text in a `msgbuf`.
+const MTYPE_SIZE: usize = mem::size_of::<c_long>();
+
+/// Get the identifier of the message queue associated with `key`, creating it
+/// if requested by `flag`.
+///
+/// `mode` provides the permission bits of a newly created queue.
+pub fn msgget(key: key_t, flag: MsggetFlag, mode: Mode) -> Result<c_int> {
+    let res = unsafe { libc::msgget(key, flag.bits() | mode.bits() as c_int) };
+
+    Errno::result(res)
+}
+
+/// Send a message of type `mtype` consisting of `payload` to the queue
+/// `msqid`.
+///
+/// `mtype` must be positive.  Blocks while the queue is full unless
+/// `IPC_NOWAIT` is given.
+pub fn msgsnd(msqid: c_int, mtype: i64, payload: &[u8], flag: MsgFlag) -> Result<()> {
+    let mut buf = vec![0u8; MTYPE_SIZE + payload.len()];
+    unsafe { ptr::write_unaligned(buf.as_mut_ptr() as *mut c_long, mtype as c_long) };
+    buf[MTYPE_SIZE..].copy_from_slice(payload);
+
+    let res = unsafe {
+        libc::msgsnd(msqid, buf.as_ptr() as *const c_void, payload.len() as size_t, flag.bits())
+    };
+
+    Errno::result(res).map(drop)
+}
+
+/// Receive a message from the queue `msqid` into `buf`.
+///
+/// A `msgtyp` of zero receives the first message in the queue, a positive one
+/// the first message of that type, and a negative one the first message of
+/// the lowest type not greater than its absolute value.
+///
+/// Returns the type of the message and the number of bytes stored in `buf`.
+/// Messages that don't fit fail with `E2BIG` unless `MSG_NOERROR` is given.
+pub fn msgrcv(msqid: c_int, buf: &mut [u8], msgtyp: i64, flag: MsgFlag) -> Result<(i64, usize)> {
+    let mut msgbuf = vec![0u8; MTYPE_SIZE + buf.len()];
+
+    let res = unsafe {
+        libc::msgrcv(msqid, msgbuf.as_mut_ptr() as *mut c_void, buf.len() as size_t,
+                     msgtyp as c_long, flag.bits())
+    };
+    let len = try!(Errno::result(res)) as usize;
+
+    let mtype = unsafe { ptr::read_unaligned(msgbuf.as_ptr() as *const c_long) };
+    buf[..len].copy_from_slice(&msgbuf[MTYPE_SIZE..MTYPE_SIZE + len]);
+    Ok((mtype as i64, len))
+}
+
+/// Perform the control operation `cmd` on the message queue `msqid`.
+///
+/// `IPC_STAT` and `IPC_SET` require `buf`; see [`msg_stat`](fn.msg_stat.html)
+/// and [`msg_remove`](fn.msg_remove.html) for safe wrappers around the common
+/// cases.
+pub fn msgctl(msqid: c_int, cmd: MsgctlCmd, buf: Option<&mut libc::msqid_ds>) -> Result<c_int> {
+    let buf_ptr = buf.map_or(ptr::null_mut(), |b| b as *mut libc::msqid_ds);
+    let res = unsafe { libc::msgctl(msqid, cmd as c_int, buf_ptr) };
+
+    Errno::result(res)
+}
+
+/// Retrieve status information about the message queue `msqid`.
+pub fn msg_stat(msqid: c_int) -> Result<MsqidDs> {
+    let mut ds: libc::msqid_ds = unsafe { mem::zeroed() };
+    try!(msgctl(msqid, MsgctlCmd::IPC_STAT, Some(&mut ds)));
+
+    Ok(MsqidDs(ds))
+}
+
+/// Remove the message queue `msqid`.
+pub fn msg_remove(msqid: c_int) -> Result<()> {
+    msgctl(msqid, MsgctlCmd::IPC_RMID, None).map(drop)
+}
@@ -0,0 +1,119 @@
+//! Query and set the process execution domain.
+//!
+//! See [`personality(2)`](http://man7.org/linux/man-pages/man2/personality.2.html).
+//! The persona of a process affects how the kernel sets up the address space
+//! of programs it `exec`s, so changes only take full effect after an `exec`.
+use libc::{self, c_int, c_ulong};
+use Result;
+use errno::Errno;
+
+// libc doesn't define the execution domains, so their values are taken from
+// `include/uapi/linux/personality.h`.
+bitflags!{
+    /// Flags altering the execution domain of a process.
+    ///
+    /// The low byte (`PER_MASK`) holds the execution domain itself. Domains are
+    /// values rather than independent bits, so select one by replacing the
+    /// whole byte, e.g. `(persona - Persona::PER_MASK) | Persona::PER_LINUX32`,
+    /// and test for one by comparing `persona & Persona::PER_MASK`. Some
+    /// domains also imply flags outside of the low byte.
+    ///
+    /// Bits unknown to nix are preserved, so a value returned by [`get`] can
+    /// always be passed back to [`set`] unchanged.
+    ///
+    /// [`get`]: fn.get.html
+    /// [`set`]: fn.set.html
+    pub struct Persona: c_int {
+        /// Provide the legacy virtual address space layout.
+        const ADDR_COMPAT_LAYOUT = libc::ADDR_COMPAT_LAYOUT;
+        /// Disable address space layout randomization.
+        const ADDR_NO_RANDOMIZE = libc::ADDR_NO_RANDOMIZE;
+        /// Limit the address space to 32 bits.
+        const ADDR_LIMIT_32BIT = libc::ADDR_LIMIT_32BIT;
+        /// Limit the address space to 3 GB.
+        const ADDR_LIMIT_3GB = libc::ADDR_LIMIT_3GB;
+        /// Treat user-space function pointers as pointers to descriptors.
+        const FDPIC_FUNCPTRS = libc::FDPIC_FUNCPTRS;
+        /// Map page 0 as read-only.
+        const MMAP_PAGE_ZERO = libc::MMAP_PAGE_ZERO;
+        /// Make `PROT_READ` imply `PROT_EXEC` for `mmap`.
+        const READ_IMPLIES_EXEC = libc::READ_IMPLIES_EXEC;
+        /// No effect.
+        const SHORT_INODE = libc::SHORT_INODE;
+        /// No effect.
+        const STICKY_TIMEOUTS = libc::STICKY_TIMEOUTS;
+        /// Report a 2.6.40+ kernel version as 2.6.(40 + n).
+        const UNAME26 = libc::UNAME26;
+        /// No effect.
+        const WHOLE_SECONDS = libc::WHOLE_SECONDS;
+        /// Mask selecting the execution domain.
+        const PER_MASK = 0x00ff;
+        /// The default Linux execution domain.
+        const PER_LINUX = 0x0000;
+        /// Linux with a 32-bit address space.
+        const PER_LINUX_32BIT = 0x0000 | libc::ADDR_LIMIT_32BIT;
+        /// Linux with function descriptors.
+        const PER_LINUX_FDPIC = 0x0000 | libc::FDPIC_FUNCPTRS;
+        /// 32-bit Linux, as reported by `uname` on a 64-bit kernel.
+        const PER_LINUX32 = 0x0008;
+        /// 32-bit Linux with a 3 GB address space.
+        const PER_LINUX32_3GB = 0x0008 | libc::ADDR_LIMIT_3GB;
+        /// SVr4 emulation.
+        const PER_SVR4 = 0x0001 | libc::STICKY_TIMEOUTS | libc::MMAP_PAGE_ZERO;
+        /// SVr3 emulation.
+        const PER_SVR3 = 0x0002 | libc::STICKY_TIMEOUTS | libc::SHORT_INODE;
+        /// SCO SVr3 emulation.
+        const PER_SCOSVR3 = 0x0003 | libc::STICKY_TIMEOUTS | libc::WHOLE_SECONDS | libc::SHORT_INODE;
+        /// SCO OpenServer 5 emulation.
+        const PER_OSR5 = 0x0003 | libc::STICKY_TIMEOUTS | libc::WHOLE_SECONDS;
+        /// Wyse Unix V/386 emulation.
+        const PER_WYSEV386 = 0x0004 | libc::STICKY_TIMEOUTS | libc::SHORT_INODE;
+        /// Interactive Unix emulation.
+        const PER_ISCR4 = 0x0005 | libc::STICKY_TIMEOUTS;
+        /// BSD emulation.
+        const PER_BSD = 0x0006;
+        /// SunOS emulation.
+        const PER_SUNOS = 0x0006 | libc::STICKY_TIMEOUTS;
+        /// Xenix emulation.
+        const PER_XENIX = 0x0007 | libc::STICKY_TIMEOUTS | libc::SHORT_INODE;
+        /// IRIX5 32-bit emulation.
+        const PER_IRIX32 = 0x0009 | libc::STICKY_TIMEOUTS;
+        /// IRIX6 new 32-bit emulation.
+        const PER_IRIXN32 = 0x000a | libc::STICKY_TIMEOUTS;
+        /// IRIX6 64-bit emulation.
+        const PER_IRIX64 = 0x000b | libc::STICKY_TIMEOUTS;
+        /// RISC/os emulation.
+        const PER_RISCOS = 0x000c;
+        /// Solaris emulation.
+        const PER_SOLARIS = 0x000d | libc::STICKY_TIMEOUTS;
+        /// UnixWare 7 emulation.
+        const PER_UW7 = 0x000e | libc::STICKY_TIMEOUTS | libc::MMAP_PAGE_ZERO;
+        /// Digital Unix emulation.
+        const PER_OSF4 = 0x000f;
+        /// HP-UX emulation.
+        const PER_HPUX = 0x0010;
+    }
+}
+
+impl Persona {
+    // Unlike `from_bits_truncate`, keeps flags nix does not know about.
+    fn from_raw(bits: c_int) -> Persona {
+        Persona { bits: bits }
+    }
+}
+
+/// Retrieve the current persona of the calling process.
+pub fn get() -> Result<Persona> {
+    let res = unsafe { libc::personality(0xFFFFFFFF as c_ulong) };
+
+    Errno::result(res).map(Persona::from_raw)
+}
+
+/// Set the persona of the calling process.
+///
+/// Returns the previous persona, so that it can be restored later.
+pub fn set(persona: Persona) -> Result<Persona> {
+    let res = unsafe { libc::personality(persona.bits() as c_ulong) };
+
+    Errno::result(res).map(Persona::from_raw)
+}
@@ -354,3 +354,91 @@ pub fn step<T: Into<Option<Signal>>>(pid: Pid, sig: T) -> Result<()> {
         ptrace_other(Request::PTRACE_SINGLESTEP, pid, ptr::null_mut(), data).map(|_| ())
     }
 }
+
+/// `PTRACE_GET_SYSCALL_INFO`, available since Linux 5.3
+const PTRACE_GET_SYSCALL_INFO: RequestType = 0x420e;
+
+/// The system call a tracee is stopped at, as returned by
+/// [`get_syscall_info`](fn.get_syscall_info.html).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SyscallInfo {
+    /// The tracee is stopped on entry to system call number `nr`.
+    Entry {
+        /// The system call number.
+        nr: u64,
+        /// The arguments of the system call.
+        args: [u64; 6],
+    },
+    /// The tracee is stopped on exit from a system call.
+    Exit {
+        /// The return value, or the negated error number if `is_error` is set.
+        rval: i64,
+        /// Whether the system call failed.
+        is_error: bool,
+    },
+    /// The tracee is stopped by a `SECCOMP_RET_TRACE` filter on entry to
+    /// system call number `nr`.
+    Seccomp {
+        /// The system call number.
+        nr: u64,
+        /// The arguments of the system call.
+        args: [u64; 6],
+        /// The `SECCOMP_RET_DATA` part of the filter's return value.
+        ret_data: u32,
+    },
+    /// The tracee isn't stopped at a system call.
+    None,
+}
+
+/// Layout of `struct ptrace_syscall_info`, with the union of the per-stop
+/// records flattened into `data`.
+#[repr(C)]
+#[allow(dead_code)]
+struct RawSyscallInfo {
+    op: u8,
+    pad: [u8; 3],
+    arch: u32,
+    instruction_pointer: u64,
+    stack_pointer: u64,
+    data: [u64; 8],
+}
+
+/// Get information about the system call the tracee is stopped at, as with
+/// `ptrace(PTRACE_GET_SYSCALL_INFO, ...)`.
+///
+/// Unlike reading the registers, this works the same on all architectures.
+/// Kernels older than 5.3 fail with `EIO`.
+pub fn get_syscall_info(pid: Pid) -> Result<SyscallInfo> {
+    let mut raw: RawSyscallInfo = unsafe { mem::zeroed() };
+    let res = unsafe {
+        libc::ptrace(PTRACE_GET_SYSCALL_INFO,
+                     libc::pid_t::from(pid),
+                     mem::size_of::<RawSyscallInfo>() as *mut c_void,
+                     &mut raw as *mut RawSyscallInfo as *mut c_void)
+    };
+    // The kernel returns the size of the record it has, and copies no more
+    // than fits into the buffer.  The buffer is zeroed, so fields missing
+    // from a shorter record read as zero.
+    try!(Errno::result(res));
+
+    let args = [raw.data[1], raw.data[2], raw.data[3], raw.data[4], raw.data[5], raw.data[6]];
+    let info = match raw.op {
+        // PTRACE_SYSCALL_INFO_ENTRY
+        1 => SyscallInfo::Entry { nr: raw.data[0], args: args },
+        // PTRACE_SYSCALL_INFO_EXIT
+        2 => {
+            // `is_error` is the byte following the 64-bit `rval`.
+            let is_error = unsafe { *(&raw.data[1] as *const u64 as *const u8) };
+            SyscallInfo::Exit { rval: raw.data[0] as i64, is_error: is_error != 0 }
+        },
+        // PTRACE_SYSCALL_INFO_SECCOMP
+        3 => {
+            // `ret_data` is the 32-bit field following the arguments.
+            let ret_data = unsafe { *(&raw.data[7] as *const u64 as *const u32) };
+            SyscallInfo::Seccomp { nr: raw.data[0], args: args, ret_data: ret_data }
+        },
+        _ => SyscallInfo::None,
+    };
+
+    Ok(info)
+}
@@ -13,7 +13,7 @@
 //! quotactl_set(QuotaType::USRQUOTA, "/dev/sda1", 50, &dqblk, QuotaValidFlags::QIF_BLIMITS);
 //! ```
 use std::default::Default;
-use std::{mem, ptr};
+use std::ptr;
 use libc::{self, c_int, c_char};
 use {Result, NixPath};
 use errno::Errno;
@@ -130,6 +130,7 @@ impl Dqblk {
     /// Set the absolute limit on disk quota blocks allocated.
     pub fn set_blocks_hard_limit(&mut self, limit: u64) {
         self.0.dqb_bhardlimit = limit;
+        self.0.dqb_valid |= QuotaValidFlags::QIF_BLIMITS.bits();
     }
 
     /// Preferred limit on disk quota blocks
@@ -145,6 +146,7 @@ impl Dqblk {
     /// Set the preferred limit on disk quota blocks allocated.
     pub fn set_blocks_soft_limit(&mut self, limit: u64) {
         self.0.dqb_bsoftlimit = limit;
+        self.0.dqb_valid |= QuotaValidFlags::QIF_BLIMITS.bits();
     }
 
     /// Current occupied space (bytes).
@@ -170,6 +172,7 @@ impl Dqblk {
     /// Set the maximum number of allocated inodes.
     pub fn set_inodes_hard_limit(&mut self, limit: u64) {
         self.0.dqb_ihardlimit = limit;
+        self.0.dqb_valid |= QuotaValidFlags::QIF_ILIMITS.bits();
     }
 
     /// Preferred inode limit
@@ -185,6 +188,7 @@ impl Dqblk {
     /// Set the preferred limit of allocated inodes.
     pub fn set_inodes_soft_limit(&mut self, limit: u64) {
         self.0.dqb_isoftlimit = limit;
+        self.0.dqb_valid |= QuotaValidFlags::QIF_ILIMITS.bits();
     }
 
     /// Current number of allocated inodes.
@@ -210,6 +214,7 @@ impl Dqblk {
     /// Set the time limit for excessive disk use.
     pub fn set_block_time_limit(&mut self, limit: u64) {
         self.0.dqb_btime = limit;
+        self.0.dqb_valid |= QuotaValidFlags::QIF_BTIME.bits();
     }
 
     /// Time limit for excessive files.
@@ -225,6 +230,7 @@ impl Dqblk {
     /// Set the time limit for excessive files.
     pub fn set_inode_time_limit(&mut self, limit: u64) {
         self.0.dqb_itime = limit;
+        self.0.dqb_valid |= QuotaValidFlags::QIF_ITIME.bits();
     }
 }
 
@@ -263,9 +269,9 @@ pub fn quotactl_sync<P: ?Sized + NixPath>(which: QuotaType, special: Option<&P>)
 
 /// Get disk quota limits and current usage for the given user/group id.
 pub fn quotactl_get<P: ?Sized + NixPath>(which: QuotaType, special: &P, id: c_int) -> Result<Dqblk> {
-    let mut dqblk = unsafe { mem::uninitialized() };
-    quotactl(QuotaCmd(QuotaSubCmd::Q_GETQUOTA, which), Some(special), id, &mut dqblk as *mut _ as *mut c_char)?;
-    dqblk
+    let mut dqblk: Dqblk = Default::default();
+    try!(quotactl(QuotaCmd(QuotaSubCmd::Q_GETQUOTA, which), Some(special), id, &mut dqblk as *mut _ as *mut c_char));
+    Ok(dqblk)
 }
 
 /// Configure quota values for the specified fields for a given user/group id.
@@ -274,3 +280,38 @@ pub fn quotactl_set<P: ?Sized + NixPath>(which: QuotaType, special: &P, id: c_in
     dqblk_copy.0.dqb_valid = fields.bits();
     quotactl(QuotaCmd(QuotaSubCmd::Q_SETQUOTA, which), Some(special), id, &mut dqblk_copy as *mut _ as *mut c_char)
 }
+
+#[cfg(test)]
+mod test {
+    use libc;
+    use sys::quota::*;
+    use super::{QuotaCmd, QuotaSubCmd};
+
+    #[test]
+    fn test_quota_cmd_encoding() {
+        let cmd = QuotaCmd(QuotaSubCmd::Q_GETQUOTA, QuotaType::GRPQUOTA);
+        assert_eq!(cmd.as_int(), (libc::Q_GETQUOTA << 8) | (libc::GRPQUOTA & 0xff));
+        let cmd = QuotaCmd(QuotaSubCmd::Q_QUOTAON, QuotaType::USRQUOTA);
+        assert_eq!(cmd.as_int(), (libc::Q_QUOTAON << 8) | (libc::USRQUOTA & 0xff));
+    }
+
+    #[test]
+    fn test_dqblk_round_trip() {
+        let mut dqblk: Dqblk = Default::default();
+        assert_eq!(dqblk.blocks_hard_limit(), None);
+        assert_eq!(dqblk.inodes_soft_limit(), None);
+
+        dqblk.set_blocks_hard_limit(10000);
+        dqblk.set_blocks_soft_limit(8000);
+        assert_eq!(dqblk.blocks_hard_limit(), Some(10000));
+        assert_eq!(dqblk.blocks_soft_limit(), Some(8000));
+        // Setting block limits doesn't touch the inode limits
+        assert_eq!(dqblk.inodes_hard_limit(), None);
+
+        dqblk.set_inodes_hard_limit(100);
+        dqblk.set_inode_time_limit(3600);
+        assert_eq!(dqblk.inodes_hard_limit(), Some(100));
+        assert_eq!(dqblk.inode_time_limit(), Some(3600));
+        assert_eq!(dqblk.block_time_limit(), None);
+    }
+}
@@ -13,15 +13,29 @@ libc_enum! {
     /// enabling/disabling Ctrl-Alt-Delete.
     #[repr(i32)]
     pub enum RebootMode {
+        /// Halt the system.
         RB_HALT_SYSTEM,
+        /// Execute a kernel previously loaded with `kexec_load`.
         RB_KEXEC,
+        /// Stop the system and remove all power from it, if possible.
         RB_POWER_OFF,
+        /// Restart the system.
         RB_AUTOBOOT,
         // we do not support Restart2,
+        /// Suspend the system to disk.
         RB_SW_SUSPEND,
     }
 }
 
+/// Reboot, halt or power off the system (see
+/// [reboot(2)](http://man7.org/linux/man-pages/man2/reboot.2.html)).
+///
+/// Requires the `CAP_SYS_BOOT` capability; otherwise this fails with `EPERM`.
+/// Buffered data is not synced to disk first.
+///
+/// On success this call does not return.  The exception is `RB_SW_SUSPEND`,
+/// which returns once the system resumes; in that case the result is an
+/// `Err` holding whatever `errno` value was left over.
 pub fn reboot(how: RebootMode) -> Result<Void> {
     unsafe {
         libc::reboot(how as libc::c_int)
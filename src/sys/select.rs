@@ -1,5 +1,5 @@
 use std::mem;
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::ptr::{null, null_mut};
 use libc::{self, c_int};
 use Result;
@@ -26,6 +26,33 @@ impl FdSet {
         unsafe { libc::FD_SET(fd, &mut self.0) };
     }
 
+    /// Adds the file descriptors of all of `fds`, which may be any types
+    /// owning a file descriptor, such as `std::net::TcpStream`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate nix;
+    /// # use std::os::unix::io::AsRawFd;
+    /// # use std::os::unix::net::UnixStream;
+    /// # use nix::sys::select::FdSet;
+    /// # fn main() {
+    /// let (a, b) = UnixStream::pair().unwrap();
+    /// let streams = [a, b];
+    /// let mut set = FdSet::new();
+    /// set.insert_all(&streams);
+    /// assert!(set.contains(streams[1].as_raw_fd()));
+    /// # }
+    /// ```
+    pub fn insert_all<'a, I, F>(&mut self, fds: I)
+        where I: IntoIterator<Item = &'a F>,
+              F: AsRawFd + 'a
+    {
+        for fd in fds {
+            self.insert(fd.as_raw_fd());
+        }
+    }
+
     pub fn remove(&mut self, fd: RawFd) {
         unsafe { libc::FD_CLR(fd, &mut self.0) };
     }
@@ -0,0 +1,172 @@
+//! System V semaphore sets.
+//!
+//! See [`semget(2)`](http://man7.org/linux/man-pages/man2/semget.2.html),
+//! [`semop(2)`](http://man7.org/linux/man-pages/man2/semop.2.html) and
+//! [`semctl(2)`](http://man7.org/linux/man-pages/man2/semctl.2.html).
+use std::mem;
+use libc::{self, c_int, c_short, c_ushort, key_t, size_t};
+use {Error, Result};
+use errno::Errno;
+use sys::stat::Mode;
+use sys::time::TimeSpec;
+
+pub use sys::shm::IPC_PRIVATE;
+
+libc_bitflags!{
+    /// Flags for [`semget`](fn.semget.html).
+    pub struct SemgetFlag: c_int {
+        /// Create the semaphore set if it doesn't already exist.
+        IPC_CREAT;
+        /// Fail with `EEXIST` if the semaphore set already exists.
+        IPC_EXCL;
+    }
+}
+
+libc_bitflags!{
+    /// Flags for an individual semaphore operation.
+    pub struct SemFlag: c_int {
+        /// Fail with `EAGAIN` instead of blocking.
+        IPC_NOWAIT;
+        /// Undo the operation automatically when the process exits.
+        SEM_UNDO;
+    }
+}
+
+/// A single operation on one semaphore of a set, as passed to
+/// [`semop`](fn.semop.html).
+#[repr(C)]
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub struct Sembuf(libc::sembuf);
+
+impl Sembuf {
+    /// Create an operation adding `sem_op` to semaphore number `sem_num`.
+    ///
+    /// A positive `sem_op` releases the semaphore, a negative one acquires it
+    /// and zero waits for the semaphore value to become zero.
+    pub fn new(sem_num: u16, sem_op: i16, flags: SemFlag) -> Sembuf {
+        Sembuf(libc::sembuf {
+            sem_num: sem_num as c_ushort,
+            sem_op: sem_op as c_short,
+            sem_flg: flags.bits() as c_short,
+        })
+    }
+
+    /// Index of the semaphore within the set.
+    pub fn sem_num(&self) -> u16 {
+        self.0.sem_num as u16
+    }
+
+    /// Amount to add to the semaphore value.
+    pub fn sem_op(&self) -> i16 {
+        self.0.sem_op as i16
+    }
+
+    /// Flags for this operation.
+    pub fn flags(&self) -> SemFlag {
+        SemFlag::from_bits_truncate(self.0.sem_flg as c_int)
+    }
+}
+
+/// Get the identifier of the semaphore set associated with `key`, creating a
+/// set of `nsems` semaphores if requested by `flag`.
+///
+/// `mode` provides the permission bits of a newly created set.
+pub fn semget(key: key_t, nsems: c_int, flag: SemgetFlag, mode: Mode) -> Result<c_int> {
+    let res = unsafe { libc::semget(key, nsems, flag.bits() | mode.bits() as c_int) };
+
+    Errno::result(res)
+}
+
+/// Atomically perform all of `sops` on the semaphore set `semid`.
+pub fn semop(semid: c_int, sops: &[Sembuf]) -> Result<()> {
+    let res = unsafe {
+        libc::semop(semid, sops.as_ptr() as *mut libc::sembuf, sops.len() as size_t)
+    };
+
+    Errno::result(res).map(drop)
+}
+
+mod ffi {
+    use libc::{c_int, sembuf, size_t, timespec};
+
+    // libc doesn't declare semtimedop, and there is no semtimedop system call
+    // to fall back to on architectures that multiplex System V IPC through
+    // ipc(2), such as i686 and MIPS o32.
+    extern {
+        pub fn semtimedop(semid: c_int, sops: *mut sembuf, nsops: size_t,
+                          timeout: *const timespec) -> c_int;
+    }
+}
+
+/// Like [`semop`](fn.semop.html), but give up with `EAGAIN` if the operations
+/// could not be performed within `timeout`.
+pub fn semtimedop(semid: c_int, sops: &[Sembuf], timeout: &TimeSpec) -> Result<()> {
+    let res = unsafe {
+        ffi::semtimedop(semid, sops.as_ptr() as *mut libc::sembuf, sops.len() as size_t,
+                        timeout.as_ref() as *const libc::timespec)
+    };
+
+    Errno::result(res).map(drop)
+}
+
+/// Commands for [`semctl`](fn.semctl.html), along with their arguments.
+#[allow(missing_debug_implementations)]
+pub enum SemctlArg<'a> {
+    /// Return the value of the given semaphore.
+    GETVAL(u16),
+    /// Set the value of the given semaphore.
+    SETVAL(u16, c_int),
+    /// Read the values of all semaphores in the set.
+    ///
+    /// The slice must have exactly one element per semaphore.
+    GETALL(&'a mut [c_ushort]),
+    /// Set the values of all semaphores in the set.
+    ///
+    /// The slice must have exactly one element per semaphore.
+    SETALL(&'a [c_ushort]),
+    /// Remove the semaphore set, waking up any blocked processes.
+    IPC_RMID,
+}
+pub use self::SemctlArg::*;
+
+/// Number of semaphores in the set `semid`.
+fn sem_nsems(semid: c_int) -> Result<usize> {
+    let mut ds: libc::semid_ds = unsafe { mem::zeroed() };
+    let res = unsafe {
+        libc::semctl(semid, 0, libc::IPC_STAT, &mut ds as *mut libc::semid_ds)
+    };
+    try!(Errno::result(res));
+
+    Ok(ds.sem_nsems as usize)
+}
+
+/// Perform a control operation on the semaphore set `semid`.
+///
+/// Returns the semaphore value for `GETVAL` and zero otherwise.
+pub fn semctl(semid: c_int, arg: SemctlArg) -> Result<c_int> {
+    // The kernel reads or writes one value per semaphore for GETALL and
+    // SETALL, so refuse buffers that don't match the size of the set.
+    let expected = match arg {
+        GETALL(ref vals) => Some(vals.len()),
+        SETALL(vals) => Some(vals.len()),
+        _ => None,
+    };
+    if let Some(len) = expected {
+        if try!(sem_nsems(semid)) != len {
+            return Err(Error::Sys(Errno::EINVAL));
+        }
+    }
+
+    let res = unsafe {
+        match arg {
+            GETVAL(semnum) => libc::semctl(semid, semnum as c_int, libc::GETVAL),
+            SETVAL(semnum, val) => libc::semctl(semid, semnum as c_int, libc::SETVAL, val),
+            GETALL(vals) => libc::semctl(semid, 0, libc::GETALL, vals.as_mut_ptr()),
+            SETALL(vals) => libc::semctl(semid, 0, libc::SETALL, vals.as_ptr()),
+            IPC_RMID => libc::semctl(semid, 0, libc::IPC_RMID),
+        }
+    };
+
+    Errno::result(res)
+}
@@ -0,0 +1,166 @@
+//! System V shared memory segments.
+//!
+//! See [`shmget(2)`](http://man7.org/linux/man-pages/man2/shmget.2.html) and
+//! [`shmctl(2)`](http://man7.org/linux/man-pages/man2/shmctl.2.html).
+use std::{mem, ptr};
+use libc::{self, c_int, c_void, key_t, size_t};
+use {Error, Result, NixPath};
+use errno::Errno;
+use sys::stat::Mode;
+use unistd::{Gid, Uid};
+
+/// Key requesting a new segment that can only be shared via its identifier.
+pub const IPC_PRIVATE: key_t = libc::IPC_PRIVATE;
+
+libc_bitflags!{
+    /// Flags for [`shmget`](fn.shmget.html).
+    pub struct ShmgetFlag: c_int {
+        /// Create the segment if it doesn't already exist.
+        IPC_CREAT;
+        /// Fail with `EEXIST` if the segment already exists.
+        IPC_EXCL;
+        /// Allocate the segment using huge pages.
+        SHM_HUGETLB;
+        /// Don't reserve swap space for the segment.
+        SHM_NORESERVE;
+    }
+}
+
+libc_bitflags!{
+    /// Flags for [`shmat`](fn.shmat.html).
+    pub struct ShmatFlag: c_int {
+        /// Attach the segment for read-only access.
+        SHM_RDONLY;
+        /// Round the attach address down to a multiple of `SHMLBA`.
+        SHM_RND;
+    }
+}
+
+libc_enum!{
+    /// Commands for [`shmctl`](fn.shmctl.html).
+    #[repr(i32)]
+    pub enum ShmctlCmd {
+        /// Mark the segment to be destroyed once the last process detaches.
+        IPC_RMID,
+        /// Write the segment's permissions from the supplied `shmid_ds`.
+        IPC_SET,
+        /// Copy the segment's kernel data structure into the supplied `shmid_ds`.
+        IPC_STAT,
+    }
+}
+
+/// Status information about a shared memory segment, as returned by
+/// [`shm_stat`](fn.shm_stat.html).
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub struct ShmidDs(pub libc::shmid_ds);
+
+impl ShmidDs {
+    /// Size of the segment in bytes.
+    pub fn size(&self) -> usize {
+        self.0.shm_segsz as usize
+    }
+
+    /// Number of current attaches.
+    pub fn attach_count(&self) -> u64 {
+        self.0.shm_nattch as u64
+    }
+
+    /// Effective user ID of the owner.
+    pub fn uid(&self) -> Uid {
+        Uid::from_raw(self.0.shm_perm.uid)
+    }
+
+    /// Effective group ID of the owner.
+    pub fn gid(&self) -> Gid {
+        Gid::from_raw(self.0.shm_perm.gid)
+    }
+
+    /// Effective user ID of the creator.
+    pub fn creator_uid(&self) -> Uid {
+        Uid::from_raw(self.0.shm_perm.cuid)
+    }
+
+    /// Permission bits of the segment.
+    pub fn mode(&self) -> Mode {
+        Mode::from_bits_truncate(self.0.shm_perm.mode as libc::mode_t)
+    }
+}
+
+/// Generate a System V IPC key from an existing file and a project identifier.
+///
+/// Only the low 8 bits of `id` are used and they must not all be zero.
+pub fn ftok<P: ?Sized + NixPath>(path: &P, id: u8) -> Result<key_t> {
+    let res = try!(path.with_nix_path(|cstr| {
+        unsafe { libc::ftok(cstr.as_ptr(), id as c_int) }
+    }));
+
+    Errno::result(res)
+}
+
+/// Get the identifier of the shared memory segment associated with `key`,
+/// creating it if requested by `flag`.
+///
+/// `mode` provides the permission bits of a newly created segment.
+pub fn shmget(key: key_t, size: usize, flag: ShmgetFlag, mode: Mode) -> Result<c_int> {
+    let res = unsafe {
+        libc::shmget(key, size as size_t, flag.bits() | mode.bits() as c_int)
+    };
+
+    Errno::result(res)
+}
+
+/// Attach the shared memory segment `shmid` to the address space of the
+/// calling process.
+///
+/// If `addr` is `None` the kernel chooses a suitable address.
+///
+/// # Safety
+///
+/// The returned memory may be modified by other processes at any time.
+pub unsafe fn shmat(shmid: c_int, addr: Option<*const c_void>, flag: ShmatFlag) -> Result<*mut c_void> {
+    let ret = libc::shmat(shmid, addr.unwrap_or(ptr::null()), flag.bits());
+
+    if ret as isize == -1 {
+        Err(Error::Sys(Errno::last()))
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Detach the shared memory segment attached at `addr`.
+///
+/// # Safety
+///
+/// `addr` must have been returned by [`shmat`](fn.shmat.html), and no
+/// references into the segment may be used afterwards.
+pub unsafe fn shmdt(addr: *const c_void) -> Result<()> {
+    Errno::result(libc::shmdt(addr)).map(drop)
+}
+
+/// Perform the control operation `cmd` on the shared memory segment `shmid`.
+///
+/// `IPC_STAT` and `IPC_SET` require `buf`; see [`shm_stat`](fn.shm_stat.html)
+/// and [`shm_remove`](fn.shm_remove.html) for safe wrappers around the common
+/// cases.
+pub fn shmctl(shmid: c_int, cmd: ShmctlCmd, buf: Option<&mut libc::shmid_ds>) -> Result<c_int> {
+    let buf_ptr = buf.map_or(ptr::null_mut(), |b| b as *mut libc::shmid_ds);
+    let res = unsafe { libc::shmctl(shmid, cmd as c_int, buf_ptr) };
+
+    Errno::result(res)
+}
+
+/// Retrieve status information about the shared memory segment `shmid`.
+pub fn shm_stat(shmid: c_int) -> Result<ShmidDs> {
+    let mut ds: libc::shmid_ds = unsafe { mem::zeroed() };
+    try!(shmctl(shmid, ShmctlCmd::IPC_STAT, Some(&mut ds)));
+
+    Ok(ShmidDs(ds))
+}
+
+/// Mark the shared memory segment `shmid` for destruction.
+///
+/// The segment is destroyed once the last process detaches from it.
+pub fn shm_remove(shmid: c_int) -> Result<()> {
+    shmctl(shmid, ShmctlCmd::IPC_RMID, None).map(drop)
+}
@@ -6,6 +6,7 @@
 use libc;
 use {Error, Result};
 use errno::Errno;
+use std::marker::PhantomData;
 use std::mem;
 #[cfg(any(target_os = "dragonfly", target_os = "freebsd"))]
 use std::os::unix::io::RawFd;
@@ -339,6 +340,24 @@ impl SigSet {
         Ok(oldmask)
     }
 
+    /// Adds the set of signals to the signal mask for the calling thread until
+    /// the returned guard is dropped.
+    pub fn thread_block_scoped(&self) -> Result<SignalMaskGuard> {
+        self.thread_swap_mask(SigmaskHow::SIG_BLOCK).map(SignalMaskGuard::new)
+    }
+
+    /// Removes the set of signals from the signal mask for the calling thread
+    /// until the returned guard is dropped.
+    pub fn thread_unblock_scoped(&self) -> Result<SignalMaskGuard> {
+        self.thread_swap_mask(SigmaskHow::SIG_UNBLOCK).map(SignalMaskGuard::new)
+    }
+
+    /// Sets the set of signals as the signal mask for the calling thread until
+    /// the returned guard is dropped.
+    pub fn thread_set_mask_scoped(&self) -> Result<SignalMaskGuard> {
+        self.thread_swap_mask(SigmaskHow::SIG_SETMASK).map(SignalMaskGuard::new)
+    }
+
     /// Suspends execution of the calling thread until one of the signals in the
     /// signal mask becomes pending, and returns the accepted signal.
     pub fn wait(&self) -> Result<Signal> {
@@ -355,6 +374,34 @@ impl AsRef<libc::sigset_t> for SigSet {
     }
 }
 
+/// Restores the signal mask of the calling thread when dropped.
+///
+/// Returned by [`SigSet::thread_block_scoped`](struct.SigSet.html#method.thread_block_scoped)
+/// and its siblings.  The signal mask is per thread, so the guard can't be
+/// sent to other threads.  Errors while restoring the mask are ignored.
+#[allow(missing_debug_implementations)]
+pub struct SignalMaskGuard {
+    prev: SigSet,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl SignalMaskGuard {
+    fn new(prev: SigSet) -> SignalMaskGuard {
+        SignalMaskGuard { prev: prev, _not_send: PhantomData }
+    }
+
+    /// The signal mask that will be restored.
+    pub fn previous(&self) -> &SigSet {
+        &self.prev
+    }
+}
+
+impl Drop for SignalMaskGuard {
+    fn drop(&mut self) {
+        let _ = self.prev.thread_set_mask();
+    }
+}
+
 /// A signal handler.
 #[allow(unknown_lints)]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -473,7 +520,11 @@ pub fn pthread_sigmask(how: SigmaskHow,
                                                 |os| &mut os.sigset as *mut libc::sigset_t))
     };
 
-    Errno::result(res).map(drop)
+    // pthread_sigmask returns the error number instead of setting errno
+    match res {
+        0 => Ok(()),
+        errno => Err(Error::Sys(Errno::from_i32(errno))),
+    }
 }
 
 /// Examine and change blocked signals.
@@ -647,6 +647,21 @@ impl UnixAddr {
         }
     }
 
+    /// Create a new `sockaddr_un` without a name.
+    ///
+    /// This is the address of unbound and unnamed sockets.  On Linux, binding a
+    /// socket to it makes the kernel assign it a unique abstract name
+    /// ("autobind"), which can be read back with
+    /// [`getsockname`](fn.getsockname.html).
+    pub fn new_unnamed() -> UnixAddr {
+        let ret = libc::sockaddr_un {
+            sun_family: AddressFamily::Unix as sa_family_t,
+            .. unsafe { mem::zeroed() }
+        };
+
+        UnixAddr(ret, 0)
+    }
+
     fn sun_path(&self) -> &[u8] {
         unsafe { slice::from_raw_parts(self.0.sun_path.as_ptr() as *const u8, self.1) }
     }
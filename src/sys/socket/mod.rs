@@ -4,8 +4,13 @@
 use {Error, Result};
 use errno::Errno;
 use libc::{self, c_void, c_int, socklen_t, size_t};
-use std::{fmt, mem, ptr, slice};
-use std::os::unix::io::RawFd;
+use std::{cmp, fmt, mem, net, ptr, slice};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::ffi::OsStr;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{FromRawFd, RawFd};
+use fd::OwnedFd;
 use sys::time::TimeVal;
 use sys::uio::IoVec;
 
@@ -42,6 +47,16 @@ pub use libc::{
     sockaddr_un,
 };
 
+// The origin of zero copy completions on the error queue, from
+// `include/uapi/linux/errqueue.h`, which libc doesn't expose.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SO_EE_ORIGIN_ZEROCOPY: u8 = 5;
+
+// The control message type of security labels, from
+// `include/linux/socket.h`, which libc doesn't expose.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const SCM_SECURITY: c_int = 0x03;
+
 /// These constants are used to specify the communication semantics
 /// when creating a socket with [`socket()`](fn.socket.html)
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -169,6 +184,12 @@ libc_bitflags!{
                   target_os = "netbsd",
                   target_os = "openbsd"))]
         MSG_CMSG_CLOEXEC;
+        /// Send the data without copying it, if possible, on a socket with
+        /// the [`ZeroCopy`](sockopt/struct.ZeroCopy.html) option enabled.
+        /// The buffers must not be modified until a completion is read from
+        /// the error queue with `MSG_ERRQUEUE`.
+        #[cfg(target_os = "linux")]
+        MSG_ZEROCOPY;
     }
 }
 
@@ -234,6 +255,24 @@ impl IpMembershipRequest {
             imr_interface: interface.unwrap_or_else(Ipv4Addr::any).0,
         })
     }
+
+    /// Instantiate a new `IpMembershipRequest` from `std::net` addresses
+    ///
+    /// `interface` is the address of the local interface to join the group
+    /// on, as for [`new`](#method.new).
+    pub fn from_std(group: &net::Ipv4Addr, interface: Option<&net::Ipv4Addr>) -> Self {
+        IpMembershipRequest::new(Ipv4Addr::from_std(group), interface.map(Ipv4Addr::from_std))
+    }
+
+    /// The multicast group
+    pub fn multiaddr(&self) -> Ipv4Addr {
+        Ipv4Addr(self.0.imr_multiaddr)
+    }
+
+    /// The address of the local interface
+    pub fn interface(&self) -> Ipv4Addr {
+        Ipv4Addr(self.0.imr_interface)
+    }
 }
 
 impl PartialEq for IpMembershipRequest {
@@ -262,12 +301,39 @@ pub struct Ipv6MembershipRequest(libc::ipv6_mreq);
 
 impl Ipv6MembershipRequest {
     /// Instantiate a new `Ipv6MembershipRequest`
+    ///
+    /// The kernel picks the interface to join the group on.
     pub fn new(group: Ipv6Addr) -> Self {
+        Ipv6MembershipRequest::with_interface(group, 0)
+    }
+
+    /// Instantiate a new `Ipv6MembershipRequest` joining the group on the
+    /// interface with index `interface`
+    ///
+    /// An index of zero lets the kernel pick the interface.  See
+    /// [`if_nametoindex`](../../net/if_/fn.if_nametoindex.html).
+    pub fn with_interface(group: Ipv6Addr, interface: u32) -> Self {
         Ipv6MembershipRequest(libc::ipv6_mreq {
             ipv6mr_multiaddr: group.0,
-            ipv6mr_interface: 0,
+            ipv6mr_interface: interface as _,
         })
     }
+
+    /// Instantiate a new `Ipv6MembershipRequest` from a `std::net` address,
+    /// as for [`with_interface`](#method.with_interface)
+    pub fn from_std(group: &net::Ipv6Addr, interface: u32) -> Self {
+        Ipv6MembershipRequest::with_interface(Ipv6Addr::from_std(group), interface)
+    }
+
+    /// The multicast group
+    pub fn multiaddr(&self) -> Ipv6Addr {
+        Ipv6Addr(self.0.ipv6mr_multiaddr)
+    }
+
+    /// The index of the interface
+    pub fn interface(&self) -> u32 {
+        self.0.ipv6mr_interface as u32
+    }
 }
 
 impl PartialEq for Ipv6MembershipRequest {
@@ -301,6 +367,15 @@ unsafe fn copy_bytes<'a, 'b, T: ?Sized>(src: &T, dst: &'a mut &'b mut [u8]) {
     mem::swap(dst, &mut remainder);
 }
 
+/// Skip the first `len` bytes of the byte slice dst, updating the slice to
+/// point to the remainder of dst only.
+fn skip_bytes<'a, 'b>(len: usize, dst: &'a mut &'b mut [u8]) {
+    let mut tmpdst = &mut [][..];
+    mem::swap(&mut tmpdst, dst);
+    let (_skipped, mut remainder) = tmpdst.split_at_mut(len);
+    mem::swap(dst, &mut remainder);
+}
+
 cfg_if! {
     // Darwin and DragonFly BSD always align struct cmsghdr to 32-bit only.
     if #[cfg(any(target_os = "dragonfly", target_os = "ios", target_os = "macos"))] {
@@ -340,87 +415,204 @@ impl<T> CmsgSpace<T> {
     }
 }
 
+/// Compute the type of a [`CmsgSpace`](sys/socket/struct.CmsgSpace.html)
+/// with room for control messages with the given payload types.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __nix_cmsg_space_ty {
+    ( $t:ty ) => {
+        $crate::sys::socket::CmsgSpace<$t>
+    };
+    ( $t:ty, $($rest:ty),+ ) => {
+        $crate::sys::socket::CmsgSpace<($t, __nix_cmsg_space_ty!($($rest),+))>
+    };
+}
+
+/// Create a [`CmsgSpace`](sys/socket/struct.CmsgSpace.html) with room for
+/// one control message per given payload type, to be passed to
+/// [`recvmsg`](sys/socket/fn.recvmsg.html).
+///
+/// Its size is the sum of `CMSG_SPACE` for each of the payloads.  File
+/// descriptors received with `SCM_RIGHTS` are given as an array of
+/// `RawFd`s holding the most descriptors expected in a single message.
+///
+/// ```
+/// # #[macro_use] extern crate nix;
+/// use std::os::unix::io::RawFd;
+/// use nix::sys::time::TimeVal;
+///
+/// # fn main() {
+/// // Room for up to three file descriptors and a timestamp
+/// let cmsgspace = cmsg_space!([RawFd; 3], TimeVal);
+/// # drop(cmsgspace);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cmsg_space {
+    ( $($t:ty),+ ) => {
+        <__nix_cmsg_space_ty!($($t),+)>::new()
+    };
+}
+
+/// A message received by [`recvmsg`](fn.recvmsg.html)
 #[allow(missing_debug_implementations)]
 pub struct RecvMsg<'a> {
-    // The number of bytes received.
+    /// The number of bytes received.
     pub bytes: usize,
     cmsg_buffer: &'a [u8],
+    /// The address of the sender, if the socket is not connected.
     pub address: Option<SockAddr>,
+    /// Flags describing the received message.  `MSG_CTRUNC` is set if the
+    /// control message buffer was too small.
     pub flags: MsgFlags,
 }
 
 impl<'a> RecvMsg<'a> {
     /// Iterate over the valid control messages pointed to by this
     /// msghdr.
+    ///
+    /// If the control message buffer was too small for the received
+    /// control messages, the kernel sets `MSG_CTRUNC` in
+    /// [`flags`](#structfield.flags) and fits what it can into the buffer,
+    /// cutting short or dropping messages.  Which message was affected can't
+    /// be told afterwards, so once the buffer was truncated, its last message
+    /// is yielded as [`CmsgError::Truncated`](enum.CmsgError.html) rather
+    /// than as a possibly partial message.  Any file descriptors received
+    /// are installed in the process regardless, and have to be closed by the
+    /// caller even then.
     pub fn cmsgs(&self) -> CmsgIterator {
         CmsgIterator {
             buf: self.cmsg_buffer,
-            next: 0
+            truncated: self.flags.contains(MsgFlags::MSG_CTRUNC),
         }
     }
 }
 
+/// Error yielded by [`CmsgIterator`](struct.CmsgIterator.html) for control
+/// messages that couldn't be received whole.  More variants may be added to
+/// this enum; do not exhaustively pattern-match it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CmsgError<'a> {
+    /// The control message buffer passed to `recvmsg` was too small, so a
+    /// control message may have been cut short or dropped.
+    ///
+    /// Contains the file descriptors of the last message in the buffer if it
+    /// was an `SCM_RIGHTS` message.  They have been installed in the process
+    /// and need to be closed.
+    Truncated(&'a [RawFd]),
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+/// Iterator over the control messages of a [`RecvMsg`](struct.RecvMsg.html)
 #[allow(missing_debug_implementations)]
 pub struct CmsgIterator<'a> {
     buf: &'a [u8],
-    next: usize,
+    // Whether the truncation of the buffer still has to be reported.
+    truncated: bool,
 }
 
 impl<'a> Iterator for CmsgIterator<'a> {
-    type Item = ControlMessage<'a>;
+    type Item = ::std::result::Result<ControlMessage<'a>, CmsgError<'a>>;
 
     // The implementation loosely follows CMSG_FIRSTHDR / CMSG_NXTHDR,
     // although we handle the invariants in slightly different places to
     // get a better iterator interface.
-    fn next(&mut self) -> Option<ControlMessage<'a>> {
+    fn next(&mut self) -> Option<Self::Item> {
         let sizeof_cmsghdr = mem::size_of::<cmsghdr>();
-        if self.buf.len() < sizeof_cmsghdr {
-            return None;
-        }
-        let cmsg: &'a cmsghdr = unsafe { &*(self.buf.as_ptr() as *const cmsghdr) };
+        let cmsg: Option<&'a cmsghdr> = if self.buf.len() < sizeof_cmsghdr {
+            None
+        } else {
+            Some(unsafe { &*(self.buf.as_ptr() as *const cmsghdr) })
+        };
+        let cmsg = match cmsg {
+            // The check for too short messages is only in the glibc
+            // implementation of CMSG_NXTHDR (although it claims the kernel
+            // header checks this), but such a structure is clearly invalid,
+            // either way.  A message that doesn't fit in the buffer is one
+            // the kernel cut short.
+            Some(cmsg) if cmsg.cmsg_len as usize >= sizeof_cmsghdr &&
+                          cmsg.cmsg_len as usize <= self.buf.len() => cmsg,
+            _ => {
+                self.buf = &[];
+                if mem::replace(&mut self.truncated, false) {
+                    return Some(Err(CmsgError::Truncated(&[])));
+                }
+                return None;
+            }
+        };
 
-        // This check is only in the glibc implementation of CMSG_NXTHDR
-        // (although it claims the kernel header checks this), but such
-        // a structure is clearly invalid, either way.
         let cmsg_len = cmsg.cmsg_len as usize;
-        if cmsg_len < sizeof_cmsghdr {
-            return None;
-        }
         let len = cmsg_len - sizeof_cmsghdr;
-        let aligned_cmsg_len = if self.next == 0 {
-            // CMSG_FIRSTHDR
-            cmsg_len
+        let cmsg_data = &self.buf[cmsg_align(sizeof_cmsghdr)..cmsg_len];
+        // Skip the padding up to the next message, which the last message in
+        // the buffer may not have.
+        let aligned_cmsg_len = cmp::min(cmsg_align(cmsg_len), self.buf.len());
+        self.buf = &self.buf[aligned_cmsg_len..];
+
+        let is_scm_rights = (cmsg.cmsg_level, cmsg.cmsg_type) == (libc::SOL_SOCKET, libc::SCM_RIGHTS);
+        let fds: &'a [RawFd] = if is_scm_rights {
+            unsafe {
+                slice::from_raw_parts(cmsg_data.as_ptr() as *const _,
+                                      cmsg_data.len() / mem::size_of::<RawFd>())
+            }
         } else {
-            // CMSG_NXTHDR
-            cmsg_align(cmsg_len)
+            &[]
         };
 
-        // Advance our internal pointer.
-        if aligned_cmsg_len > self.buf.len() {
-            return None;
+        if self.truncated && self.buf.len() < sizeof_cmsghdr {
+            self.truncated = false;
+            return Some(Err(CmsgError::Truncated(fds)));
         }
-        let cmsg_data = &self.buf[cmsg_align(sizeof_cmsghdr)..cmsg_len];
-        self.buf = &self.buf[aligned_cmsg_len..];
-        self.next += 1;
 
-        match (cmsg.cmsg_level, cmsg.cmsg_type) {
-            (libc::SOL_SOCKET, libc::SCM_RIGHTS) => unsafe {
-                Some(ControlMessage::ScmRights(
-                    slice::from_raw_parts(cmsg_data.as_ptr() as *const _,
-                                          cmsg_data.len() / mem::size_of::<RawFd>())))
-            },
+        let msg = match (cmsg.cmsg_level, cmsg.cmsg_type) {
+            (libc::SOL_SOCKET, libc::SCM_RIGHTS) => ControlMessage::ScmRights(fds),
             (libc::SOL_SOCKET, libc::SCM_TIMESTAMP) => unsafe {
-                Some(ControlMessage::ScmTimestamp(
-                    &*(cmsg_data.as_ptr() as *const _)))
+                ControlMessage::ScmTimestamp(
+                    &*(cmsg_data.as_ptr() as *const _))
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::IPPROTO_IP, libc::IP_PKTINFO) => unsafe {
+                ControlMessage::Ipv4PacketInfo(
+                    &*(cmsg_data.as_ptr() as *const _))
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO) => unsafe {
+                ControlMessage::Ipv6PacketInfo(
+                    &*(cmsg_data.as_ptr() as *const _))
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::SOL_SOCKET, SCM_SECURITY) => {
+                // Some modules include the terminating NUL
+                let label = match cmsg_data.split_last() {
+                    Some((&0, label)) => label,
+                    _ => cmsg_data,
+                };
+                ControlMessage::ScmSecurity(OsStr::from_bytes(label))
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (level, ty) if ((level, ty) == (libc::IPPROTO_IP, libc::IP_RECVERR) ||
+                            (level, ty) == (libc::IPPROTO_IPV6, libc::IPV6_RECVERR)) &&
+                           cmsg_data.len() >= mem::size_of::<libc::sock_extended_err>() => {
+                let err = SockExtendedErr { level: level, data: cmsg_data };
+                if err.origin() == SO_EE_ORIGIN_ZEROCOPY {
+                    ControlMessage::ScmZerocopyCompletion(err)
+                } else if level == libc::IPPROTO_IP {
+                    ControlMessage::Ipv4RecvErr(err)
+                } else {
+                    ControlMessage::Ipv6RecvErr(err)
+                }
             },
             (_, _) => unsafe {
-                Some(ControlMessage::Unknown(UnknownCmsg(
+                ControlMessage::Unknown(UnknownCmsg(
                     cmsg,
                     slice::from_raw_parts(
                         cmsg_data.as_ptr() as *const _,
-                        len))))
+                        len)))
             }
-        }
+        };
+
+        Some(Ok(msg))
     }
 }
 
@@ -471,13 +663,13 @@ pub enum ControlMessage<'a> {
     /// let mut buffer1 = vec![0u8; message1.len() + message2.len()];
     /// let mut time1: CmsgSpace<TimeVal> = CmsgSpace::new();
     /// let received1 = recvmsg(in_socket, &[IoVec::from_mut_slice(&mut buffer1)], Some(&mut time1), MsgFlags::empty()).unwrap();
-    /// let mut time1 = if let Some(ControlMessage::ScmTimestamp(&time1)) = received1.cmsgs().next() { time1 } else { panic!("Unexpected or no control message") };
+    /// let mut time1 = if let Some(Ok(ControlMessage::ScmTimestamp(&time1))) = received1.cmsgs().next() { time1 } else { panic!("Unexpected or no control message") };
     ///
     /// // Receive the second
     /// let mut buffer2 = vec![0u8; message1.len() + message2.len()];
     /// let mut time2: CmsgSpace<TimeVal> = CmsgSpace::new();
     /// let received2 = recvmsg(in_socket, &[IoVec::from_mut_slice(&mut buffer2)], Some(&mut time2), MsgFlags::empty()).unwrap();
-    /// let mut time2 = if let Some(ControlMessage::ScmTimestamp(&time2)) = received2.cmsgs().next() { time2 } else { panic!("Unexpected or no control message") };
+    /// let mut time2 = if let Some(Ok(ControlMessage::ScmTimestamp(&time2))) = received2.cmsgs().next() { time2 } else { panic!("Unexpected or no control message") };
     ///
     /// // Swap if needed; UDP is unordered
     /// match (received1.bytes, received2.bytes, message1.len(), message2.len()) {
@@ -503,10 +695,170 @@ pub enum ControlMessage<'a> {
     /// nix::unistd::close(in_socket).unwrap();
     /// ```
     ScmTimestamp(&'a TimeVal),
+    /// A message of type `IP_PKTINFO`, containing the interface a packet
+    /// arrived on, its destination address and the local address it was
+    /// routed to.
+    ///
+    /// It is received on IPv4 sockets with the
+    /// [`Ipv4PacketInfo`](sockopt/struct.Ipv4PacketInfo.html) option enabled.
+    /// When sent, it selects the interface (`ipi_ifindex`) and source address
+    /// (`ipi_spec_dst`) of the outgoing packet; zero fields are ignored.
+    ///
+    /// See [ip(7)](http://man7.org/linux/man-pages/man7/ip.7.html).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv4PacketInfo(&'a libc::in_pktinfo),
+    /// A message of type `IPV6_PKTINFO`, containing the interface a packet
+    /// arrived on and its destination address.
+    ///
+    /// It is received on IPv6 sockets with the
+    /// [`Ipv6RecvPacketInfo`](sockopt/struct.Ipv6RecvPacketInfo.html) option
+    /// enabled.  When sent, it selects the interface (`ipi6_ifindex`) and
+    /// source address (`ipi6_addr`) of the outgoing packet; zero fields are
+    /// ignored.
+    ///
+    /// See [ipv6(7)](http://man7.org/linux/man-pages/man7/ipv6.7.html).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv6PacketInfo(&'a libc::in6_pktinfo),
+    /// A message of type `IP_RECVERR`, containing an error from the error
+    /// queue of an IPv4 socket, such as an ICMP error caused by a packet sent
+    /// earlier.
+    ///
+    /// It is received with `recvmsg` and
+    /// [`MSG_ERRQUEUE`](struct.MsgFlags.html#associatedconstant.MSG_ERRQUEUE)
+    /// on sockets with the [`Ipv4RecvErr`](sockopt/struct.Ipv4RecvErr.html)
+    /// option enabled.
+    ///
+    /// See [ip(7)](http://man7.org/linux/man-pages/man7/ip.7.html).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv4RecvErr(SockExtendedErr<'a>),
+    /// A message of type `IPV6_RECVERR`, like
+    /// [`Ipv4RecvErr`](#variant.Ipv4RecvErr) but for IPv6 sockets with the
+    /// [`Ipv6RecvErr`](sockopt/struct.Ipv6RecvErr.html) option enabled.
+    ///
+    /// See [ipv6(7)](http://man7.org/linux/man-pages/man7/ipv6.7.html).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    Ipv6RecvErr(SockExtendedErr<'a>),
+    /// An `IP_RECVERR` or `IPV6_RECVERR` message from the error queue
+    /// reporting that sends with `MSG_ZEROCOPY` have completed, so their
+    /// buffers may be reused.
+    ///
+    /// The completed sends are given by
+    /// [`zerocopy_range`](struct.SockExtendedErr.html#method.zerocopy_range).
+    /// If the data had to be copied after all, the
+    /// [`code`](struct.SockExtendedErr.html#method.code) is
+    /// `SO_EE_CODE_ZEROCOPY_COPIED`.
+    ///
+    /// See [msg_zerocopy](https://www.kernel.org/doc/html/latest/networking/msg_zerocopy.html).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    ScmZerocopyCompletion(SockExtendedErr<'a>),
+    /// A message of type `SCM_SECURITY`, containing the security label of the
+    /// sender of a message received on a UNIX domain socket.
+    ///
+    /// It is received on sockets with the
+    /// [`PassSec`](sockopt/struct.PassSec.html) option enabled, if a Linux
+    /// security module labels sockets.  The kernel refuses to send it.
+    ///
+    /// See [unix(7)](http://man7.org/linux/man-pages/man7/unix.7.html).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    ScmSecurity(&'a OsStr),
     #[doc(hidden)]
     Unknown(UnknownCmsg<'a>),
 }
 
+/// An error from the error queue of a socket (`struct sock_extended_err`),
+/// as received in a [`ControlMessage`](enum.ControlMessage.html).
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub struct SockExtendedErr<'a> {
+    level: c_int,
+    // The `sock_extended_err`, followed by the address of the offender
+    data: &'a [u8],
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl<'a> SockExtendedErr<'a> {
+    fn raw(&self) -> &'a libc::sock_extended_err {
+        unsafe { &*(self.data.as_ptr() as *const libc::sock_extended_err) }
+    }
+
+    /// The error, such as `ECONNREFUSED` for an ICMP port unreachable
+    /// message.
+    pub fn errno(&self) -> Errno {
+        Errno::from_i32(self.raw().ee_errno as i32)
+    }
+
+    /// Where the error came from, one of the `SO_EE_ORIGIN_*` constants.
+    pub fn origin(&self) -> u8 {
+        self.raw().ee_origin
+    }
+
+    /// The ICMP type of an error from `SO_EE_ORIGIN_ICMP` or
+    /// `SO_EE_ORIGIN_ICMP6`.
+    pub fn ee_type(&self) -> u8 {
+        self.raw().ee_type
+    }
+
+    /// The ICMP code of an ICMP error, or further details on others.
+    pub fn code(&self) -> u8 {
+        self.raw().ee_code
+    }
+
+    /// Additional information, like the MTU for "fragmentation needed"
+    /// errors.
+    pub fn info(&self) -> u32 {
+        self.raw().ee_info
+    }
+
+    /// Additional data.
+    pub fn data(&self) -> u32 {
+        self.raw().ee_data
+    }
+
+    /// The address of the node that caused the error, such as the router
+    /// that sent an ICMP error, if known.
+    pub fn offender(&self) -> Option<SockAddr> {
+        // The address follows the `sock_extended_err` (`SO_EE_OFFENDER`),
+        // which keeps it aligned.
+        let addr = &self.data[mem::size_of::<libc::sock_extended_err>()..];
+        if addr.len() < mem::size_of::<libc::sa_family_t>() {
+            return None;
+        }
+
+        let family = unsafe { *(addr.as_ptr() as *const libc::sa_family_t) };
+        match family as c_int {
+            libc::AF_INET if addr.len() >= mem::size_of::<libc::sockaddr_in>() => {
+                let sin = unsafe { *(addr.as_ptr() as *const libc::sockaddr_in) };
+                Some(SockAddr::Inet(InetAddr::V4(sin)))
+            },
+            libc::AF_INET6 if addr.len() >= mem::size_of::<libc::sockaddr_in6>() => {
+                let sin6 = unsafe { *(addr.as_ptr() as *const libc::sockaddr_in6) };
+                Some(SockAddr::Inet(InetAddr::V6(sin6)))
+            },
+            // AF_UNSPEC for errors without an offender
+            _ => None,
+        }
+    }
+
+    /// The inclusive range of `MSG_ZEROCOPY` sends, numbered from zero per
+    /// socket, completed by a zero-copy completion.
+    pub fn zerocopy_range(&self) -> Option<(u32, u32)> {
+        if self.origin() == SO_EE_ORIGIN_ZEROCOPY {
+            Some((self.info(), self.data()))
+        } else {
+            None
+        }
+    }
+
+    fn cmsg_type(&self) -> c_int {
+        if self.level == libc::IPPROTO_IP {
+            libc::IP_RECVERR
+        } else {
+            libc::IPV6_RECVERR
+        }
+    }
+}
+
 // An opaque structure used to prevent cmsghdr from being a public type
 #[doc(hidden)]
 #[allow(missing_debug_implementations)]
@@ -537,6 +889,24 @@ impl<'a> ControlMessage<'a> {
             ControlMessage::ScmTimestamp(t) => {
                 mem::size_of_val(t)
             },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv4PacketInfo(info) => {
+                mem::size_of_val(info)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv6PacketInfo(info) => {
+                mem::size_of_val(info)
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv4RecvErr(ref err) |
+            ControlMessage::Ipv6RecvErr(ref err) |
+            ControlMessage::ScmZerocopyCompletion(ref err) => {
+                err.data.len()
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::ScmSecurity(label) => {
+                label.len()
+            },
             ControlMessage::Unknown(UnknownCmsg(_, bytes)) => {
                 mem::size_of_val(bytes)
             }
@@ -548,48 +918,56 @@ impl<'a> ControlMessage<'a> {
     unsafe fn encode_into<'b>(&self, buf: &mut &'b mut [u8]) {
         match *self {
             ControlMessage::ScmRights(fds) => {
-                let cmsg = cmsghdr {
-                    cmsg_len: self.len() as _,
-                    cmsg_level: libc::SOL_SOCKET,
-                    cmsg_type: libc::SCM_RIGHTS,
-                    ..mem::uninitialized()
-                };
-                copy_bytes(&cmsg, buf);
-
-                let padlen = cmsg_align(mem::size_of_val(&cmsg)) -
-                    mem::size_of_val(&cmsg);
-
-                let mut tmpbuf = &mut [][..];
-                mem::swap(&mut tmpbuf, buf);
-                let (_padding, mut remainder) = tmpbuf.split_at_mut(padlen);
-                mem::swap(buf, &mut remainder);
-
-                copy_bytes(fds, buf);
+                self.encode_data_into(libc::SOL_SOCKET, libc::SCM_RIGHTS, fds, buf);
             },
             ControlMessage::ScmTimestamp(t) => {
-                let cmsg = cmsghdr {
-                    cmsg_len: self.len() as _,
-                    cmsg_level: libc::SOL_SOCKET,
-                    cmsg_type: libc::SCM_TIMESTAMP,
-                    ..mem::uninitialized()
-                };
-                copy_bytes(&cmsg, buf);
-
-                let padlen = cmsg_align(mem::size_of_val(&cmsg)) -
-                    mem::size_of_val(&cmsg);
-
-                let mut tmpbuf = &mut [][..];
-                mem::swap(&mut tmpbuf, buf);
-                let (_padding, mut remainder) = tmpbuf.split_at_mut(padlen);
-                mem::swap(buf, &mut remainder);
-
-                copy_bytes(t, buf);
+                self.encode_data_into(libc::SOL_SOCKET, libc::SCM_TIMESTAMP, t, buf);
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv4PacketInfo(info) => {
+                self.encode_data_into(libc::IPPROTO_IP, libc::IP_PKTINFO, info, buf);
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv6PacketInfo(info) => {
+                self.encode_data_into(libc::IPPROTO_IPV6, libc::IPV6_PKTINFO, info, buf);
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::Ipv4RecvErr(ref err) |
+            ControlMessage::Ipv6RecvErr(ref err) |
+            ControlMessage::ScmZerocopyCompletion(ref err) => {
+                self.encode_data_into(err.level, err.cmsg_type(), err.data, buf);
+            },
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::ScmSecurity(label) => {
+                self.encode_data_into(libc::SOL_SOCKET, SCM_SECURITY, label.as_bytes(), buf);
             },
             ControlMessage::Unknown(UnknownCmsg(orig_cmsg, bytes)) => {
                 copy_bytes(orig_cmsg, buf);
                 copy_bytes(bytes, buf);
             }
         }
+
+        // Skip the padding up to the start of the next message.
+        skip_bytes(self.space() - self.len(), buf);
+    }
+
+    // Encode a header for this message followed by `data`, with the same
+    // requirements as `encode_into`.
+    unsafe fn encode_data_into<'b, T: ?Sized>(&self, level: c_int, ty: c_int, data: &T,
+                                              buf: &mut &'b mut [u8]) {
+        let cmsg = cmsghdr {
+            cmsg_len: self.len() as _,
+            cmsg_level: level,
+            cmsg_type: ty,
+            ..mem::uninitialized()
+        };
+        copy_bytes(&cmsg, buf);
+
+        let padlen = cmsg_align(mem::size_of_val(&cmsg)) -
+            mem::size_of_val(&cmsg);
+        skip_bytes(padlen, buf);
+
+        copy_bytes(data, buf);
     }
 }
 
@@ -600,19 +978,12 @@ impl<'a> ControlMessage<'a> {
 ///
 /// Allocates if cmsgs is nonempty.
 pub fn sendmsg<'a>(fd: RawFd, iov: &[IoVec<&'a [u8]>], cmsgs: &[ControlMessage<'a>], flags: MsgFlags, addr: Option<&'a SockAddr>) -> Result<usize> {
-    let mut len = 0;
     let mut capacity = 0;
     for cmsg in cmsgs {
-        len += cmsg.len();
         capacity += cmsg.space();
     }
-    // Note that the resulting vector claims to have length == capacity,
-    // so it's presently uninitialized.
-    let mut cmsg_buffer = unsafe {
-        let mut vec = Vec::<u8>::with_capacity(len);
-        vec.set_len(len);
-        vec
-    };
+    // The padding after each message is left zeroed.
+    let mut cmsg_buffer = vec![0u8; capacity];
     {
         let mut ptr = &mut cmsg_buffer[..];
         for cmsg in cmsgs {
@@ -707,6 +1078,12 @@ pub fn socket<T: Into<Option<SockProtocol>>>(domain: AddressFamily, ty: SockType
     Errno::result(res)
 }
 
+/// Like [`socket`](fn.socket.html), but return an
+/// [`OwnedFd`](../../fd/struct.OwnedFd.html).
+pub fn socket_owned<T: Into<Option<SockProtocol>>>(domain: AddressFamily, ty: SockType, flags: SockFlag, protocol: T) -> Result<OwnedFd> {
+    socket(domain, ty, flags, protocol).map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
 /// Create a pair of connected sockets
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/9699919799/functions/socketpair.html)
@@ -761,6 +1138,12 @@ pub fn accept(sockfd: RawFd) -> Result<RawFd> {
     Errno::result(res)
 }
 
+/// Like [`accept`](fn.accept.html), but return an
+/// [`OwnedFd`](../../fd/struct.OwnedFd.html).
+pub fn accept_owned(sockfd: RawFd) -> Result<OwnedFd> {
+    accept(sockfd).map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
 /// Accept a connection on a socket
 ///
 /// [Further reading](http://man7.org/linux/man-pages/man2/accept.2.html)
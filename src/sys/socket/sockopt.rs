@@ -1,9 +1,17 @@
 use super::{GetSockOpt, SetSockOpt};
 use Result;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use Error;
 use errno::Errno;
 use sys::time::TimeVal;
 use libc::{self, c_int, uint8_t, c_void, socklen_t};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::cmp;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::ffi::OsString;
 use std::mem;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::os::unix::ffi::OsStringExt;
 use std::os::unix::io::RawFd;
 
 /// Helper for implementing `SetSockOpt` for a given socket option. See
@@ -102,6 +110,7 @@ macro_rules! getsockopt_impl {
 ///
 /// # Arguments
 ///
+/// * `$(#[$attr:meta])*`: attributes of the generated type, such as its documentation; optional.
 /// * `GetOnly`, `SetOnly` or `Both`: whether you want to implement only getter, only setter or
 ///    both of them.
 /// * `$name:ident`: name of type `GetSockOpt`/`SetSockOpt` will be implemented for.
@@ -116,69 +125,72 @@ macro_rules! getsockopt_impl {
 /// * `$getter:ty`: `Get` implementation; optional; only for `GetOnly` and `Both`.
 /// * `$setter:ty`: `Set` implementation; optional; only for `SetOnly` and `Both`.
 macro_rules! sockopt_impl {
-    (GetOnly, $name:ident, $level:path, $flag:path, bool) => {
-        sockopt_impl!(GetOnly, $name, $level, $flag, bool, GetBool);
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, bool) => {
+        sockopt_impl!($(#[$attr])* GetOnly, $name, $level, $flag, bool, GetBool);
     };
 
-    (GetOnly, $name:ident, $level:path, $flag:path, u8) => {
-        sockopt_impl!(GetOnly, $name, $level, $flag, u8, GetU8);
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, u8) => {
+        sockopt_impl!($(#[$attr])* GetOnly, $name, $level, $flag, u8, GetU8);
     };
 
-    (GetOnly, $name:ident, $level:path, $flag:path, usize) => {
-        sockopt_impl!(GetOnly, $name, $level, $flag, usize, GetUsize);
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, usize) => {
+        sockopt_impl!($(#[$attr])* GetOnly, $name, $level, $flag, usize, GetUsize);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, bool) => {
-        sockopt_impl!(SetOnly, $name, $level, $flag, bool, SetBool);
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, bool) => {
+        sockopt_impl!($(#[$attr])* SetOnly, $name, $level, $flag, bool, SetBool);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, u8) => {
-        sockopt_impl!(SetOnly, $name, $level, $flag, u8, SetU8);
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, u8) => {
+        sockopt_impl!($(#[$attr])* SetOnly, $name, $level, $flag, u8, SetU8);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, usize) => {
-        sockopt_impl!(SetOnly, $name, $level, $flag, usize, SetUsize);
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, usize) => {
+        sockopt_impl!($(#[$attr])* SetOnly, $name, $level, $flag, usize, SetUsize);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, bool) => {
-        sockopt_impl!(Both, $name, $level, $flag, bool, GetBool, SetBool);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, bool) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, bool, GetBool, SetBool);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, u8) => {
-        sockopt_impl!(Both, $name, $level, $flag, u8, GetU8, SetU8);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, u8) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, u8, GetU8, SetU8);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, usize) => {
-        sockopt_impl!(Both, $name, $level, $flag, usize, GetUsize, SetUsize);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, usize) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, usize, GetUsize, SetUsize);
     };
 
     /*
      * Matchers with generic getter types must be placed at the end, so
      * they'll only match _after_ specialized matchers fail
      */
-    (GetOnly, $name:ident, $level:path, $flag:path, $ty:ty) => {
-        sockopt_impl!(GetOnly, $name, $level, $flag, $ty, GetStruct<$ty>);
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, $ty:ty) => {
+        sockopt_impl!($(#[$attr])* GetOnly, $name, $level, $flag, $ty, GetStruct<$ty>);
     };
 
-    (GetOnly, $name:ident, $level:path, $flag:path, $ty:ty, $getter:ty) => {
+    ($(#[$attr:meta])* GetOnly, $name:ident, $level:path, $flag:path, $ty:ty, $getter:ty) => {
+        $(#[$attr])*
         #[derive(Copy, Clone, Debug)]
         pub struct $name;
 
         getsockopt_impl!($name, $level, $flag, $ty, $getter);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, $ty:ty) => {
-        sockopt_impl!(SetOnly, $name, $level, $flag, $ty, SetStruct<$ty>);
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, $ty:ty) => {
+        sockopt_impl!($(#[$attr])* SetOnly, $name, $level, $flag, $ty, SetStruct<$ty>);
     };
 
-    (SetOnly, $name:ident, $level:path, $flag:path, $ty:ty, $setter:ty) => {
+    ($(#[$attr:meta])* SetOnly, $name:ident, $level:path, $flag:path, $ty:ty, $setter:ty) => {
+        $(#[$attr])*
         #[derive(Copy, Clone, Debug)]
         pub struct $name;
 
         setsockopt_impl!($name, $level, $flag, $ty, $setter);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, $ty:ty, $getter:ty, $setter:ty) => {
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, $ty:ty, $getter:ty, $setter:ty) => {
+        $(#[$attr])*
         #[derive(Copy, Clone, Debug)]
         pub struct $name;
 
@@ -186,8 +198,8 @@ macro_rules! sockopt_impl {
         getsockopt_impl!($name, $level, $flag, $ty, $getter);
     };
 
-    (Both, $name:ident, $level:path, $flag:path, $ty:ty) => {
-        sockopt_impl!(Both, $name, $level, $flag, $ty, GetStruct<$ty>, SetStruct<$ty>);
+    ($(#[$attr:meta])* Both, $name:ident, $level:path, $flag:path, $ty:ty) => {
+        sockopt_impl!($(#[$attr])* Both, $name, $level, $flag, $ty, GetStruct<$ty>, SetStruct<$ty>);
     };
 }
 
@@ -219,6 +231,10 @@ cfg_if! {
 }
 sockopt_impl!(Both, IpMulticastTtl, libc::IPPROTO_IP, libc::IP_MULTICAST_TTL, u8);
 sockopt_impl!(Both, IpMulticastLoop, libc::IPPROTO_IP, libc::IP_MULTICAST_LOOP, bool);
+sockopt_impl!(Both, IpMulticastIf, libc::IPPROTO_IP, libc::IP_MULTICAST_IF, super::Ipv4Addr);
+sockopt_impl!(Both, Ipv6MulticastHops, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_HOPS, i32);
+sockopt_impl!(Both, Ipv6MulticastLoop, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_LOOP, bool);
+sockopt_impl!(Both, Ipv6MulticastIf, libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_IF, u32);
 sockopt_impl!(Both, ReceiveTimeout, libc::SOL_SOCKET, libc::SO_RCVTIMEO, TimeVal);
 sockopt_impl!(Both, SendTimeout, libc::SOL_SOCKET, libc::SO_SNDTIMEO, TimeVal);
 sockopt_impl!(Both, Broadcast, libc::SOL_SOCKET, libc::SO_BROADCAST, bool);
@@ -236,12 +252,35 @@ sockopt_impl!(Both, TcpKeepAlive, libc::IPPROTO_TCP, libc::TCP_KEEPALIVE, u32);
           target_os = "linux",
           target_os = "nacl"))]
 sockopt_impl!(Both, TcpKeepIdle, libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, u32);
-sockopt_impl!(Both, RcvBuf, libc::SOL_SOCKET, libc::SO_RCVBUF, usize);
-sockopt_impl!(Both, SndBuf, libc::SOL_SOCKET, libc::SO_SNDBUF, usize);
+sockopt_impl!(
+    /// The size of the receive buffer of the socket.
+    ///
+    /// Linux doubles the size set to leave room for bookkeeping overhead, so
+    /// reading it back returns twice the size set.  The size is capped by
+    /// `net.core.rmem_max` first.
+    Both, RcvBuf, libc::SOL_SOCKET, libc::SO_RCVBUF, usize);
+sockopt_impl!(
+    /// The size of the send buffer of the socket.
+    ///
+    /// Linux doubles the size set like for [`RcvBuf`](struct.RcvBuf.html),
+    /// and caps it by `net.core.wmem_max` first.
+    Both, SndBuf, libc::SOL_SOCKET, libc::SO_SNDBUF, usize);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Like [`RcvBuf`](struct.RcvBuf.html), but the size isn't capped by
+    /// `net.core.rmem_max`.  Requires `CAP_NET_ADMIN`.
+    SetOnly, RcvBufForce, libc::SOL_SOCKET, libc::SO_RCVBUFFORCE, usize);
 #[cfg(any(target_os = "android", target_os = "linux"))]
-sockopt_impl!(SetOnly, RcvBufForce, libc::SOL_SOCKET, libc::SO_RCVBUFFORCE, usize);
+sockopt_impl!(
+    /// Like [`SndBuf`](struct.SndBuf.html), but the size isn't capped by
+    /// `net.core.wmem_max`.  Requires `CAP_NET_ADMIN`.
+    SetOnly, SndBufForce, libc::SOL_SOCKET, libc::SO_SNDBUFFORCE, usize);
 #[cfg(any(target_os = "android", target_os = "linux"))]
-sockopt_impl!(SetOnly, SndBufForce, libc::SOL_SOCKET, libc::SO_SNDBUFFORCE, usize);
+sockopt_impl!(
+    /// The CPU that last processed packets of the socket, or -1.  Setting it
+    /// lets `SO_REUSEPORT` groups steer new connections to the listener on
+    /// that CPU.
+    Both, IncomingCpu, libc::SOL_SOCKET, libc::SO_INCOMING_CPU, i32);
 sockopt_impl!(GetOnly, SockType, libc::SOL_SOCKET, libc::SO_TYPE, super::SockType);
 sockopt_impl!(GetOnly, AcceptConn, libc::SOL_SOCKET, libc::SO_ACCEPTCONN, bool);
 #[cfg(any(target_os = "android", target_os = "linux"))]
@@ -255,6 +294,75 @@ sockopt_impl!(Both, BindAny, libc::SOL_SOCKET, libc::SO_BINDANY, bool);
 sockopt_impl!(Both, BindAny, libc::IPPROTO_IP, libc::IP_BINDANY, bool);
 #[cfg(target_os = "linux")]
 sockopt_impl!(Both, Mark, libc::SOL_SOCKET, libc::SO_MARK, u32);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, Ipv4PacketInfo, libc::IPPROTO_IP, libc::IP_PKTINFO, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(Both, Ipv6RecvPacketInfo, libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Queue errors, like ICMP errors, on the error queue of the socket to be
+    /// read with `MSG_ERRQUEUE`.
+    Both, Ipv4RecvErr, libc::IPPROTO_IP, libc::IP_RECVERR, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Like [`Ipv4RecvErr`](struct.Ipv4RecvErr.html), for IPv6 sockets.
+    Both, Ipv6RecvErr, libc::IPPROTO_IPV6, libc::IPV6_RECVERR, bool);
+#[cfg(target_os = "linux")]
+sockopt_impl!(
+    /// Allow sending with `MSG_ZEROCOPY`.
+    Both, ZeroCopy, libc::SOL_SOCKET, libc::SO_ZEROCOPY, bool);
+#[cfg(any(target_os = "android", target_os = "linux"))]
+sockopt_impl!(
+    /// Receive the security label of the sender of each message on a UNIX
+    /// domain socket as a `ControlMessage::ScmSecurity`.
+    Both, PassSec, libc::SOL_SOCKET, libc::SO_PASSSEC, bool);
+
+/// The security label of the peer of a connected UNIX domain socket, as
+/// assigned by the Linux security module in force, such as SELinux or
+/// AppArmor.
+///
+/// If no security module labels sockets, the label is empty.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Copy, Clone, Debug)]
+pub struct PeerSec;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl GetSockOpt for PeerSec {
+    type Val = OsString;
+
+    fn get(&self, fd: RawFd) -> Result<OsString> {
+        let mut buf = vec![0u8; 256];
+        loop {
+            let mut len = buf.len() as socklen_t;
+            let res = unsafe {
+                libc::getsockopt(fd, libc::SOL_SOCKET, libc::SO_PEERSEC,
+                                 buf.as_mut_ptr() as *mut c_void, &mut len)
+            };
+            match Errno::result(res) {
+                Ok(_) => {
+                    buf.truncate(len as usize);
+                    break;
+                },
+                // `len` has been set to the length of the label
+                Err(Error::Sys(Errno::ERANGE)) => {
+                    let len = cmp::max(len as usize, buf.len() * 2);
+                    buf.resize(len, 0);
+                },
+                Err(Error::Sys(Errno::ENOPROTOOPT)) => {
+                    buf.clear();
+                    break;
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Some modules include the terminating NUL
+        if buf.last() == Some(&0) {
+            buf.pop();
+        }
+        Ok(OsString::from_vec(buf))
+    }
+}
 
 /*
  *
@@ -425,7 +533,7 @@ unsafe impl<'a> Set<'a, u8> for SetU8 {
     }
 
     fn ffi_len(&self) -> socklen_t {
-        mem::size_of::<c_int>() as socklen_t
+        mem::size_of::<uint8_t>() as socklen_t
     }
 }
 
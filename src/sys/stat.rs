@@ -71,11 +71,62 @@ pub fn makedev(major: u64, minor: u64) -> dev_t {
      (minor & 0x0000_00ff)
 }
 
+/// Set the file mode creation mask of the process to `mode`, returning the
+/// previous mask.
+///
+/// # Thread safety
+///
+/// The mask is shared by all threads of the process, and there is no way to
+/// read it without also changing it.  Changing it in one thread affects files
+/// created concurrently by every other thread.
+///
+/// See also [umask(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/umask.html)
 pub fn umask(mode: Mode) -> Mode {
     let prev = unsafe { libc::umask(mode.bits() as mode_t) };
     Mode::from_bits(prev).expect("[BUG] umask returned invalid Mode")
 }
 
+/// Sets the file mode creation mask for as long as it is alive.
+///
+/// The previous mask is restored when the guard is dropped.  Like
+/// [`umask`](fn.umask.html) itself this affects the whole process, not just
+/// the current thread, so other threads must not create files or change the
+/// mask while a guard is alive.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use nix::sys::stat::{Mode, UmaskGuard};
+/// {
+///     let _guard = UmaskGuard::new(Mode::S_IRWXG | Mode::S_IRWXO);
+///     // Files created here are only accessible by their owner
+/// }
+/// // The previous mask is in effect again
+/// ```
+#[derive(Debug)]
+#[must_use]
+pub struct UmaskGuard {
+    prev: Mode,
+}
+
+impl UmaskGuard {
+    /// Set the mask to `mode` until the returned guard is dropped.
+    pub fn new(mode: Mode) -> UmaskGuard {
+        UmaskGuard { prev: umask(mode) }
+    }
+
+    /// The mask that will be restored when the guard is dropped.
+    pub fn previous(&self) -> Mode {
+        self.prev
+    }
+}
+
+impl Drop for UmaskGuard {
+    fn drop(&mut self) {
+        umask(self.prev);
+    }
+}
+
 pub fn stat<P: ?Sized + NixPath>(path: &P) -> Result<FileStat> {
     let mut dst = unsafe { mem::uninitialized() };
     let res = try!(path.with_nix_path(|cstr| {
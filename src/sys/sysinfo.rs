@@ -0,0 +1,65 @@
+//! Overall system statistics.
+//!
+//! See [`sysinfo(2)`](http://man7.org/linux/man-pages/man2/sysinfo.2.html).
+use std::{cmp, mem};
+use std::time::Duration;
+use libc::{self, SI_LOAD_SHIFT};
+use Result;
+use errno::Errno;
+
+/// System statistics, as returned by [`sysinfo`](fn.sysinfo.html).
+///
+/// Memory sizes are scaled to bytes.
+#[derive(Clone, Copy)]
+#[allow(missing_debug_implementations)]
+pub struct SysInfo(libc::sysinfo);
+
+impl SysInfo {
+    /// Time elapsed since boot.
+    pub fn uptime(&self) -> Duration {
+        // Truncate negative values to 0
+        Duration::from_secs(cmp::max(self.0.uptime, 0) as u64)
+    }
+
+    /// The 1, 5 and 15 minute load averages.
+    pub fn load_average(&self) -> (f64, f64, f64) {
+        let f = |n: libc::c_ulong| n as f64 / (1 << SI_LOAD_SHIFT) as f64;
+        (f(self.0.loads[0]), f(self.0.loads[1]), f(self.0.loads[2]))
+    }
+
+    /// Number of processes currently running.
+    pub fn process_count(&self) -> u16 {
+        self.0.procs
+    }
+
+    /// Total usable main memory, in bytes.
+    pub fn ram_total(&self) -> u64 {
+        self.scale_mem(self.0.totalram)
+    }
+
+    /// Available main memory, in bytes.
+    pub fn ram_unused(&self) -> u64 {
+        self.scale_mem(self.0.freeram)
+    }
+
+    /// Total swap space, in bytes.
+    pub fn swap_total(&self) -> u64 {
+        self.scale_mem(self.0.totalswap)
+    }
+
+    /// Available swap space, in bytes.
+    pub fn swap_free(&self) -> u64 {
+        self.scale_mem(self.0.freeswap)
+    }
+
+    fn scale_mem(&self, units: libc::c_ulong) -> u64 {
+        units as u64 * self.0.mem_unit as u64
+    }
+}
+
+/// Returns system statistics such as uptime, load averages and memory usage.
+pub fn sysinfo() -> Result<SysInfo> {
+    let mut info: libc::sysinfo = unsafe { mem::uninitialized() };
+    let res = unsafe { libc::sysinfo(&mut info) };
+    Errno::result(res).map(|_| SysInfo(info))
+}
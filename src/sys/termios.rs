@@ -1096,6 +1096,21 @@ pub fn tcsendbreak(fd: RawFd, duration: c_int) -> Result<()> {
     Errno::result(unsafe { libc::tcsendbreak(fd, duration) }).map(|_| ())
 }
 
+/// Make the terminal `fd` the controlling terminal of the calling process, as
+/// with `ioctl(fd, TIOCSCTTY, force)`.
+///
+/// The calling process must be a session leader without a controlling
+/// terminal, such as after [`setsid`](../../unistd/fn.setsid.html).  If the
+/// terminal already controls another session it is only taken over if
+/// `force` is set and the caller has the `CAP_SYS_ADMIN` capability.
+pub fn tiocsctty(fd: RawFd, force: bool) -> Result<()> {
+    let res = unsafe {
+        libc::ioctl(fd, libc::TIOCSCTTY as ::sys::ioctl::ioctl_num_type, force as c_int)
+    };
+
+    Errno::result(res).map(drop)
+}
+
 /// Get the session controlled by the given terminal (see
 /// [tcgetsid(3)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/tcgetsid.html)).
 pub fn tcgetsid(fd: RawFd) -> Result<Pid> {
@@ -1,5 +1,15 @@
-use std::{cmp, fmt, ops};
-use libc::{c_long, time_t, suseconds_t, timespec, timeval};
+use std::{cmp, fmt, mem, ops};
+use std::time::Duration;
+use libc::{self, c_int, c_long, clockid_t, time_t, suseconds_t, timespec, timeval};
+use Result;
+use errno::Errno;
+#[cfg(target_os = "linux")]
+use Error;
+#[cfg(target_os = "linux")]
+use unistd::Pid;
+
+#[cfg(target_os = "linux")]
+pub use self::timex::*;
 
 pub trait TimeValLike: Sized {
     #[inline]
@@ -150,7 +160,7 @@ impl TimeValLike for TimeSpec {
     }
 
     fn num_microseconds(&self) -> i64 {
-        self.num_nanoseconds() / 1_000_000_000
+        self.num_nanoseconds() / 1_000
     }
 
     fn num_nanoseconds(&self) -> i64 {
@@ -176,6 +186,37 @@ impl TimeSpec {
     pub fn tv_nsec(&self) -> c_long {
         self.0.tv_nsec
     }
+
+    /// Makes a new `TimeSpec` from a `Duration`, or `None` if the duration
+    /// doesn't fit into a `TimeSpec`.
+    pub fn from_duration(duration: Duration) -> Option<TimeSpec> {
+        if duration.as_secs() > TS_MAX_SECONDS as u64 {
+            return None;
+        }
+        Some(TimeSpec(timespec {tv_sec: duration.as_secs() as time_t,
+                                tv_nsec: duration.subsec_nanos() as c_long }))
+    }
+
+    /// Converts the `TimeSpec` into a `Duration`, or `None` if it is
+    /// negative.
+    pub fn to_duration(&self) -> Option<Duration> {
+        if self.tv_sec() < 0 {
+            None
+        } else {
+            Some(Duration::new(self.tv_sec() as u64, self.tv_nsec() as u32))
+        }
+    }
+}
+
+/// Converts a `Duration` like [`from_duration`](#method.from_duration), but
+/// saturates at the largest `TimeSpec` if it doesn't fit.
+impl From<Duration> for TimeSpec {
+    fn from(duration: Duration) -> TimeSpec {
+        TimeSpec::from_duration(duration).unwrap_or_else(|| {
+            TimeSpec(timespec {tv_sec: TS_MAX_SECONDS as time_t,
+                               tv_nsec: (NANOS_PER_SEC - 1) as c_long })
+        })
+    }
 }
 
 impl ops::Neg for TimeSpec {
@@ -390,6 +431,38 @@ impl TimeVal {
     pub fn tv_usec(&self) -> suseconds_t {
         self.0.tv_usec
     }
+
+    /// Makes a new `TimeVal` from a `Duration`, or `None` if the duration
+    /// doesn't fit into a `TimeVal`.  Like for
+    /// [`nanoseconds`](trait.TimeValLike.html#tymethod.nanoseconds), the
+    /// nanoseconds are rounded down to whole microseconds.
+    pub fn from_duration(duration: Duration) -> Option<TimeVal> {
+        if duration.as_secs() > TV_MAX_SECONDS as u64 {
+            return None;
+        }
+        Some(TimeVal(timeval {tv_sec: duration.as_secs() as time_t,
+                              tv_usec: (duration.subsec_nanos() / 1_000) as suseconds_t }))
+    }
+
+    /// Converts the `TimeVal` into a `Duration`, or `None` if it is negative.
+    pub fn to_duration(&self) -> Option<Duration> {
+        if self.tv_sec() < 0 {
+            None
+        } else {
+            Some(Duration::new(self.tv_sec() as u64, self.tv_usec() as u32 * 1_000))
+        }
+    }
+}
+
+/// Converts a `Duration` like [`from_duration`](#method.from_duration), but
+/// saturates at the largest `TimeVal` if it doesn't fit.
+impl From<Duration> for TimeVal {
+    fn from(duration: Duration) -> TimeVal {
+        TimeVal::from_duration(duration).unwrap_or_else(|| {
+            TimeVal(timeval {tv_sec: TV_MAX_SECONDS as time_t,
+                             tv_usec: (MICROS_PER_SEC - 1) as suseconds_t })
+        })
+    }
 }
 
 impl ops::Neg for TimeVal {
@@ -466,6 +539,465 @@ impl fmt::Display for TimeVal {
     }
 }
 
+/// Identifies one of the system clocks.
+///
+/// Newtype pattern around `clockid_t`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ClockId(clockid_t);
+
+impl ClockId {
+    /// The system-wide wall clock.
+    pub const REALTIME: ClockId = ClockId(libc::CLOCK_REALTIME);
+    /// A clock that can't be set and never jumps, measuring the time since
+    /// some unspecified point in the past.
+    pub const MONOTONIC: ClockId = ClockId(libc::CLOCK_MONOTONIC);
+    /// Like [`MONOTONIC`](#associatedconstant.MONOTONIC), but not subject to
+    /// frequency adjustments by NTP.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub const MONOTONIC_RAW: ClockId = ClockId(libc::CLOCK_MONOTONIC_RAW);
+    /// Like [`MONOTONIC`](#associatedconstant.MONOTONIC), but including the
+    /// time the system was suspended.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub const BOOTTIME: ClockId = ClockId(libc::CLOCK_BOOTTIME);
+    /// Like [`BOOTTIME`](#associatedconstant.BOOTTIME), but timers on it wake
+    /// the system up from suspend.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub const BOOTTIME_ALARM: ClockId = ClockId(libc::CLOCK_BOOTTIME_ALARM);
+    /// The CPU time consumed by the calling process.
+    #[cfg(any(target_os = "android",
+              target_os = "dragonfly",
+              target_os = "freebsd",
+              target_os = "linux",
+              target_os = "netbsd",
+              target_os = "openbsd"))]
+    pub const PROCESS_CPUTIME_ID: ClockId = ClockId(libc::CLOCK_PROCESS_CPUTIME_ID);
+    /// The CPU time consumed by the calling thread.
+    #[cfg(any(target_os = "android",
+              target_os = "dragonfly",
+              target_os = "freebsd",
+              target_os = "linux",
+              target_os = "netbsd",
+              target_os = "openbsd"))]
+    pub const THREAD_CPUTIME_ID: ClockId = ClockId(libc::CLOCK_THREAD_CPUTIME_ID);
+    /// International Atomic Time, which unlike
+    /// [`REALTIME`](#associatedconstant.REALTIME) has no leap seconds.
+    #[cfg(target_os = "linux")]
+    pub const TAI: ClockId = ClockId(libc::CLOCK_TAI);
+
+    /// Creates `ClockId` from a raw `clockid_t`.
+    pub fn from_raw(clk_id: clockid_t) -> Self {
+        ClockId(clk_id)
+    }
+
+    /// Returns the raw `clockid_t`.
+    pub fn as_raw(self) -> clockid_t {
+        self.0
+    }
+
+    /// Get the clock measuring the CPU time consumed by the process `pid`
+    /// (see
+    /// [clock_getcpuclockid(3)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/clock_getcpuclockid.html)).
+    ///
+    /// Fails with `ESRCH` if there is no such process.
+    #[cfg(target_os = "linux")]
+    pub fn pid_cpu_clock_id(pid: Pid) -> Result<ClockId> {
+        let mut clk_id: clockid_t = 0;
+        let res = unsafe { libc::clock_getcpuclockid(pid.into(), &mut clk_id) };
+
+        if res == 0 {
+            Ok(ClockId(clk_id))
+        } else {
+            Err(Error::Sys(Errno::from_i32(res)))
+        }
+    }
+
+    /// Get the current time of the clock (see
+    /// [clock_gettime(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/clock_gettime.html)).
+    pub fn now(self) -> Result<TimeSpec> {
+        let mut ts: timespec = unsafe { mem::zeroed() };
+        let res = unsafe { libc::clock_gettime(self.0, &mut ts) };
+
+        Errno::result(res).map(|_| TimeSpec(ts))
+    }
+
+    /// Get the resolution of the clock (see
+    /// [clock_getres(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/clock_getres.html)).
+    pub fn res(self) -> Result<TimeSpec> {
+        let mut ts: timespec = unsafe { mem::zeroed() };
+        let res = unsafe { libc::clock_getres(self.0, &mut ts) };
+
+        Errno::result(res).map(|_| TimeSpec(ts))
+    }
+
+    /// Set the time of the clock (see
+    /// [clock_settime(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/clock_settime.html)).
+    ///
+    /// Usually only [`REALTIME`](#associatedconstant.REALTIME) can be set.
+    /// On Linux this requires `CAP_SYS_TIME` and fails with `EPERM` without
+    /// it.
+    #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+    pub fn set(self, time: TimeSpec) -> Result<()> {
+        let res = unsafe { libc::clock_settime(self.0, time.as_ref()) };
+
+        Errno::result(res).map(drop)
+    }
+}
+
+impl From<ClockId> for clockid_t {
+    fn from(clk_id: ClockId) -> Self {
+        clk_id.0
+    }
+}
+
+libc_enum!{
+    /// Selects one of the interval timers of a process, as passed to
+    /// [`setitimer`](fn.setitimer.html).
+    #[repr(i32)]
+    pub enum ItimerWhich {
+        /// Counts down in real time and sends `SIGALRM` when it expires.
+        ITIMER_REAL,
+        /// Counts down while the process runs in user mode and sends
+        /// `SIGVTALRM` when it expires.
+        ITIMER_VIRTUAL,
+        /// Counts down while the process runs in user or kernel mode and
+        /// sends `SIGPROF` when it expires.
+        ITIMER_PROF,
+    }
+}
+
+/// The setting of an interval timer (`struct itimerval`).
+#[derive(Clone, Copy)]
+pub struct ItimerVal(libc::itimerval);
+
+impl ItimerVal {
+    /// A timer expiring after `value`, and then every `interval` if it isn't
+    /// zero.
+    pub fn new(interval: TimeVal, value: TimeVal) -> ItimerVal {
+        ItimerVal(libc::itimerval { it_interval: interval.0, it_value: value.0 })
+    }
+
+    /// A stopped timer.
+    pub fn disarm() -> ItimerVal {
+        ItimerVal::new(TimeVal::zero(), TimeVal::zero())
+    }
+
+    /// The period of the timer after its first expiry, or zero if it only
+    /// expires once.
+    pub fn interval(&self) -> TimeVal {
+        TimeVal(self.0.it_interval)
+    }
+
+    /// The time until the timer next expires, or zero if it is stopped.
+    pub fn value(&self) -> TimeVal {
+        TimeVal(self.0.it_value)
+    }
+}
+
+impl fmt::Debug for ItimerVal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ItimerVal")
+            .field("interval", &self.interval())
+            .field("value", &self.value())
+            .finish()
+    }
+}
+
+mod ffi {
+    use libc::{c_int, itimerval};
+
+    // libc doesn't declare the interval timer functions on every platform.
+    extern {
+        pub fn setitimer(which: c_int, new: *const itimerval, old: *mut itimerval) -> c_int;
+        pub fn getitimer(which: c_int, curr: *mut itimerval) -> c_int;
+    }
+}
+
+/// Set the interval timer `which` of the calling process, returning its
+/// previous setting (see
+/// [setitimer(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/setitimer.html)).
+///
+/// Each process has a single timer of each kind, which may also be used by
+/// `alarm`, `sleep` or a profiler.  Timers created with `timer_create` often
+/// send the same signals, so a handler can't tell which timer expired unless
+/// they use different ones.
+pub fn setitimer(which: ItimerWhich, new: &ItimerVal) -> Result<ItimerVal> {
+    let mut old: libc::itimerval = unsafe { mem::zeroed() };
+    let res = unsafe { ffi::setitimer(which as c_int, &new.0, &mut old) };
+
+    Errno::result(res).map(|_| ItimerVal(old))
+}
+
+/// Get the current setting of the interval timer `which` of the calling
+/// process (see
+/// [getitimer(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getitimer.html)).
+pub fn getitimer(which: ItimerWhich) -> Result<ItimerVal> {
+    let mut curr: libc::itimerval = unsafe { mem::zeroed() };
+    let res = unsafe { ffi::getitimer(which as c_int, &mut curr) };
+
+    Errno::result(res).map(|_| ItimerVal(curr))
+}
+
+#[cfg(target_os = "linux")]
+mod timex {
+    use std::mem;
+    use libc::{self, c_int, c_long, c_uint};
+    use Result;
+    use errno::Errno;
+    use super::{ClockId, TimeVal};
+
+    libc_bitflags!{
+        /// Selects which fields of a [`TimeX`](struct.TimeX.html) are applied
+        /// to the clock.
+        pub struct AdjFlags: c_uint {
+            /// Adjust the time offset.
+            ADJ_OFFSET;
+            /// Adjust the frequency offset.
+            ADJ_FREQUENCY;
+            /// Set the maximum time error.
+            ADJ_MAXERROR;
+            /// Set the estimated time error.
+            ADJ_ESTERROR;
+            /// Set the clock status bits.
+            ADJ_STATUS;
+            /// Set the PLL time constant.
+            ADJ_TIMECONST;
+            /// Set the TAI offset.
+            ADJ_TAI;
+            /// Step the clock by the time offset.
+            ADJ_SETOFFSET;
+            /// Interpret `offset` and `time` in microseconds.
+            ADJ_MICRO;
+            /// Interpret `offset` and `time` in nanoseconds.
+            ADJ_NANO;
+            /// Set the tick value.
+            ADJ_TICK;
+        }
+    }
+
+    libc_bitflags!{
+        /// Clock status bits, as reported by and set through a
+        /// [`TimeX`](struct.TimeX.html).
+        pub struct TimexStatus: c_int {
+            /// Enable PLL updates.
+            STA_PLL;
+            /// Enable PPS frequency discipline.
+            STA_PPSFREQ;
+            /// Enable PPS time discipline.
+            STA_PPSTIME;
+            /// Select frequency-lock mode.
+            STA_FLL;
+            /// Insert a leap second at the end of the UTC day.
+            STA_INS;
+            /// Delete a leap second at the end of the UTC day.
+            STA_DEL;
+            /// The clock is unsynchronized.
+            STA_UNSYNC;
+            /// Hold the frequency.
+            STA_FREQHOLD;
+            /// A PPS signal is present.
+            STA_PPSSIGNAL;
+            /// The PPS signal jitter is exceeded.
+            STA_PPSJITTER;
+            /// The PPS signal wander is exceeded.
+            STA_PPSWANDER;
+            /// The PPS signal calibration error is exceeded.
+            STA_PPSERROR;
+            /// The clock hardware has failed.
+            STA_CLOCKERR;
+            /// Time is kept with nanosecond resolution.
+            STA_NANO;
+            /// Select FLL mode rather than PLL mode.
+            STA_MODE;
+            /// The clock source is B rather than A.
+            STA_CLK;
+        }
+    }
+
+    libc_enum!{
+        /// The state of the clock, as returned by [`adjtimex`](fn.adjtimex.html).
+        #[repr(i32)]
+        pub enum ClockState {
+            /// The clock is synchronized and no leap second is pending.
+            TIME_OK,
+            /// A leap second will be inserted at the end of the UTC day.
+            TIME_INS,
+            /// A leap second will be deleted at the end of the UTC day.
+            TIME_DEL,
+            /// A leap second insertion is in progress.
+            TIME_OOP,
+            /// A leap second has just occurred.
+            TIME_WAIT,
+            /// The clock is not synchronized.
+            TIME_ERROR,
+        }
+    }
+
+    impl ClockState {
+        fn from_raw(state: c_int) -> ClockState {
+            match state {
+                libc::TIME_OK => ClockState::TIME_OK,
+                libc::TIME_INS => ClockState::TIME_INS,
+                libc::TIME_DEL => ClockState::TIME_DEL,
+                libc::TIME_OOP => ClockState::TIME_OOP,
+                libc::TIME_WAIT => ClockState::TIME_WAIT,
+                // The kernel doesn't return any other values.
+                _ => ClockState::TIME_ERROR,
+            }
+        }
+    }
+
+    /// Parameters of the kernel clock discipline, as read and adjusted by
+    /// [`adjtimex`](fn.adjtimex.html).
+    ///
+    /// A new `TimeX` has no modes set, so passing it to `adjtimex` only reads
+    /// the current parameters.  Each setter also sets the corresponding
+    /// [`AdjFlags`](struct.AdjFlags.html) mode.
+    #[derive(Clone, Copy)]
+    #[allow(missing_debug_implementations)]
+    pub struct TimeX(libc::timex);
+
+    impl TimeX {
+        /// Create a `TimeX` that doesn't adjust anything.
+        pub fn new() -> TimeX {
+            TimeX(unsafe { mem::zeroed() })
+        }
+
+        /// The modes that will be applied.
+        pub fn modes(&self) -> AdjFlags {
+            AdjFlags::from_bits_truncate(self.0.modes)
+        }
+
+        /// Time offset, in microseconds or, if `STA_NANO` is set, nanoseconds.
+        pub fn offset(&self) -> c_long {
+            self.0.offset as c_long
+        }
+
+        /// Adjust the time offset by `offset`.
+        pub fn set_offset(&mut self, offset: c_long) -> &mut TimeX {
+            self.0.offset = offset as _;
+            self.0.modes |= libc::ADJ_OFFSET;
+            self
+        }
+
+        /// Frequency offset in units of 2^-16 ppm.
+        pub fn frequency(&self) -> c_long {
+            self.0.freq as c_long
+        }
+
+        /// Set the frequency offset, in units of 2^-16 ppm.
+        pub fn set_frequency(&mut self, freq: c_long) -> &mut TimeX {
+            self.0.freq = freq as _;
+            self.0.modes |= libc::ADJ_FREQUENCY;
+            self
+        }
+
+        /// Maximum error in microseconds.
+        pub fn max_error(&self) -> c_long {
+            self.0.maxerror as c_long
+        }
+
+        /// Set the maximum error, in microseconds.
+        pub fn set_max_error(&mut self, maxerror: c_long) -> &mut TimeX {
+            self.0.maxerror = maxerror as _;
+            self.0.modes |= libc::ADJ_MAXERROR;
+            self
+        }
+
+        /// Estimated error in microseconds.
+        pub fn est_error(&self) -> c_long {
+            self.0.esterror as c_long
+        }
+
+        /// Set the estimated error, in microseconds.
+        pub fn set_est_error(&mut self, esterror: c_long) -> &mut TimeX {
+            self.0.esterror = esterror as _;
+            self.0.modes |= libc::ADJ_ESTERROR;
+            self
+        }
+
+        /// Clock status bits.
+        pub fn status(&self) -> TimexStatus {
+            TimexStatus::from_bits_truncate(self.0.status)
+        }
+
+        /// Set the clock status bits.  The read-only bits are ignored.
+        pub fn set_status(&mut self, status: TimexStatus) -> &mut TimeX {
+            self.0.status = status.bits();
+            self.0.modes |= libc::ADJ_STATUS;
+            self
+        }
+
+        /// PLL time constant.
+        pub fn time_constant(&self) -> c_long {
+            self.0.constant as c_long
+        }
+
+        /// Set the PLL time constant.
+        pub fn set_time_constant(&mut self, constant: c_long) -> &mut TimeX {
+            self.0.constant = constant as _;
+            self.0.modes |= libc::ADJ_TIMECONST;
+            self
+        }
+
+        /// Microseconds between clock ticks.
+        pub fn tick(&self) -> c_long {
+            self.0.tick as c_long
+        }
+
+        /// Set the microseconds between clock ticks.
+        pub fn set_tick(&mut self, tick: c_long) -> &mut TimeX {
+            self.0.tick = tick as _;
+            self.0.modes |= libc::ADJ_TICK;
+            self
+        }
+
+        /// Clock precision in microseconds.
+        pub fn precision(&self) -> c_long {
+            self.0.precision as c_long
+        }
+
+        /// Maximum frequency error in units of 2^-16 ppm.
+        pub fn tolerance(&self) -> c_long {
+            self.0.tolerance as c_long
+        }
+
+        /// The current time of the clock, as read by the last call.
+        pub fn time(&self) -> TimeVal {
+            TimeVal(self.0.time)
+        }
+
+        /// Step the clock by `delta`, which may be negative.
+        pub fn set_time_offset(&mut self, delta: TimeVal) -> &mut TimeX {
+            self.0.time = delta.0;
+            self.0.modes |= libc::ADJ_SETOFFSET;
+            self
+        }
+
+        /// Offset between TAI and UTC in seconds.
+        pub fn tai(&self) -> c_int {
+            self.0.tai as c_int
+        }
+    }
+
+    /// Read and optionally adjust the parameters of the system clock (see
+    /// [adjtimex(2)](http://man7.org/linux/man-pages/man2/adjtimex.2.html)).
+    ///
+    /// On return `buf` holds the current parameters.  Only reading them, with
+    /// no modes set, doesn't need any privileges.
+    pub fn adjtimex(buf: &mut TimeX) -> Result<ClockState> {
+        let res = unsafe { libc::adjtimex(&mut buf.0) };
+
+        Errno::result(res).map(ClockState::from_raw)
+    }
+
+    /// Like [`adjtimex`](fn.adjtimex.html), but for the clock `clk_id`.
+    pub fn clock_adjtime(clk_id: ClockId, buf: &mut TimeX) -> Result<ClockState> {
+        let res = unsafe { libc::clock_adjtime(clk_id.as_raw(), &mut buf.0) };
+
+        Errno::result(res).map(ClockState::from_raw)
+    }
+}
+
 #[inline]
 fn div_mod_floor_64(this: i64, other: i64) -> (i64, i64) {
     (div_floor_64(this, other), mod_floor_64(this, other))
@@ -496,6 +1028,7 @@ fn div_rem_64(this: i64, other: i64) -> (i64, i64) {
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
     use super::{TimeSpec, TimeVal, TimeValLike};
 
     #[test]
@@ -515,6 +1048,12 @@ mod test {
         assert_eq!(a, -b);
     }
 
+    #[test]
+    pub fn test_timespec_num_microseconds() {
+        assert_eq!(TimeSpec::milliseconds(1500).num_microseconds(), 1_500_000);
+        assert_eq!(TimeSpec::nanoseconds(-2_500).num_microseconds(), -2);
+    }
+
     #[test]
     pub fn test_timespec_ord() {
         assert!(TimeSpec::seconds(1) == TimeSpec::nanoseconds(1_000_000_000));
@@ -569,4 +1108,53 @@ mod test {
         assert_eq!(TimeVal::nanoseconds(1402).to_string(), "0.000001 seconds");
         assert_eq!(TimeVal::seconds(-86401).to_string(), "-86401 seconds");
     }
+
+    #[test]
+    pub fn test_timespec_duration() {
+        let duration = Duration::from_millis(1500);
+        let ts = TimeSpec::from_duration(duration).unwrap();
+        assert_eq!(ts, TimeSpec::milliseconds(1500));
+        assert_eq!(ts.to_duration(), Some(duration));
+        assert_eq!(TimeSpec::from_duration(Duration::new(::std::u64::MAX, 0)), None);
+        assert_eq!(TimeSpec::from(duration), ts);
+        let max = TimeSpec::from(Duration::new(::std::u64::MAX, 0));
+        assert_eq!(max.tv_sec() as i64, super::TS_MAX_SECONDS);
+        assert_eq!(max.tv_nsec(), 999_999_999);
+
+        let sum = TimeSpec::milliseconds(700) + TimeSpec::milliseconds(600);
+        assert_eq!((sum.tv_sec(), sum.tv_nsec()), (1, 300_000_000));
+
+        // Negative values are normalized to a nanosecond field in [0, 1s)
+        let diff = TimeSpec::milliseconds(600) - TimeSpec::milliseconds(700);
+        assert_eq!((diff.tv_sec(), diff.tv_nsec()), (-1, 900_000_000));
+        assert_eq!(diff.num_milliseconds(), -100);
+        assert_eq!(diff.to_duration(), None);
+
+        assert!(TimeSpec::nanoseconds(999_999_999) < TimeSpec::seconds(1));
+        assert!(TimeSpec::nanoseconds(-1) < TimeSpec::zero());
+        assert!(TimeSpec::nanoseconds(-1_000_000_001) < TimeSpec::seconds(-1));
+    }
+
+    #[test]
+    pub fn test_timeval_duration() {
+        let duration = Duration::from_millis(1500);
+        let tv = TimeVal::from_duration(duration).unwrap();
+        assert_eq!(tv, TimeVal::milliseconds(1500));
+        assert_eq!(tv.to_duration(), Some(duration));
+        // Nanoseconds are rounded down
+        assert_eq!(TimeVal::from_duration(Duration::new(1, 1_999)),
+                   Some(TimeVal::microseconds(1_000_001)));
+        assert_eq!(TimeVal::from_duration(Duration::new(::std::u64::MAX, 0)), None);
+        assert_eq!(TimeVal::from(duration), tv);
+        let max = TimeVal::from(Duration::new(::std::u64::MAX, 0));
+        assert_eq!(max.tv_sec() as i64, super::TV_MAX_SECONDS);
+        assert_eq!(max.tv_usec(), 999_999);
+
+        let sum = TimeVal::milliseconds(700) + TimeVal::milliseconds(600);
+        assert_eq!((sum.tv_sec(), sum.tv_usec()), (1, 300_000));
+
+        let diff = TimeVal::milliseconds(600) - TimeVal::milliseconds(700);
+        assert_eq!((diff.tv_sec(), diff.tv_usec()), (-1, 900_000));
+        assert_eq!(diff.to_duration(), None);
+    }
 }
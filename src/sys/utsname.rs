@@ -1,68 +1,176 @@
 use std::mem;
 use libc::{self, c_char};
-use std::ffi::CStr;
-use std::str::from_utf8_unchecked;
+use std::ffi::{CStr, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use Result;
+use errno::Errno;
 
+/// Information about the running kernel, as returned by [`uname`](fn.uname.html).
 #[repr(C)]
 #[derive(Clone, Copy)]
 #[allow(missing_debug_implementations)]
 pub struct UtsName(libc::utsname);
 
 impl UtsName {
-    pub fn sysname(&self) -> &str {
-        to_str(&(&self.0.sysname as *const c_char ) as *const *const c_char)
+    /// Name of the operating system, such as `Linux`.
+    pub fn sysname(&self) -> &OsStr {
+        to_os_str(&self.0.sysname)
     }
 
-    pub fn nodename(&self) -> &str {
-        to_str(&(&self.0.nodename as *const c_char ) as *const *const c_char)
+    /// Network node hostname.
+    pub fn nodename(&self) -> &OsStr {
+        to_os_str(&self.0.nodename)
     }
 
-    pub fn release(&self) -> &str {
-        to_str(&(&self.0.release as *const c_char ) as *const *const c_char)
+    /// Release level of the operating system, such as `5.15.0-91-generic`.
+    pub fn release(&self) -> &OsStr {
+        to_os_str(&self.0.release)
     }
 
-    pub fn version(&self) -> &str {
-        to_str(&(&self.0.version as *const c_char ) as *const *const c_char)
+    /// Version level of the release.
+    pub fn version(&self) -> &OsStr {
+        to_os_str(&self.0.version)
     }
 
-    pub fn machine(&self) -> &str {
-        to_str(&(&self.0.machine as *const c_char ) as *const *const c_char)
+    /// Machine hardware platform, such as `x86_64`.
+    pub fn machine(&self) -> &OsStr {
+        to_os_str(&self.0.machine)
+    }
+
+    /// NIS or YP domain name of the system.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn domainname(&self) -> &OsStr {
+        to_os_str(&self.0.domainname)
+    }
+
+    /// The version of the running kernel as `(major, minor, patch)`, parsed
+    /// from [`release`](#method.release).
+    ///
+    /// See [`parse_kernel_version`](fn.parse_kernel_version.html).
+    pub fn kernel_version(&self) -> Option<(u32, u32, u32)> {
+        parse_kernel_version(self.release())
     }
 }
 
-pub fn uname() -> UtsName {
+/// Get information about the running kernel.
+///
+/// See also [uname(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/uname.html)
+pub fn uname() -> Result<UtsName> {
     unsafe {
         let mut ret: UtsName = mem::uninitialized();
-        libc::uname(&mut ret.0);
-        ret
+        let res = libc::uname(&mut ret.0);
+        Errno::result(res).map(|_| ret)
     }
 }
 
-#[inline]
-fn to_str<'a>(s: *const *const c_char) -> &'a str {
-    unsafe {
-        let res = CStr::from_ptr(*s).to_bytes();
-        from_utf8_unchecked(res)
+/// Parse a kernel release string such as `5.15.0-91-generic` into
+/// `(major, minor, patch)`.
+///
+/// Only the leading version numbers are considered and anything after them
+/// is ignored.  A missing patch level is reported as zero.  Returns `None`
+/// if the string doesn't start with at least `major.minor`.
+pub fn parse_kernel_version(release: &OsStr) -> Option<(u32, u32, u32)> {
+    let (major, rest) = match leading_number(release.as_bytes()) {
+        Some(n) => n,
+        None => return None,
+    };
+    let rest = match rest.split_first() {
+        Some((&b'.', rest)) => rest,
+        _ => return None,
+    };
+    let (minor, rest) = match leading_number(rest) {
+        Some(n) => n,
+        None => return None,
+    };
+    let patch = match rest.split_first() {
+        Some((&b'.', rest)) => leading_number(rest).map_or(0, |(patch, _)| patch),
+        _ => 0,
+    };
+
+    Some((major, minor, patch))
+}
+
+/// Split the decimal number at the start of `s` off the rest of it.
+fn leading_number(s: &[u8]) -> Option<(u32, &[u8])> {
+    let len = s.iter().take_while(|&&b| b >= b'0' && b <= b'9').count();
+    if len == 0 {
+        return None;
+    }
+
+    let mut n: u32 = 0;
+    for &b in &s[..len] {
+        n = match n.checked_mul(10).and_then(|n| n.checked_add((b - b'0') as u32)) {
+            Some(n) => n,
+            None => return None,
+        };
     }
+
+    Some((n, &s[len..]))
+}
+
+#[inline]
+fn to_os_str(s: &[c_char]) -> &OsStr {
+    // The kernel always NUL-terminates the fields of `struct utsname`.
+    unsafe { OsStr::from_bytes(CStr::from_ptr(s.as_ptr()).to_bytes()) }
 }
 
 #[cfg(test)]
 mod test {
+    use std::ffi::OsStr;
+
     #[cfg(target_os = "linux")]
     #[test]
     pub fn test_uname_linux() {
-        assert_eq!(super::uname().sysname(), "Linux");
+        assert_eq!(super::uname().unwrap().sysname(), "Linux");
     }
 
     #[cfg(any(target_os = "macos", target_os = "ios"))]
     #[test]
     pub fn test_uname_darwin() {
-        assert_eq!(super::uname().sysname(), "Darwin");
+        assert_eq!(super::uname().unwrap().sysname(), "Darwin");
     }
 
     #[cfg(target_os = "freebsd")]
     #[test]
     pub fn test_uname_freebsd() {
-        assert_eq!(super::uname().sysname(), "FreeBSD");
+        assert_eq!(super::uname().unwrap().sysname(), "FreeBSD");
+    }
+
+    #[test]
+    pub fn test_uname_fields() {
+        let uts = super::uname().unwrap();
+        assert!(!uts.machine().is_empty());
+        assert!(!uts.release().is_empty());
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    #[test]
+    pub fn test_running_kernel_version() {
+        let (major, _, _) = super::uname().unwrap().kernel_version().unwrap();
+        assert!(major >= 2);
+    }
+
+    #[test]
+    pub fn test_parse_kernel_version() {
+        let cases: &[(&str, Option<(u32, u32, u32)>)] = &[
+            ("5.15.0-91-generic", Some((5, 15, 0))),
+            ("6.1.55+", Some((6, 1, 55))),
+            ("4.19.112.1-microsoft-standard", Some((4, 19, 112))),
+            ("3.0", Some((3, 0, 0))),
+            ("3.0-rc1", Some((3, 0, 0))),
+            ("2.6.32.x", Some((2, 6, 32))),
+            ("13.2-RELEASE", Some((13, 2, 0))),
+            ("", None),
+            ("5", None),
+            ("5.", None),
+            ("5-generic", None),
+            (".5.15", None),
+            ("v5.15.0", None),
+            ("99999999999.1.0", None),
+        ];
+        for &(release, expected) in cases {
+            assert_eq!(super::parse_kernel_version(OsStr::new(release)), expected,
+                       "parsing {:?}", release);
+        }
     }
 }
@@ -1,9 +1,9 @@
 use libc::{self, c_int};
-use Result;
+use {Error, Result};
 use errno::Errno;
 use unistd::Pid;
 
-use sys::signal::Signal;
+use sys::signal::{kill, Signal};
 
 libc_bitflags!(
     pub struct WaitPidFlag: c_int {
@@ -41,6 +41,8 @@ libc_bitflags!(
         /// Wait on all children, regardless of type
         #[cfg(any(target_os = "android", target_os = "linux"))]
         __WALL;
+        /// Wait only on "clone" children, which deliver no signal or a signal
+        /// other than `SIGCHLD` to the parent when they terminate
         #[cfg(any(target_os = "android", target_os = "linux"))]
         __WCLONE;
     }
@@ -209,6 +211,13 @@ impl WaitStatus {
     }
 }
 
+/// Wait for a change of state of the child process `pid`, or of any child if it
+/// is `None` (see
+/// [waitpid(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/waitpid.html)).
+///
+/// On Linux, children created with [`clone`](../../sched/fn.clone.html) that
+/// don't deliver `SIGCHLD` on termination are only waited on if `__WALL` or
+/// `__WCLONE` is given; otherwise they cause `ECHILD`.
 pub fn waitpid<P: Into<Option<Pid>>>(pid: P, options: Option<WaitPidFlag>) -> Result<WaitStatus> {
     use self::WaitStatus::*;
 
@@ -236,3 +245,113 @@ pub fn waitpid<P: Into<Option<Pid>>>(pid: P, options: Option<WaitPidFlag>) -> Re
 pub fn wait() -> Result<WaitStatus> {
     waitpid(None, None)
 }
+
+/// Reap all children that have terminated without waiting for any others.
+///
+/// Calls `waitpid` with `WNOHANG` until no more children are ready or there
+/// are none left, returning the status of each one reaped.  This is useful
+/// for supervisors, including those made a child subreaper with
+/// `PR_SET_CHILD_SUBREAPER`, that have to reap orphans they didn't create.
+/// On Linux and Android `__WALL` is also used, so that children created with
+/// `clone` are reaped too.
+pub fn wait_all_nonblocking() -> Result<Vec<WaitStatus>> {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    let flags = WaitPidFlag::WNOHANG | WaitPidFlag::__WALL;
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    let flags = WaitPidFlag::WNOHANG;
+
+    let mut statuses = Vec::new();
+    loop {
+        match waitpid(None, Some(flags)) {
+            Ok(WaitStatus::StillAlive) | Err(Error::Sys(Errno::ECHILD)) => return Ok(statuses),
+            Ok(status) => statuses.push(status),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Kills and reaps a child process when dropped.
+///
+/// This makes sure that a child doesn't outlive its parent by accident, for
+/// example when the parent panics while the child is running.  On drop, the
+/// guard sends its signal to the child and then waits until it is reaped.  A
+/// child that has already exited is simply reaped, and one that has been
+/// [`wait`](#method.wait)ed for is left alone.
+///
+/// Dropping the guard blocks until the child terminates, so the signal should
+/// be one that terminates it, usually `SIGKILL`.
+#[derive(Debug)]
+pub struct ChildGuard {
+    pid: Pid,
+    signal: Signal,
+    armed: bool,
+}
+
+impl ChildGuard {
+    /// Guard the child `pid`, sending it `signal` when dropped.
+    pub fn new(pid: Pid, signal: Signal) -> ChildGuard {
+        ChildGuard { pid: pid, signal: signal, armed: true }
+    }
+
+    /// The child being guarded.
+    pub fn pid(&self) -> Pid {
+        self.pid
+    }
+
+    /// Wait for the child to change state, like `waitpid(pid, None)`.
+    ///
+    /// Once the child has terminated, the guard no longer does anything when
+    /// dropped.
+    pub fn wait(&mut self) -> Result<WaitStatus> {
+        let status = try!(waitpid(self.pid, Some(child_guard_flags())));
+        match status {
+            WaitStatus::Exited(..) | WaitStatus::Signaled(..) => self.armed = false,
+            _ => (),
+        }
+
+        Ok(status)
+    }
+
+    /// Disarm the guard, leaving the child running, and return its pid.
+    pub fn forget(mut self) -> Pid {
+        self.armed = false;
+        self.pid
+    }
+}
+
+impl From<Pid> for ChildGuard {
+    /// Guard the child `pid`, killing it with `SIGKILL` when dropped.
+    fn from(pid: Pid) -> ChildGuard {
+        ChildGuard::new(pid, Signal::SIGKILL)
+    }
+}
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+
+        // Fails with ESRCH if the child has already been reaped by other
+        // means, and then so does waitpid.
+        let _ = kill(self.pid, self.signal);
+        loop {
+            match waitpid(self.pid, Some(child_guard_flags())) {
+                Err(Error::Sys(Errno::EINTR)) => (),
+                Ok(WaitStatus::Exited(..)) | Ok(WaitStatus::Signaled(..)) | Err(_) => return,
+                // Stopped or continued
+                Ok(_) => (),
+            }
+        }
+    }
+}
+
+/// Also wait for children created with `clone` on Linux and Android.
+fn child_guard_flags() -> WaitPidFlag {
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    let flags = WaitPidFlag::__WALL;
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    let flags = WaitPidFlag::empty();
+
+    flags
+}
@@ -0,0 +1,189 @@
+//! Extended file attributes.
+//!
+//! Extended attributes are name-value pairs associated with files, with
+//! names like `user.mime_type` or `security.selinux` whose prefix selects
+//! the namespace.  Each operation comes in three variants: one taking a
+//! path, one taking a path but not following a final symbolic link (prefixed
+//! with `l`) and one taking a file descriptor (prefixed with `f`).  See
+//! [`xattr(7)`](http://man7.org/linux/man-pages/man7/xattr.7.html).
+//!
+//! Reading a missing attribute fails with `ENODATA`, while a filesystem that
+//! doesn't support extended attributes, or the namespace of the name, fails
+//! with `ENOTSUP`.  On Linux `ENOTSUP` is the same error as `EOPNOTSUPP`, so
+//! it is reported as `Errno::EOPNOTSUPP`; `errno::ENOTSUP` is an alias.
+use std::cmp;
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::io::RawFd;
+use std::ptr;
+use libc::{self, c_char, c_int, c_void, size_t, ssize_t};
+use {Error, Result, NixPath};
+use errno::Errno;
+
+libc_bitflags!{
+    /// Flags for [`setxattr`](fn.setxattr.html) and its variants.
+    pub struct XattrFlags: c_int {
+        /// Fail with `EEXIST` if the attribute already exists.
+        XATTR_CREATE;
+        /// Fail with `ENODATA` if the attribute doesn't exist yet.
+        XATTR_REPLACE;
+    }
+}
+
+/// Call `f` once to get the size of the value and again to fetch it,
+/// retrying if the value grew in between.
+fn get_value<F>(f: F) -> Result<Vec<u8>>
+    where F: Fn(*mut c_void, size_t) -> ssize_t
+{
+    loop {
+        // A size of zero would make the second call another size query, so
+        // always pass a buffer of at least one byte.
+        let size = try!(Errno::result(f(ptr::null_mut(), 0))) as usize;
+        let mut buf = vec![0u8; cmp::max(size, 1)];
+        match Errno::result(f(buf.as_mut_ptr() as *mut c_void, buf.len())) {
+            Ok(len) if len as usize <= buf.len() => {
+                buf.truncate(len as usize);
+                return Ok(buf);
+            },
+            Ok(_) | Err(Error::Sys(Errno::ERANGE)) => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Split a list of NUL-terminated attribute names.
+fn split_names(list: Vec<u8>) -> Vec<OsString> {
+    list.split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| OsString::from_vec(name.to_vec()))
+        .collect()
+}
+
+/// Get the value of the extended attribute `name` of the file at `path`.
+pub fn getxattr<P, N>(path: &P, name: &N) -> Result<Vec<u8>>
+    where P: ?Sized + NixPath, N: ?Sized + NixPath
+{
+    try!(try!(path.with_nix_path(|path| name.with_nix_path(|name| {
+        get_value(|buf, size| unsafe { libc::getxattr(path.as_ptr(), name.as_ptr(), buf, size) })
+    }))))
+}
+
+/// Like [`getxattr`](fn.getxattr.html), but get the attribute of a symbolic
+/// link itself rather than the file it refers to.
+pub fn lgetxattr<P, N>(path: &P, name: &N) -> Result<Vec<u8>>
+    where P: ?Sized + NixPath, N: ?Sized + NixPath
+{
+    try!(try!(path.with_nix_path(|path| name.with_nix_path(|name| {
+        get_value(|buf, size| unsafe { libc::lgetxattr(path.as_ptr(), name.as_ptr(), buf, size) })
+    }))))
+}
+
+/// Like [`getxattr`](fn.getxattr.html), but for the open file `fd`.
+pub fn fgetxattr<N: ?Sized + NixPath>(fd: RawFd, name: &N) -> Result<Vec<u8>> {
+    try!(name.with_nix_path(|name| {
+        get_value(|buf, size| unsafe { libc::fgetxattr(fd, name.as_ptr(), buf, size) })
+    }))
+}
+
+/// Set the extended attribute `name` of the file at `path` to `value`.
+///
+/// Without flags the attribute is created or replaced as needed.
+pub fn setxattr<P, N>(path: &P, name: &N, value: &[u8], flags: XattrFlags) -> Result<()>
+    where P: ?Sized + NixPath, N: ?Sized + NixPath
+{
+    let res = try!(try!(path.with_nix_path(|path| name.with_nix_path(|name| unsafe {
+        libc::setxattr(path.as_ptr(), name.as_ptr(), value.as_ptr() as *const c_void,
+                       value.len() as size_t, flags.bits())
+    }))));
+
+    Errno::result(res).map(drop)
+}
+
+/// Like [`setxattr`](fn.setxattr.html), but set the attribute of a symbolic
+/// link itself rather than the file it refers to.
+pub fn lsetxattr<P, N>(path: &P, name: &N, value: &[u8], flags: XattrFlags) -> Result<()>
+    where P: ?Sized + NixPath, N: ?Sized + NixPath
+{
+    let res = try!(try!(path.with_nix_path(|path| name.with_nix_path(|name| unsafe {
+        libc::lsetxattr(path.as_ptr(), name.as_ptr(), value.as_ptr() as *const c_void,
+                        value.len() as size_t, flags.bits())
+    }))));
+
+    Errno::result(res).map(drop)
+}
+
+/// Like [`setxattr`](fn.setxattr.html), but for the open file `fd`.
+pub fn fsetxattr<N: ?Sized + NixPath>(fd: RawFd, name: &N, value: &[u8], flags: XattrFlags) -> Result<()> {
+    let res = try!(name.with_nix_path(|name| unsafe {
+        libc::fsetxattr(fd, name.as_ptr(), value.as_ptr() as *const c_void,
+                        value.len() as size_t, flags.bits())
+    }));
+
+    Errno::result(res).map(drop)
+}
+
+/// List the names of the extended attributes of the file at `path`.
+///
+/// Only the attributes the caller has access to are listed.
+pub fn listxattr<P: ?Sized + NixPath>(path: &P) -> Result<Vec<OsString>> {
+    let list = try!(try!(path.with_nix_path(|path| {
+        get_value(|buf, size| unsafe {
+            libc::listxattr(path.as_ptr(), buf as *mut c_char, size)
+        })
+    })));
+
+    Ok(split_names(list))
+}
+
+/// Like [`listxattr`](fn.listxattr.html), but list the attributes of a
+/// symbolic link itself rather than the file it refers to.
+pub fn llistxattr<P: ?Sized + NixPath>(path: &P) -> Result<Vec<OsString>> {
+    let list = try!(try!(path.with_nix_path(|path| {
+        get_value(|buf, size| unsafe {
+            libc::llistxattr(path.as_ptr(), buf as *mut c_char, size)
+        })
+    })));
+
+    Ok(split_names(list))
+}
+
+/// Like [`listxattr`](fn.listxattr.html), but for the open file `fd`.
+pub fn flistxattr(fd: RawFd) -> Result<Vec<OsString>> {
+    let list = try!(get_value(|buf, size| unsafe {
+        libc::flistxattr(fd, buf as *mut c_char, size)
+    }));
+
+    Ok(split_names(list))
+}
+
+/// Remove the extended attribute `name` of the file at `path`.
+pub fn removexattr<P, N>(path: &P, name: &N) -> Result<()>
+    where P: ?Sized + NixPath, N: ?Sized + NixPath
+{
+    let res = try!(try!(path.with_nix_path(|path| name.with_nix_path(|name| unsafe {
+        libc::removexattr(path.as_ptr(), name.as_ptr())
+    }))));
+
+    Errno::result(res).map(drop)
+}
+
+/// Like [`removexattr`](fn.removexattr.html), but remove the attribute of a
+/// symbolic link itself rather than the file it refers to.
+pub fn lremovexattr<P, N>(path: &P, name: &N) -> Result<()>
+    where P: ?Sized + NixPath, N: ?Sized + NixPath
+{
+    let res = try!(try!(path.with_nix_path(|path| name.with_nix_path(|name| unsafe {
+        libc::lremovexattr(path.as_ptr(), name.as_ptr())
+    }))));
+
+    Errno::result(res).map(drop)
+}
+
+/// Like [`removexattr`](fn.removexattr.html), but for the open file `fd`.
+pub fn fremovexattr<N: ?Sized + NixPath>(fd: RawFd, name: &N) -> Result<()> {
+    let res = try!(name.with_nix_path(|name| unsafe {
+        libc::fremovexattr(fd, name.as_ptr())
+    }));
+
+    Errno::result(res).map(drop)
+}
@@ -2,18 +2,21 @@
 
 use errno::{self, Errno};
 use {Error, Result, NixPath};
-use fcntl::{fcntl, FdFlag, OFlag};
-use fcntl::FcntlArg::F_SETFD;
+use fcntl::{openat_owned, AtFlags, OFlag};
+use fd::OwnedFd;
 use libc::{self, c_char, c_void, c_int, c_long, c_uint, size_t, pid_t, off_t,
            uid_t, gid_t, mode_t};
-use std::{fmt, mem, ptr};
+use std::{error, fmt, mem, panic, ptr};
 use std::ffi::{CString, CStr, OsString, OsStr};
 use std::os::unix::ffi::{OsStringExt, OsStrExt};
-use std::os::unix::io::RawFd;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::path::PathBuf;
 use void::Void;
 use sys::stat::Mode;
 
+#[cfg(target_os = "linux")]
+pub use self::acct::*;
+
 #[cfg(any(target_os = "android", target_os = "linux"))]
 pub use self::pivot_root::*;
 
@@ -165,6 +168,16 @@ impl ForkResult {
     pub fn is_parent(&self) -> bool {
         !self.is_child()
     }
+
+    /// Returns the pid of the child in the parent process of the `fork()`,
+    /// and `None` in the child
+    #[inline]
+    pub fn child_pid(&self) -> Option<Pid> {
+        match *self {
+            ForkResult::Parent { child } => Some(child),
+            ForkResult::Child => None,
+        }
+    }
 }
 
 /// Create a new child process duplicating the parent process ([see
@@ -217,6 +230,46 @@ pub fn fork() -> Result<ForkResult> {
     })
 }
 
+/// Run `f` in a new child process and return the child's pid.
+///
+/// The child exits with the code returned by `f`, or with 101 if `f` panics.
+/// It always terminates with `_exit`, so it never returns from this
+/// function, runs no destructors or `atexit` handlers of the parent's stack
+/// and doesn't flush the stdio buffers it inherited.  Output written by `f`
+/// that is still buffered is lost too, so `f` should flush it before
+/// returning.
+///
+/// The parent has to reap the child, e.g. with
+/// [`waitpid`](../sys/wait/fn.waitpid.html).
+///
+/// ```no_run
+/// use nix::sys::wait::{waitpid, WaitStatus};
+/// use nix::unistd::fork_and_run;
+///
+/// let child = fork_and_run(|| 7).expect("fork failed");
+/// assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 7)));
+/// ```
+///
+/// # Notes
+///
+/// The restrictions of [`fork`](fn.fork.html) apply to `f`: in a
+/// multithreaded program, it may only call async-signal-safe functions.  In
+/// particular, a panic in `f` allocates and takes the lock of the panic
+/// hook, either of which may deadlock if another thread held the lock when
+/// the process was forked.
+pub fn fork_and_run<F: FnOnce() -> i32>(f: F) -> Result<Pid> {
+    match try!(fork()) {
+        ForkResult::Parent { child } => Ok(child),
+        ForkResult::Child => {
+            let code = match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+                Ok(code) => code,
+                Err(_) => 101,
+            };
+            unsafe { libc::_exit(code) }
+        }
+    }
+}
+
 /// Get the pid of this process (see
 /// [getpid(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getpid.html)).
 ///
@@ -246,11 +299,20 @@ pub fn getppid() -> Pid {
 /// apply some operations across the group.
 ///
 /// `setsid()` may be used to create a new process group.
+///
+/// A shell putting a child in a process group should call this in both the
+/// parent and the child, since either may run first.  Once the child has
+/// called `exec`, the parent's call fails with `EACCES`, which the parent can
+/// then ignore, as the child has set its group already.
 #[inline]
 pub fn setpgid(pid: Pid, pgid: Pid) -> Result<()> {
     let res = unsafe { libc::setpgid(pid.into(), pgid.into()) };
     Errno::result(res).map(drop)
 }
+
+/// Get the process group ID of process `pid`, or of the calling process if
+/// `None` (see
+/// [getpgid(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getpgid.html)).
 #[inline]
 pub fn getpgid(pid: Option<Pid>) -> Result<Pid> {
     let res = unsafe { libc::getpgid(pid.unwrap_or(Pid(0)).into()) };
@@ -264,6 +326,19 @@ pub fn setsid() -> Result<Pid> {
     Errno::result(unsafe { libc::setsid() }).map(Pid)
 }
 
+/// Simulate a hangup on the controlling terminal of the calling process (see
+/// [vhangup(2)](http://man7.org/linux/man-pages/man2/vhangup.2.html)).
+///
+/// Open file descriptors of the terminal become unusable and its foreground
+/// process group is sent `SIGHUP`, so that a new login session can start on
+/// the terminal with a clean slate.  Requires the `CAP_SYS_TTY_CONFIG`
+/// capability.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn vhangup() -> Result<()> {
+    let res = unsafe { libc::vhangup() };
+    Errno::result(res).map(drop)
+}
+
 /// Get the process group ID of a session leader
 /// [getsid(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getsid.html).
 ///
@@ -324,6 +399,41 @@ pub fn gettid() -> Pid {
     Pid(unsafe { libc::syscall(libc::SYS_gettid) as pid_t })
 }
 
+/// Flag for [`pidfd_open`](fn.pidfd_open.html) making the returned file
+/// descriptor nonblocking.
+#[cfg(target_os = "linux")]
+pub const PIDFD_NONBLOCK: u32 = libc::O_NONBLOCK as u32;
+
+/// Obtain a file descriptor referring to the process `pid` (see
+/// [pidfd_open(2)](http://man7.org/linux/man-pages/man2/pidfd_open.2.html)).
+///
+/// The file descriptor becomes readable, e.g. for `poll`, once the process
+/// has terminated.  `flags` may be zero or `PIDFD_NONBLOCK`.
+///
+/// This function is only available on Linux 5.3 and later.
+#[cfg(target_os = "linux")]
+pub fn pidfd_open(pid: Pid, flags: u32) -> Result<RawFd> {
+    let res = unsafe { libc::syscall(libc::SYS_pidfd_open, pid_t::from(pid), flags) };
+
+    Errno::result(res).map(|r| r as RawFd)
+}
+
+/// Duplicate the file descriptor `targetfd` of the process referred to by
+/// `pidfd` into the calling process (see
+/// [pidfd_getfd(2)](http://man7.org/linux/man-pages/man2/pidfd_getfd.2.html)).
+///
+/// The new file descriptor has the close-on-exec flag set.  `flags` is
+/// reserved and must be zero.  The caller needs permission to `ptrace` the
+/// target process.
+///
+/// This function is only available on Linux 5.6 and later.
+#[cfg(target_os = "linux")]
+pub fn pidfd_getfd(pidfd: RawFd, targetfd: RawFd, flags: u32) -> Result<RawFd> {
+    let res = unsafe { libc::syscall(libc::SYS_pidfd_getfd, pidfd, targetfd, flags) };
+
+    Errno::result(res).map(|r| r as RawFd)
+}
+
 /// Create a copy of the specified file descriptor (see
 /// [dup(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/dup.html)).
 ///
@@ -358,13 +468,32 @@ pub fn dup2(oldfd: RawFd, newfd: RawFd) -> Result<RawFd> {
 /// and flags (see [dup(2)](http://man7.org/linux/man-pages/man2/dup.2.html)).
 ///
 /// This function behaves similar to `dup2()` but allows for flags to be
-/// specified.
+/// specified.  Unlike `dup2()`, it fails with `EINVAL` if `oldfd` equals
+/// `newfd`.  On Linux and Android the `O_CLOEXEC` flag is applied atomically.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn dup3(oldfd: RawFd, newfd: RawFd, flags: OFlag) -> Result<RawFd> {
+    let res = unsafe { libc::dup3(oldfd, newfd, flags.bits()) };
+
+    Errno::result(res)
+}
+
+/// Create a new copy of the specified file descriptor using the specified fd
+/// and flags (see [dup(2)](http://man7.org/linux/man-pages/man2/dup.2.html)).
+///
+/// This function behaves similar to `dup2()` but allows for flags to be
+/// specified.  Unlike `dup2()`, it fails with `EINVAL` if `oldfd` equals
+/// `newfd`.
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
 pub fn dup3(oldfd: RawFd, newfd: RawFd, flags: OFlag) -> Result<RawFd> {
     dup3_polyfill(oldfd, newfd, flags)
 }
 
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
 #[inline]
 fn dup3_polyfill(oldfd: RawFd, newfd: RawFd, flags: OFlag) -> Result<RawFd> {
+    use fcntl::{fcntl, FdFlag};
+    use fcntl::FcntlArg::F_SETFD;
+
     if oldfd == newfd {
         return Err(Error::Sys(Errno::EINVAL));
     }
@@ -381,6 +510,181 @@ fn dup3_polyfill(oldfd: RawFd, newfd: RawFd, flags: OFlag) -> Result<RawFd> {
     Ok(fd)
 }
 
+/// Make the given file descriptors the standard input, output and error of
+/// the calling process.
+///
+/// Each provided fd is duplicated onto 0, 1 or 2 respectively, and the
+/// resulting standard streams don't have `FD_CLOEXEC` set, so they survive an
+/// `exec`.  Streams for which `None` is passed are left alone.  Sources that
+/// are themselves standard streams are handled correctly, so for example
+/// `stdio_redirect(None, Some(2), Some(1))` swaps stdout and stderr.  The
+/// source fds are left open.
+///
+/// This doesn't allocate, so it is safe to call between `fork` and `exec`.
+pub fn stdio_redirect(stdin: Option<RawFd>, stdout: Option<RawFd>, stderr: Option<RawFd>) -> Result<()> {
+    let mut sources = [stdin, stdout, stderr];
+    let mut moved: [Option<RawFd>; 3] = [None; 3];
+    let res = stdio_redirect_inner(&mut sources, &mut moved);
+
+    for fd in moved.iter().filter_map(|&fd| fd) {
+        let _ = close(fd);
+    }
+
+    res
+}
+
+fn stdio_redirect_inner(sources: &mut [Option<RawFd>; 3], moved: &mut [Option<RawFd>; 3]) -> Result<()> {
+    use fcntl::{fcntl, FdFlag};
+    use fcntl::FcntlArg::{F_DUPFD_CLOEXEC, F_GETFD, F_SETFD};
+
+    // Move sources that are standard streams out of the way first, so that
+    // they aren't overwritten before being duplicated.
+    for (target, source) in sources.iter_mut().enumerate() {
+        if let Some(fd) = *source {
+            if fd >= 0 && fd <= 2 && fd != target as RawFd {
+                let copy = try!(fcntl(fd, F_DUPFD_CLOEXEC(3)));
+                moved[target] = Some(copy);
+                *source = Some(copy);
+            }
+        }
+    }
+
+    for (target, source) in sources.iter().enumerate() {
+        let target = target as RawFd;
+        match *source {
+            // `dup2` does nothing if the fds are the same, so clear the flag
+            // by hand.
+            Some(fd) if fd == target => {
+                let flags = FdFlag::from_bits_truncate(try!(fcntl(fd, F_GETFD)));
+                try!(fcntl(fd, F_SETFD(flags - FdFlag::FD_CLOEXEC)));
+            },
+            Some(fd) => {
+                try!(dup2(fd, target));
+            },
+            None => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// The first file descriptor passed on by
+/// [`prepare_inherited_fds`](fn.prepare_inherited_fds.html), following the
+/// standard streams.
+const LISTEN_FDS_START: RawFd = 3;
+
+/// Arrange for `fds` to be inherited across `exec` as the file descriptors
+/// 3, 4, 5 and so on, in order, as in systemd's socket activation protocol.
+///
+/// Each fd is duplicated onto its target, and the targets don't have
+/// `FD_CLOEXEC` set.  Sources that are themselves in the target range are
+/// moved out of the way first, so they aren't overwritten before being
+/// duplicated.  Anything else open in the target range is closed, so this is
+/// meant to be called in the child between `fork` and `exec`.  The source fds
+/// are left open.
+///
+/// Unlike most functions in this module, this doesn't return a `Vec`,
+/// because allocating between `fork` and `exec` isn't safe in a multithreaded
+/// program.  Instead `scratch` holds the temporary copies of moved sources,
+/// and the target file descriptors are stored in `targets`.  Both
+/// must have room for at least `fds.len()` fds, otherwise this fails with
+/// `EINVAL` without changing anything.  The new program usually also needs
+/// `LISTEN_FDS` and `LISTEN_PID` set to find them with
+/// [`read_listen_fds`](fn.read_listen_fds.html).
+pub fn prepare_inherited_fds(fds: &[RawFd], scratch: &mut [RawFd], targets: &mut [RawFd])
+    -> Result<()>
+{
+    if scratch.len() < fds.len() || targets.len() < fds.len() {
+        return Err(Error::invalid_argument());
+    }
+
+    let moved = &mut scratch[..fds.len()];
+    for fd in moved.iter_mut() {
+        *fd = -1;
+    }
+    let res = prepare_inherited_fds_inner(fds, moved, &mut targets[..fds.len()]);
+
+    for &fd in moved.iter().filter(|&&fd| fd >= 0) {
+        let _ = close(fd);
+    }
+
+    res
+}
+
+fn prepare_inherited_fds_inner(fds: &[RawFd], moved: &mut [RawFd], targets: &mut [RawFd])
+    -> Result<()>
+{
+    use fcntl::{fcntl, FdFlag};
+    use fcntl::FcntlArg::{F_DUPFD_CLOEXEC, F_GETFD, F_SETFD};
+
+    let end = LISTEN_FDS_START + fds.len() as RawFd;
+
+    // Move sources that are in the target range out of it first, so that
+    // they aren't overwritten before being duplicated.
+    for (i, (&fd, copy)) in fds.iter().zip(moved.iter_mut()).enumerate() {
+        let target = LISTEN_FDS_START + i as RawFd;
+        if fd >= LISTEN_FDS_START && fd < end && fd != target {
+            *copy = try!(fcntl(fd, F_DUPFD_CLOEXEC(end)));
+        }
+    }
+
+    for (i, (&fd, &copy)) in fds.iter().zip(moved.iter()).enumerate() {
+        let source = if copy >= 0 { copy } else { fd };
+        let target = LISTEN_FDS_START + i as RawFd;
+        if source == target {
+            // `dup2` does nothing if the fds are the same, so clear the flag
+            // by hand.
+            let flags = FdFlag::from_bits_truncate(try!(fcntl(source, F_GETFD)));
+            try!(fcntl(source, F_SETFD(flags - FdFlag::FD_CLOEXEC)));
+        } else {
+            try!(dup2(source, target));
+        }
+        targets[i] = target;
+    }
+
+    Ok(())
+}
+
+/// Get the file descriptors passed on to the calling process by socket
+/// activation, as set up by
+/// [`prepare_inherited_fds`](fn.prepare_inherited_fds.html) or systemd.
+///
+/// The fds are described by the environment variables `LISTEN_FDS`, their
+/// number, and `LISTEN_PID`, the process they are meant for.  If either isn't
+/// set, or they are meant for another process, no fds are returned.  Fails
+/// with `EINVAL` if the variables can't be parsed, and with `EBADF` if one of
+/// the fds isn't open.
+///
+/// `FD_CLOEXEC` is set on the returned fds so they aren't passed on any
+/// further.  The environment variables are left alone.
+pub fn read_listen_fds() -> Result<Vec<RawFd>> {
+    use std::env;
+    use fcntl::{fcntl, FdFlag};
+    use fcntl::FcntlArg::{F_GETFD, F_SETFD};
+
+    let (pid, n) = match (env::var("LISTEN_PID"), env::var("LISTEN_FDS")) {
+        (Ok(pid), Ok(n)) => (pid, n),
+        _ => return Ok(Vec::new()),
+    };
+    let pid: pid_t = try!(pid.parse().map_err(|_| Error::invalid_argument()));
+    let n: RawFd = try!(n.parse().map_err(|_| Error::invalid_argument()));
+    if n < 0 || n > ::std::i32::MAX - LISTEN_FDS_START {
+        return Err(Error::invalid_argument());
+    }
+    if Pid::from_raw(pid) != getpid() {
+        return Ok(Vec::new());
+    }
+
+    let mut fds = Vec::with_capacity(n as usize);
+    for fd in LISTEN_FDS_START..LISTEN_FDS_START + n {
+        let flags = FdFlag::from_bits_truncate(try!(fcntl(fd, F_GETFD)));
+        try!(fcntl(fd, F_SETFD(flags | FdFlag::FD_CLOEXEC)));
+        fds.push(fd);
+    }
+
+    Ok(fds)
+}
+
 /// Change the current working directory of the calling process (see
 /// [chdir(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/chdir.html)).
 ///
@@ -563,6 +867,202 @@ pub fn chown<P: ?Sized + NixPath>(path: &P, owner: Option<Uid>, group: Option<Gi
     Errno::result(res).map(drop)
 }
 
+/// Flags for `fchownat` function.
+#[derive(Clone, Copy, Debug)]
+pub enum FchownatFlags {
+    FollowSymlink,
+    NoFollowSymlink,
+}
+
+/// Change the ownership of the file at `path` to be owned by the specified
+/// `owner` (user) and `group`.
+///
+/// The owner/group for the provided path name will not be modified if `None` is
+/// provided for that argument.  Ownership change will be attempted for the path
+/// only if `Some` owner/group is provided.
+///
+/// The file to be changed is determined relative to the directory associated
+/// with the file descriptor `dirfd` or the current working directory
+/// if `dirfd` is `None`.
+///
+/// If `flag` is `FchownatFlags::NoFollowSymlink` and `path` names a symbolic link,
+/// then the ownership of the symbolic link is changed.
+///
+/// `fchownat(None, path, owner, group, FchownatFlags::NoFollowSymlink)` is
+/// identical to a call `libc::lchown(path, owner, group)`.
+///
+/// # References
+///
+/// [fchownat(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/fchownat.html).
+pub fn fchownat<P: ?Sized + NixPath>(
+    dirfd: Option<RawFd>,
+    path: &P,
+    owner: Option<Uid>,
+    group: Option<Gid>,
+    flag: FchownatFlags,
+) -> Result<()> {
+    let atflag =
+        match flag {
+            FchownatFlags::FollowSymlink => AtFlags::empty(),
+            FchownatFlags::NoFollowSymlink => AtFlags::AT_SYMLINK_NOFOLLOW,
+        };
+    let res = try!(path.with_nix_path(|cstr| unsafe {
+        libc::fchownat(dirfd.unwrap_or(libc::AT_FDCWD), cstr.as_ptr(),
+                       owner.map(Into::into).unwrap_or((0 as uid_t).wrapping_sub(1)),
+                       group.map(Into::into).unwrap_or((0 as gid_t).wrapping_sub(1)),
+                       atflag.bits() as libc::c_int)
+    }));
+
+    Errno::result(res).map(drop)
+}
+
+/// Change the ownership of `path` and, if it is a directory, of everything
+/// below it, like `chown -R`.
+///
+/// `path` is resolved relative to `dirfd` or the current working directory
+/// as for [`fchownat`](fn.fchownat.html).  If `follow_symlinks` is set and
+/// `path` itself is a symbolic link, the file it points to is changed or
+/// walked instead.  Symbolic links inside the tree are never followed: the
+/// links themselves are changed, and each directory is opened relative to
+/// its parent with `O_NOFOLLOW`, so that the walk can't be led out of the
+/// tree by links replacing directories either.
+///
+/// Returns the number of files changed.  Stops at the first error.
+pub fn chown_recursive<P: ?Sized + NixPath>(dirfd: Option<RawFd>, path: &P, owner: Option<Uid>,
+                                            group: Option<Gid>, follow_symlinks: bool) -> Result<usize> {
+    let nofollow = if follow_symlinks { OFlag::empty() } else { OFlag::O_NOFOLLOW };
+    chown_tree(dirfd.unwrap_or(libc::AT_FDCWD), path, owner, group, nofollow)
+}
+
+// Walk the tree iteratively, keeping a stream for each directory between the
+// root and the current one, so that deep trees can't overflow the stack.
+fn chown_tree<P: ?Sized + NixPath>(dirfd: RawFd, path: &P, owner: Option<Uid>, group: Option<Gid>,
+                                   nofollow: OFlag) -> Result<usize> {
+    let mut stack = match try!(chown_entry(dirfd, path, owner, group, nofollow)) {
+        Some(dir) => vec![dir],
+        None => return Ok(1),
+    };
+
+    let mut count = 0;
+    loop {
+        // `None` once the current directory is done, otherwise the directory
+        // to descend into, if any.
+        let step = {
+            let dir = match stack.last_mut() {
+                Some(dir) => dir,
+                None => break,
+            };
+            let fd = dir.as_raw_fd();
+            match try!(dir.next_entry()) {
+                Some((name, d_type)) => {
+                    if d_type == libc::DT_DIR || d_type == libc::DT_UNKNOWN {
+                        Some(try!(chown_entry(fd, name, owner, group, OFlag::O_NOFOLLOW)))
+                    } else {
+                        try!(fchownat(Some(fd), name, owner, group,
+                                      FchownatFlags::NoFollowSymlink));
+                        Some(None)
+                    }
+                },
+                None => {
+                    // The directory itself is changed after its contents.
+                    let res = unsafe {
+                        libc::fchown(fd,
+                                     owner.map(Into::into).unwrap_or((0 as uid_t).wrapping_sub(1)),
+                                     group.map(Into::into).unwrap_or((0 as gid_t).wrapping_sub(1)))
+                    };
+                    try!(Errno::result(res));
+                    None
+                },
+            }
+        };
+        match step {
+            Some(Some(dir)) => stack.push(dir),
+            Some(None) => count += 1,
+            None => {
+                stack.pop();
+                count += 1;
+            },
+        }
+    }
+
+    Ok(count)
+}
+
+// Open `path` to walk it if it is a directory.  Otherwise just change it and
+// return `None`.
+fn chown_entry<P: ?Sized + NixPath>(dirfd: RawFd, path: &P, owner: Option<Uid>,
+                                    group: Option<Gid>, nofollow: OFlag) -> Result<Option<DirStream>> {
+    let flags = OFlag::O_RDONLY | OFlag::O_DIRECTORY | OFlag::O_CLOEXEC | nofollow;
+    match openat_owned(dirfd, path, flags, Mode::empty()) {
+        Ok(fd) => DirStream::new(fd).map(Some),
+        // Not a directory, or a symbolic link not to be followed
+        Err(Error::Sys(Errno::ENOTDIR)) | Err(Error::Sys(Errno::ELOOP)) => {
+            let flag = if nofollow.is_empty() {
+                FchownatFlags::FollowSymlink
+            } else {
+                FchownatFlags::NoFollowSymlink
+            };
+            try!(fchownat(Some(dirfd), path, owner, group, flag));
+            Ok(None)
+        },
+        Err(e) => Err(e),
+    }
+}
+
+// A directory stream reading the entries of a directory file descriptor.
+struct DirStream(*mut libc::DIR);
+
+impl DirStream {
+    fn new(fd: OwnedFd) -> Result<DirStream> {
+        // The stream takes ownership of the file descriptor and closes it in
+        // closedir.
+        let fd = fd.into_raw_fd();
+        let dir = unsafe { libc::fdopendir(fd) };
+        if dir.is_null() {
+            let err = Errno::last();
+            unsafe { libc::close(fd) };
+            return Err(Error::Sys(err));
+        }
+
+        Ok(DirStream(dir))
+    }
+
+    // The name and type of the next entry other than `.` and `..`.  The name
+    // stays valid until the next call.
+    fn next_entry(&mut self) -> Result<Option<(&CStr, u8)>> {
+        loop {
+            unsafe { Errno::clear() };
+            let entry = unsafe { libc::readdir(self.0) };
+            if entry.is_null() {
+                if errno::errno() != 0 {
+                    return Err(Error::Sys(Errno::last()));
+                }
+                return Ok(None);
+            }
+
+            let (name, d_type) = unsafe {
+                (CStr::from_ptr((*entry).d_name.as_ptr()), (*entry).d_type)
+            };
+            let bytes = name.to_bytes();
+            if bytes != b"." && bytes != b".." {
+                return Ok(Some((name, d_type)));
+            }
+        }
+    }
+}
+
+impl AsRawFd for DirStream {
+    fn as_raw_fd(&self) -> RawFd {
+        unsafe { libc::dirfd(self.0) }
+    }
+}
+
+impl Drop for DirStream {
+    fn drop(&mut self) {
+        unsafe { libc::closedir(self.0) };
+    }
+}
+
 fn to_exec_array(args: &[CString]) -> Vec<*const c_char> {
     let mut args_p: Vec<*const c_char> = args.iter().map(|s| s.as_ptr()).collect();
     args_p.push(ptr::null());
@@ -631,6 +1131,112 @@ pub fn execvp(filename: &CString, args: &[CString]) -> Result<Void> {
     Err(Error::Sys(Errno::last()))
 }
 
+/// Like [`execvp`](fn.execvp.html), but with the environment `env` for the
+/// new program, like for [`execve`](fn.execve.html).
+///
+/// [`EnvBuilder`](struct.EnvBuilder.html) builds `env` from the environment
+/// of the calling process.
+///
+/// This allocates the null-terminated pointer arrays for `args` and `env`, so
+/// calling it between `fork` and `exec` in a multithreaded program isn't
+/// async-signal-safe.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[inline]
+pub fn execvpe(filename: &CString, args: &[CString], env: &[CString]) -> Result<Void> {
+    let args_p = to_exec_array(args);
+    let env_p = to_exec_array(env);
+
+    unsafe {
+        libc::execvpe(filename.as_ptr(), args_p.as_ptr(), env_p.as_ptr())
+    };
+
+    Err(Error::Sys(Errno::last()))
+}
+
+#[cfg(not(any(target_os = "ios", target_os = "macos")))]
+extern {
+    static environ: *const *const c_char;
+}
+
+/// Like [`execve`](fn.execve.html), passing the live environment of the
+/// calling process to the new program without copying it.
+///
+/// Unsafe because the environment must not be modified concurrently, e.g. by
+/// `std::env::set_var` in another thread, while it is handed to the kernel.
+/// The environment isn't copied, but the null-terminated pointer array for
+/// `args` is allocated, so calling this between `fork` and `exec` in a
+/// multithreaded program isn't async-signal-safe.
+#[cfg(not(any(target_os = "ios", target_os = "macos")))]
+#[inline]
+pub unsafe fn execv_current_env(path: &CString, args: &[CString]) -> Result<Void> {
+    let args_p = to_exec_array(args);
+
+    libc::execve(path.as_ptr(), args_p.as_ptr(), environ);
+
+    Err(Error::Sys(Errno::last()))
+}
+
+/// The environment for a new program, as passed to
+/// [`execve`](fn.execve.html) and friends.
+///
+/// Starts out as a copy of the environment of the calling process, to which
+/// variables are added or from which they're removed before it is turned into
+/// the `CString`s of `key=value` pairs by [`build`](#method.build).
+///
+/// ```no_run
+/// # use std::ffi::CString;
+/// # use nix::unistd::{execvpe, EnvBuilder};
+/// let env = EnvBuilder::new().set("LC_ALL", "C").remove("LANG").build().unwrap();
+/// let args = [CString::new("ls").unwrap()];
+/// execvpe(&args[0], &args, &env).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnvBuilder {
+    vars: Vec<(OsString, OsString)>,
+}
+
+impl EnvBuilder {
+    /// Start from the current environment of the calling process.
+    pub fn new() -> EnvBuilder {
+        EnvBuilder { vars: ::std::env::vars_os().collect() }
+    }
+
+    /// Set the variable `key` to `value`, replacing any previous value.
+    pub fn set<K: AsRef<OsStr>, V: AsRef<OsStr>>(self, key: K, value: V) -> EnvBuilder {
+        let mut env = self.remove(&key);
+        env.vars.push((key.as_ref().to_owned(), value.as_ref().to_owned()));
+        env
+    }
+
+    /// Remove the variable `key`, if it is set.
+    pub fn remove<K: AsRef<OsStr>>(mut self, key: K) -> EnvBuilder {
+        self.vars.retain(|&(ref k, _)| k.as_os_str() != key.as_ref());
+        self
+    }
+
+    /// Produce the `key=value` strings of the environment.
+    ///
+    /// Fails with `EINVAL` if a key is empty or contains `=`, or if a key or
+    /// value contains a NUL byte.
+    pub fn build(&self) -> Result<Vec<CString>> {
+        let mut env = Vec::with_capacity(self.vars.len());
+        for &(ref key, ref value) in &self.vars {
+            let key = key.as_bytes();
+            if key.is_empty() || key.contains(&b'=') {
+                return Err(Error::invalid_argument());
+            }
+
+            let mut var = Vec::with_capacity(key.len() + value.len() + 2);
+            var.extend_from_slice(key);
+            var.push(b'=');
+            var.extend_from_slice(value.as_bytes());
+            env.push(try!(CString::new(var).map_err(|_| Error::invalid_argument())));
+        }
+
+        Ok(env)
+    }
+}
+
 /// Replace the current process image with a new one (see
 /// [fexecve(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/fexecve.html)).
 ///
@@ -739,35 +1345,59 @@ pub fn sethostname<S: AsRef<OsStr>>(name: S) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
-/// Get the host name and store it in the provided buffer, returning a pointer
-/// the `CStr` in that buffer on success (see
+/// Set the NIS domain name of the system (see
+/// [setdomainname(2)](http://man7.org/linux/man-pages/man2/setdomainname.2.html)).
+///
+/// Like the host name, the domain name is part of the UTS namespace of the
+/// process, so it may be changed without affecting the rest of the system
+/// after `unshare(CLONE_NEWUTS)`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn setdomainname<S: AsRef<OsStr>>(name: S) -> Result<()> {
+    let ptr = name.as_ref().as_bytes().as_ptr() as *const c_char;
+    let len = name.as_ref().len() as size_t;
+
+    let res = unsafe { libc::setdomainname(ptr, len) };
+    Errno::result(res).map(drop)
+}
+
+/// Get the host name of the system (see
 /// [gethostname(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/gethostname.html)).
 ///
-/// This function call attempts to get the host name for the running system and
-/// store it in a provided buffer.  The buffer will be populated with bytes up
-/// to the length of the provided slice including a NUL terminating byte.  If
-/// the hostname is longer than the length provided, no error will be provided.
-/// The posix specification does not specify whether implementations will
-/// null-terminate in this case, but the nix implementation will ensure that the
-/// buffer is null terminated in this case.
+/// The buffer is sized according to `HOST_NAME_MAX`, so the whole name is
+/// always returned.
 ///
 /// ```no_run
 /// use nix::unistd;
 ///
-/// let mut buf = [0u8; 64];
-/// let hostname_cstr = unistd::gethostname(&mut buf).expect("Failed getting hostname");
-/// let hostname = hostname_cstr.to_str().expect("Hostname wasn't valid UTF-8");
+/// let hostname = unistd::gethostname().expect("Failed getting hostname");
+/// let hostname = hostname.into_string().expect("Hostname wasn't valid UTF-8");
 /// println!("Hostname: {}", hostname);
 /// ```
-pub fn gethostname(buffer: &mut [u8]) -> Result<&CStr> {
-    let ptr = buffer.as_mut_ptr() as *mut c_char;
-    let len = buffer.len() as size_t;
-
-    let res = unsafe { libc::gethostname(ptr, len) };
-    Errno::result(res).map(|_| {
-        buffer[len - 1] = 0; // ensure always null-terminated
-        unsafe { CStr::from_ptr(buffer.as_ptr() as *const c_char) }
-    })
+pub fn gethostname() -> Result<OsString> {
+    // POSIX requires host names of at least 255 bytes to be supported.
+    #[cfg(any(target_os="dragonfly", target_os="freebsd", target_os = "ios",
+              target_os="linux", target_os = "macos", target_os="netbsd",
+              target_os="openbsd"))]
+    let max_len = match sysconf(SysconfVar::HOST_NAME_MAX) {
+        Ok(Some(max_len)) => max_len as usize,
+        _ => 255,
+    };
+    #[cfg(not(any(target_os="dragonfly", target_os="freebsd", target_os = "ios",
+                  target_os="linux", target_os = "macos", target_os="netbsd",
+                  target_os="openbsd")))]
+    let max_len = 255;
+
+    // A truncated name need not be NUL-terminated, so leave room for the
+    // terminator and stop at the end of the buffer if there is none.
+    let mut buffer = vec![0u8; max_len + 1];
+    let res = unsafe {
+        libc::gethostname(buffer.as_mut_ptr() as *mut c_char, buffer.len() as size_t)
+    };
+    try!(Errno::result(res));
+
+    let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    buffer.truncate(len);
+    Ok(OsString::from_vec(buffer))
 }
 
 /// Close a raw file descriptor
@@ -810,6 +1440,37 @@ pub fn close(fd: RawFd) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+#[cfg(target_os = "linux")]
+libc_bitflags!{
+    /// Flags for [`close_range`](fn.close_range.html).
+    pub struct CloseRangeFlags: c_uint {
+        /// Unshare the file descriptor table before closing, so that other
+        /// processes sharing it are unaffected.
+        CLOSE_RANGE_UNSHARE;
+        /// Set the close-on-exec flag on the file descriptors instead of
+        /// closing them.
+        CLOSE_RANGE_CLOEXEC;
+    }
+}
+
+/// Close all file descriptors from `first` to `last`, inclusive (see
+/// [close_range(2)](http://man7.org/linux/man-pages/man2/close_range.2.html)).
+///
+/// Descriptors in the range that aren't open are ignored, so passing
+/// `RawFd::max_value()` as `last` closes everything from `first` onwards.
+///
+/// This function is only available on Linux 5.9 and later; older kernels
+/// fail with `ENOSYS`, in which case callers should fall back to closing the
+/// descriptors individually.
+#[cfg(target_os = "linux")]
+pub fn close_range(first: RawFd, last: RawFd, flags: CloseRangeFlags) -> Result<()> {
+    let res = unsafe {
+        libc::syscall(libc::SYS_close_range, first as c_uint, last as c_uint, flags.bits())
+    };
+
+    Errno::result(res).map(drop)
+}
+
 /// Read from a raw file descriptor.
 ///
 /// See also [read(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/read.html)
@@ -942,10 +1603,27 @@ pub fn pipe2(flags: OFlag) -> Result<(RawFd, RawFd)> {
     Ok((fds[0], fds[1]))
 }
 
+/// Like [`pipe2`](fn.pipe2.html), but return the read and write ends as
+/// [`OwnedFd`](../fd/struct.OwnedFd.html)s.
+#[cfg(any(target_os = "android",
+          target_os = "dragonfly",
+          target_os = "emscripten",
+          target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub fn pipe2_owned(flags: OFlag) -> Result<(OwnedFd, OwnedFd)> {
+    let (reader, writer) = try!(pipe2(flags));
+
+    Ok(unsafe { (OwnedFd::from_raw_fd(reader), OwnedFd::from_raw_fd(writer)) })
+}
+
 #[cfg(any(target_os = "ios", target_os = "macos"))]
 fn pipe2_setflags(fd1: RawFd, fd2: RawFd, flags: OFlag) -> Result<()> {
-    use fcntl::FdFlag;
-    use fcntl::FcntlArg::F_SETFL;
+    use fcntl::{fcntl, FdFlag};
+    use fcntl::FcntlArg::{F_SETFD, F_SETFL};
 
     let mut res = Ok(0);
 
@@ -1008,6 +1686,13 @@ pub fn unlink<P: ?Sized + NixPath>(path: &P) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// Change the root directory of the calling process
+///
+/// This doesn't change the current working directory, which may then lie
+/// outside of the new root.  Use [`chroot_and_chdir`](fn.chroot_and_chdir.html)
+/// to confine the process to the new root.
+///
+/// See also [chroot(2)](http://man7.org/linux/man-pages/man2/chroot.2.html)
 #[inline]
 pub fn chroot<P: ?Sized + NixPath>(path: &P) -> Result<()> {
     let res = try!(path.with_nix_path(|cstr| {
@@ -1017,6 +1702,77 @@ pub fn chroot<P: ?Sized + NixPath>(path: &P) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// The step of [`chroot_and_chdir`](fn.chroot_and_chdir.html) that failed
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChrootStep {
+    /// Changing into the new root directory
+    Chdir,
+    /// Changing the root directory
+    Chroot,
+    /// Changing into `/` inside the new root
+    ChdirRoot,
+}
+
+/// Error returned by [`chroot_and_chdir`](fn.chroot_and_chdir.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChrootError {
+    /// The step that failed
+    pub step: ChrootStep,
+    /// Why it failed
+    pub error: Error,
+}
+
+impl fmt::Display for ChrootError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let step = match self.step {
+            ChrootStep::Chdir => "chdir into the new root",
+            ChrootStep::Chroot => "chroot",
+            ChrootStep::ChdirRoot => "chdir to / inside the new root",
+        };
+        write!(f, "{} failed: {}", step, self.error)
+    }
+}
+
+impl error::Error for ChrootError {
+    fn description(&self) -> &str {
+        "chroot_and_chdir failed"
+    }
+}
+
+impl From<ChrootError> for Error {
+    fn from(err: ChrootError) -> Error {
+        err.error
+    }
+}
+
+/// Change the root directory of the calling process to `path` and make it
+/// the current working directory
+///
+/// After a plain [`chroot`](fn.chroot.html) the working directory stays where
+/// it was, and relative paths like `../../etc/passwd` escape the new root.
+/// This changes into `path` first, makes it the root with `chroot(".")` so it
+/// is only resolved once, and finally changes into `/`.
+///
+/// Requires the `CAP_SYS_CHROOT` capability, which an unprivileged process
+/// gets in a new user namespace (see [`unshare`](../sched/fn.unshare.html)).
+pub fn chroot_and_chdir<P: ?Sized + NixPath>(path: &P) -> ::std::result::Result<(), ChrootError> {
+    fn step(step: ChrootStep, res: Result<()>) -> ::std::result::Result<(), ChrootError> {
+        res.map_err(|error| ChrootError { step: step, error: error })
+    }
+
+    try!(step(ChrootStep::Chdir, chdir(path)));
+    try!(step(ChrootStep::Chroot, chroot(".")));
+    step(ChrootStep::ChdirRoot, chdir("/"))
+}
+
+/// Commit all filesystem caches to disk
+///
+/// See also [sync(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/sync.html)
+#[inline]
+pub fn sync() {
+    unsafe { libc::sync() };
+}
+
 /// Synchronize changes to a file
 ///
 /// See also [fsync(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/fsync.html)
@@ -1043,6 +1799,17 @@ pub fn fdatasync(fd: RawFd) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// Synchronize the whole filesystem containing the file referred to by `fd`
+///
+/// See also [syncfs(2)](http://man7.org/linux/man-pages/man2/syncfs.2.html)
+#[cfg(target_os = "linux")]
+#[inline]
+pub fn syncfs(fd: RawFd) -> Result<()> {
+    let res = unsafe { libc::syncfs(fd) };
+
+    Errno::result(res).map(drop)
+}
+
 /// Get a real user ID
 ///
 /// See also [getuid(2)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/getuid.html)
@@ -1103,6 +1870,50 @@ pub fn setgid(gid: Gid) -> Result<()> {
     Errno::result(res).map(drop)
 }
 
+/// Set the filesystem user ID of the calling thread, returning the previous
+/// one.
+///
+/// The filesystem user ID is used for permission checks on file accesses in
+/// place of the effective user ID, without also changing which processes may
+/// send signals to this one.
+///
+/// `setfsuid(2)` always returns the previous filesystem user ID and never
+/// reports errors, so this calls it a second time with an invalid ID to check
+/// whether the change took effect, failing with `EPERM` if it didn't.
+///
+/// See also [setfsuid(2)](http://man7.org/linux/man-pages/man2/setfsuid.2.html)
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn setfsuid(uid: Uid) -> Result<Uid> {
+    let prev = unsafe { libc::setfsuid(uid.into()) };
+    // Invalid IDs are always rejected, so this only reads the current one.
+    let current = unsafe { libc::setfsuid(libc::uid_t::max_value()) };
+
+    if current as libc::uid_t == libc::uid_t::from(uid) {
+        Ok(Uid::from_raw(prev as libc::uid_t))
+    } else {
+        Err(Error::Sys(Errno::EPERM))
+    }
+}
+
+/// Set the filesystem group ID of the calling thread, returning the previous
+/// one.
+///
+/// Like [`setfsuid`](fn.setfsuid.html), this fails with `EPERM` if the change
+/// didn't take effect.
+///
+/// See also [setfsgid(2)](http://man7.org/linux/man-pages/man2/setfsgid.2.html)
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn setfsgid(gid: Gid) -> Result<Gid> {
+    let prev = unsafe { libc::setfsgid(gid.into()) };
+    let current = unsafe { libc::setfsgid(libc::gid_t::max_value()) };
+
+    if current as libc::gid_t == libc::gid_t::from(gid) {
+        Ok(Gid::from_raw(prev as libc::gid_t))
+    } else {
+        Err(Error::Sys(Errno::EPERM))
+    }
+}
+
 /// Get the list of supplementary group IDs of the calling process.
 ///
 /// [Further reading](http://pubs.opengroup.org/onlinepubs/009695399/functions/getgroups.html)
@@ -1460,6 +2271,69 @@ pub fn mkstemp<P: ?Sized + NixPath>(template: &P) -> Result<(RawFd, PathBuf)> {
     Ok((fd, PathBuf::from(pathname)))
 }
 
+/// Copy the NUL-terminated `template` for the `mk*temp*` functions, checking
+/// that the `suffix_len` bytes at its end are preceded by six `X`s.
+fn temp_template<P: ?Sized + NixPath>(template: &P, suffix_len: usize) -> Result<Vec<u8>> {
+    let path = try!(template.with_nix_path(|path| path.to_bytes_with_nul().to_owned()));
+    // The template without its suffix and the trailing nul
+    let end = match (path.len() - 1).checked_sub(suffix_len) {
+        Some(end) if end >= 6 => end,
+        _ => return Err(Error::invalid_argument()),
+    };
+    if path[end - 6..end].iter().any(|&b| b != b'X') {
+        return Err(Error::invalid_argument());
+    }
+
+    Ok(path)
+}
+
+/// Turn a template filled in by the C library back into a path.
+fn temp_path(mut path: Vec<u8>) -> PathBuf {
+    let last = path.pop(); // drop the trailing nul
+    debug_assert!(last == Some(b'\0'));
+    PathBuf::from(OsString::from_vec(path))
+}
+
+/// Creates a directory with a unique name, which only the owner can access.
+///
+/// * `template`: a path whose 6 rightmost characters must be X, e.g. `/tmp/tmpdir_XXXXXX`
+/// * returns: the name of the directory
+///
+/// The template is copied, so `template` itself isn't modified.  Fails with
+/// `EINVAL` without creating anything if it doesn't end with XXXXXX.
+///
+/// See also [mkdtemp(3)](http://pubs.opengroup.org/onlinepubs/9699919799/functions/mkdtemp.html)
+pub fn mkdtemp<P: ?Sized + NixPath>(template: &P) -> Result<PathBuf> {
+    let mut path = try!(temp_template(template, 0));
+    let res = unsafe { libc::mkdtemp(path.as_mut_ptr() as *mut c_char) };
+    if res.is_null() {
+        return Err(Error::Sys(Errno::last()));
+    }
+
+    Ok(temp_path(path))
+}
+
+/// Like [`mkstemp`](fn.mkstemp.html), but with a suffix of `suffix_len`
+/// bytes after the XXXXXX, e.g. `/tmp/tmpfile_XXXXXX.log`, and opening the
+/// file with the additional `flags`.
+///
+/// The file is created with mode 0600, and `O_CLOEXEC` is always added to
+/// `flags`.  The only other flags allowed are `O_APPEND` and `O_SYNC`.  Fails
+/// with `EINVAL` without creating anything if the XXXXXX doesn't come right
+/// before the suffix.
+#[cfg(any(target_os = "freebsd", target_os = "linux"))]
+pub fn mkostemps<P: ?Sized + NixPath>(template: &P, suffix_len: usize,
+                                      flags: OFlag) -> Result<(RawFd, PathBuf)> {
+    let mut path = try!(temp_template(template, suffix_len));
+    let flags = flags | OFlag::O_CLOEXEC;
+    let fd = unsafe {
+        libc::mkostemps(path.as_mut_ptr() as *mut c_char, suffix_len as c_int, flags.bits())
+    };
+    try!(Errno::result(fd));
+
+    Ok((fd, temp_path(path)))
+}
+
 /// Variable names for `pathconf`
 ///
 /// Nix uses the same naming convention for these variables as the
@@ -2076,6 +2950,118 @@ pub fn sysconf(var: SysconfVar) -> Result<Option<c_long>> {
     }
 }
 
+#[cfg(target_os = "linux")]
+mod acct {
+    use std::ptr;
+    use libc;
+    use {Error, Result, NixPath};
+    use errno::Errno;
+    use super::{Gid, Pid, Uid};
+
+    /// Enable process accounting to the file `filename`, or disable it if
+    /// `filename` is `None` (see
+    /// [acct(2)](http://man7.org/linux/man-pages/man2/acct.2.html)).
+    ///
+    /// The file must already exist.  A record is appended to it whenever a
+    /// process terminates; see [`AcctRecord`](struct.AcctRecord.html) for
+    /// reading them back.  Requires the `CAP_SYS_PACCT` capability.
+    pub fn acct<P: ?Sized + NixPath>(filename: Option<&P>) -> Result<()> {
+        let res = match filename {
+            Some(path) => try!(path.with_nix_path(|cstr| unsafe { libc::acct(cstr.as_ptr()) })),
+            None => unsafe { libc::acct(ptr::null()) },
+        };
+
+        Errno::result(res).map(drop)
+    }
+
+    /// A process accounting record in the `acct_v3` format written by Linux.
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub struct AcctRecord {
+        /// Accounting flags (`AFORK`, `ASU`, `ACORE`, `AXSIG`)
+        pub flags: u8,
+        /// Controlling terminal
+        pub tty: u16,
+        /// Termination status, as returned by `wait`
+        pub exit_code: u32,
+        /// Real user ID
+        pub uid: Uid,
+        /// Real group ID
+        pub gid: Gid,
+        /// Process ID
+        pub pid: Pid,
+        /// Parent process ID
+        pub ppid: Pid,
+        /// Creation time, in seconds since the Epoch
+        pub btime: u32,
+        /// Elapsed time, in clock ticks
+        pub etime: u32,
+        /// User CPU time, in clock ticks
+        pub utime: u64,
+        /// System CPU time, in clock ticks
+        pub stime: u64,
+        /// Average memory usage, in kilobytes
+        pub mem: u64,
+        /// Minor page faults
+        pub minflt: u64,
+        /// Major page faults
+        pub majflt: u64,
+        /// Command name
+        pub comm: String,
+    }
+
+    impl AcctRecord {
+        /// Size of a single record in bytes.
+        pub const SIZE: usize = 64;
+
+        /// Parse a record from the first `AcctRecord::SIZE` bytes of `buf`.
+        ///
+        /// Fails with `EINVAL` if `buf` is too short or doesn't hold a
+        /// version 3 record.
+        pub fn from_bytes(buf: &[u8]) -> Result<AcctRecord> {
+            if buf.len() < AcctRecord::SIZE || buf[1] != 3 {
+                return Err(Error::invalid_argument());
+            }
+
+            // Fields are stored in native byte order, but not necessarily
+            // aligned within `buf`.
+            let u16_at = |i: usize| unsafe { ptr::read_unaligned(buf[i..].as_ptr() as *const u16) };
+            let u32_at = |i: usize| unsafe { ptr::read_unaligned(buf[i..].as_ptr() as *const u32) };
+            // comp_t is a 13-bit mantissa with a 3-bit base 8 exponent
+            let comp_at = |i: usize| {
+                let v = u16_at(i);
+                u64::from(v & 0x1fff) << (3 * (v >> 13))
+            };
+            let comm = &buf[48..64];
+            let comm_len = comm.iter().position(|&b| b == 0).unwrap_or(comm.len());
+
+            Ok(AcctRecord {
+                flags: buf[0],
+                tty: u16_at(2),
+                exit_code: u32_at(4),
+                uid: Uid::from_raw(u32_at(8)),
+                gid: Gid::from_raw(u32_at(12)),
+                pid: Pid::from_raw(u32_at(16) as libc::pid_t),
+                ppid: Pid::from_raw(u32_at(20) as libc::pid_t),
+                btime: u32_at(24),
+                // ac_etime is stored as a 32-bit float
+                etime: f32::from_bits(u32_at(28)) as u32,
+                utime: comp_at(32),
+                stime: comp_at(34),
+                mem: comp_at(36),
+                minflt: comp_at(42),
+                majflt: comp_at(44),
+                comm: String::from_utf8_lossy(&comm[..comm_len]).into_owned(),
+            })
+        }
+
+        /// Parse all records in `buf`, such as the contents of an accounting
+        /// file.
+        pub fn parse_all(buf: &[u8]) -> Result<Vec<AcctRecord>> {
+            buf.chunks(AcctRecord::SIZE).map(AcctRecord::from_bytes).collect()
+        }
+    }
+}
+
 #[cfg(any(target_os = "android", target_os = "linux"))]
 mod pivot_root {
     use libc;
@@ -27,3 +27,47 @@ mod test_pthread;
 #[cfg(any(target_os = "android",
           target_os = "linux"))]
 mod test_ptrace;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_shm;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_sem;
+#[cfg(target_os = "linux")]
+mod test_futex;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_personality;
+#[cfg(target_os = "linux")]
+mod test_reboot;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_sysinfo;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_klog;
+#[cfg(target_os = "linux")]
+mod test_quota;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_key;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_mman;
+#[cfg(target_os = "linux")]
+mod test_time;
+#[cfg(target_os = "linux")]
+mod test_msg;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_xattr;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_ioprio;
+#[cfg(target_os = "linux")]
+mod test_landlock;
+#[cfg(any(target_os = "android",
+          target_os = "linux"))]
+mod test_block;
+#[cfg(target_os = "linux")]
+mod test_io_uring;
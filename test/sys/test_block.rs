@@ -0,0 +1,64 @@
+use std::env;
+use std::io::Write;
+use std::os::unix::io::AsRawFd;
+use nix::Error;
+use nix::errno::Errno;
+use nix::fcntl::{open, OFlag};
+use nix::sys::block::*;
+use nix::sys::stat::Mode;
+use nix::unistd::{close, syncfs, Uid};
+use tempfile::tempfile;
+
+#[test]
+fn test_block_ioctls_regular_file() {
+    let mut file = tempfile().unwrap();
+    file.write_all(&[0u8; 4096]).unwrap();
+    let fd = file.as_raw_fd();
+
+    assert_eq!(blk_getsize64(fd), Err(Error::Sys(Errno::ENOTTY)));
+    assert_eq!(blk_sszget(fd), Err(Error::Sys(Errno::ENOTTY)));
+    assert_eq!(blk_flsbuf(fd), Err(Error::Sys(Errno::ENOTTY)));
+    assert_eq!(blk_discard(fd, 0, 512), Err(Error::Sys(Errno::ENOTTY)));
+}
+
+// Set NIX_TEST_LOOP_DEVICE to a loop device whose contents may be discarded,
+// such as one set up with `losetup --find --show <image>`, and run as root.
+#[test]
+fn test_block_ioctls_loop_device() {
+    let device = match env::var_os("NIX_TEST_LOOP_DEVICE") {
+        Some(device) => device,
+        None => {
+            let stderr = ::std::io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "test_block_ioctls_loop_device requires NIX_TEST_LOOP_DEVICE. Skipping test.")
+                .unwrap();
+            return;
+        },
+    };
+    if !Uid::current().is_root() {
+        let stderr = ::std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_block_ioctls_loop_device requires root privileges. Skipping test.").unwrap();
+        return;
+    }
+
+    let fd = open(device.as_os_str(), OFlag::O_RDWR, Mode::empty()).unwrap();
+
+    let size = blk_getsize64(fd).unwrap();
+    let sector_size = blk_sszget(fd).unwrap();
+    assert!(sector_size >= 512 && sector_size.is_power_of_two());
+    assert!(size > 0 && size % sector_size as u64 == 0);
+
+    blk_flsbuf(fd).unwrap();
+    syncfs(fd).unwrap();
+    match blk_discard(fd, 0, sector_size as u64) {
+        Ok(()) => {
+            // Ranges must be sector aligned
+            assert_eq!(blk_discard(fd, 1, sector_size as u64), Err(Error::Sys(Errno::EINVAL)));
+        },
+        Err(Error::Sys(Errno::EOPNOTSUPP)) => (),
+        Err(e) => panic!("blk_discard failed: {}", e),
+    }
+
+    close(fd).unwrap();
+}
@@ -22,3 +22,125 @@ pub fn test_epoll_ctl() {
     epoll_ctl(efd, EpollOp::EpollCtlAdd, 1, &mut event).unwrap();
     epoll_ctl(efd, EpollOp::EpollCtlDel, 1, None).unwrap();
 }
+
+#[test]
+pub fn test_epoll_ctl_fd() {
+    use std::net::{TcpListener, TcpStream};
+    use nix::sys::epoll::{epoll_ctl_fd, epoll_wait};
+    use nix::unistd::close;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    let efd = epoll_create1(EpollCreateFlags::EPOLL_CLOEXEC).unwrap();
+    let mut event = EpollEvent::new(EpollFlags::EPOLLIN, 42);
+    epoll_ctl_fd(efd, EpollOp::EpollCtlAdd, &listener, &mut event).unwrap();
+
+    let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+    let mut events = [EpollEvent::empty(); 1];
+    let accepted = loop {
+        let n = epoll_wait(efd, &mut events, 1000).unwrap();
+        assert_eq!(n, 1, "timed out waiting for the connection");
+        assert_eq!(events[0].data(), 42);
+        match listener.accept() {
+            Ok((stream, _)) => break stream,
+            Err(ref e) if e.kind() == ::std::io::ErrorKind::WouldBlock => continue,
+            Err(e) => panic!("accept failed: {}", e),
+        }
+    };
+    drop(accepted);
+
+    epoll_ctl_fd(efd, EpollOp::EpollCtlDel, &listener, None).unwrap();
+    close(efd).unwrap();
+}
+
+#[test]
+pub fn test_epoll_struct_errors() {
+    use std::os::unix::io::AsRawFd;
+    use nix::fcntl::OFlag;
+    use nix::sys::epoll::Epoll;
+    use nix::unistd::pipe2_owned;
+
+    let epoll = Epoll::new(EpollCreateFlags::EPOLL_CLOEXEC).unwrap();
+    let (reader, _writer) = pipe2_owned(OFlag::O_CLOEXEC).unwrap();
+    let r = reader.as_raw_fd();
+    let event = EpollEvent::new(EpollFlags::EPOLLIN, 0);
+    assert_eq!(epoll.modify(r, event), Err(Error::Sys(Errno::ENOENT)));
+    epoll.add(r, event).unwrap();
+    assert_eq!(epoll.add(r, event), Err(Error::Sys(Errno::EEXIST)));
+    epoll.delete(r).unwrap();
+    assert_eq!(epoll.delete(r), Err(Error::Sys(Errno::ENOENT)));
+}
+
+#[test]
+pub fn test_epoll_oneshot_rearm() {
+    use std::os::unix::io::AsRawFd;
+    use nix::fcntl::OFlag;
+    use nix::sys::epoll::Epoll;
+    use nix::unistd::{pipe2_owned, read, write};
+
+    let epoll = Epoll::new(EpollCreateFlags::EPOLL_CLOEXEC).unwrap();
+    let (reader, writer) = pipe2_owned(OFlag::O_CLOEXEC).unwrap();
+    let (r, w) = (reader.as_raw_fd(), writer.as_raw_fd());
+    let event = EpollEvent::new(EpollFlags::EPOLLIN | EpollFlags::EPOLLONESHOT, 7);
+    epoll.add(r, event).unwrap();
+
+    let mut events = [EpollEvent::empty(); 1];
+    let mut buf = [0u8; 1];
+    write(w, b"a").unwrap();
+    assert_eq!(epoll.wait(&mut events, 1000), Ok(1));
+    assert_eq!(events[0].data(), 7);
+    read(r, &mut buf).unwrap();
+
+    // Disabled until rearmed, even though there is data again
+    write(w, b"b").unwrap();
+    assert_eq!(epoll.wait(&mut events, 100), Ok(0));
+    epoll.rearm(r, event).unwrap();
+    assert_eq!(epoll.wait(&mut events, 1000), Ok(1));
+    assert_eq!(events[0].data(), 7);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_epoll_exclusive() {
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::os::unix::io::AsRawFd;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use nix::sys::epoll::Epoll;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let event = EpollEvent::new(EpollFlags::EPOLLIN | EpollFlags::EPOLLEXCLUSIVE, 0);
+    let epolls: Vec<_> = (0..2).map(|_| Arc::new(Epoll::new(EpollCreateFlags::EPOLL_CLOEXEC).unwrap()))
+                               .collect();
+    for epoll in &epolls {
+        match epoll.add(listener.as_raw_fd(), event) {
+            Ok(()) => (),
+            Err(Error::Sys(Errno::EINVAL)) => {
+                let stderr = ::std::io::stderr();
+                let mut handle = stderr.lock();
+                writeln!(handle, "test_epoll_exclusive requires Linux 4.5 or newer. Skipping test.")
+                    .unwrap();
+                return;
+            },
+            Err(e) => panic!("adding the listener failed: {:?}", e),
+        }
+    }
+
+    // EPOLLEXCLUSIVE only limits how many blocked waiters are woken, so both
+    // instances have to be waiting when the connection arrives.
+    let waiters: Vec<_> = epolls.iter().map(|epoll| {
+        let epoll = epoll.clone();
+        thread::spawn(move || {
+            let mut events = [EpollEvent::empty(); 1];
+            epoll.wait(&mut events, 1000).unwrap()
+        })
+    }).collect();
+    thread::sleep(Duration::from_millis(200));
+    let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+
+    let woken: usize = waiters.into_iter().map(|waiter| waiter.join().unwrap()).sum();
+    assert_eq!(woken, 1);
+}
@@ -0,0 +1,181 @@
+use std::ffi::CString;
+use std::{mem, ptr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use libc::{_exit, c_long, c_void, pid_t};
+
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::futex::*;
+use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+use nix::sys::mman::{mmap, munmap, MapFlags, ProtFlags};
+use nix::sys::signal::{kill, Signal};
+use nix::sys::time::{TimeSpec, TimeValLike};
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{close, fork, ftruncate, getpid, gettid};
+use nix::unistd::ForkResult::*;
+
+#[test]
+fn test_futex_wait_wake() {
+    let word = Arc::new(AtomicUsize::new(0));
+    let waiter_word = word.clone();
+    let waiter = thread::spawn(move || {
+        while waiter_word.load(Ordering::SeqCst) == 0 {
+            match futex_wait(&waiter_word, 0, None, true) {
+                Ok(()) | Err(Error::Sys(Errno::EAGAIN)) | Err(Error::Sys(Errno::EINTR)) => (),
+                Err(e) => panic!("futex_wait failed: {:?}", e),
+            }
+        }
+    });
+
+    thread::sleep(Duration::from_millis(50));
+    word.store(1, Ordering::SeqCst);
+    futex_wake(&word, 1, true).unwrap();
+    waiter.join().unwrap();
+}
+
+#[test]
+fn test_futex_wait_mismatch() {
+    let word = AtomicUsize::new(1);
+    assert_eq!(futex_wait(&word, 0, None, true), Err(Error::Sys(Errno::EAGAIN)));
+    assert_eq!(futex_wait_bitset(&word, 0, None, 0b1, true), Err(Error::Sys(Errno::EAGAIN)));
+}
+
+#[test]
+fn test_futex_wait_timeout() {
+    let word = AtomicUsize::new(0);
+    let start = Instant::now();
+    let res = futex_wait(&word, 0, Some(TimeSpec::milliseconds(100)), true);
+    let elapsed = start.elapsed();
+
+    assert_eq!(res, Err(Error::Sys(Errno::ETIMEDOUT)));
+    assert!(elapsed >= Duration::from_millis(100));
+    assert!(elapsed < Duration::from_secs(2));
+}
+
+#[test]
+fn test_futex_no_waiters() {
+    let word = AtomicUsize::new(0);
+    let word2 = AtomicUsize::new(0);
+    assert_eq!(futex_wake(&word, 1, true), Ok(0));
+    assert_eq!(futex_wake_bitset(&word, 1, 0b1, true), Ok(0));
+    assert_eq!(futex_requeue(&word, 1, &word2, 1, true), Ok(0));
+    assert_eq!(futex_cmp_requeue(&word, 1, &word2, 1, 0, true), Ok(0));
+    assert_eq!(futex_cmp_requeue(&word, 1, &word2, 1, 1, true),
+               Err(Error::Sys(Errno::EAGAIN)));
+}
+
+#[test]
+fn test_futex_cross_process() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let name = CString::new("test_futex").unwrap();
+    let fd = memfd_create(&name, MemFdCreateFlag::empty()).unwrap();
+    let len = mem::size_of::<AtomicUsize>();
+    ftruncate(fd, len as _).unwrap();
+    let addr = unsafe {
+        mmap(ptr::null_mut(), len, ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+             MapFlags::MAP_SHARED, fd, 0)
+    }.unwrap();
+    let word = unsafe { &*(addr as *const AtomicUsize) };
+
+    // Safe: The child only calls `futex_wait` and `_exit`, which are async-signal-safe.
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            while word.load(Ordering::SeqCst) == 0 {
+                match futex_wait(word, 0, Some(TimeSpec::seconds(10)), false) {
+                    Err(Error::Sys(Errno::ETIMEDOUT)) => unsafe { _exit(1) },
+                    _ => (),
+                }
+            }
+            unsafe { _exit(0) }
+        },
+        Parent { child } => {
+            thread::sleep(Duration::from_millis(50));
+            word.store(1, Ordering::SeqCst);
+            futex_wake(word, 1, false).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+
+    unsafe { munmap(addr as *mut c_void, len) }.unwrap();
+    close(fd).unwrap();
+}
+
+// A lock registered on a robust list: the list entry followed by the futex
+// word.
+#[repr(C)]
+struct RobustLock {
+    entry: RobustList,
+    word: AtomicUsize,
+}
+
+fn empty_robust_list_head() -> RobustListHead {
+    RobustListHead {
+        list: RobustList { next: ptr::null_mut() },
+        futex_offset: (mem::size_of::<RobustList>() + FUTEX_WORD_OFFSET) as c_long,
+        list_op_pending: ptr::null_mut(),
+    }
+}
+
+#[test]
+fn test_robust_list_register() {
+    // Use a new thread to leave the robust list of the test thread alone.
+    thread::spawn(|| {
+        let (orig_head, orig_len) = get_robust_list(None).unwrap();
+
+        let mut head = empty_robust_list_head();
+        head.list.next = &mut head.list;
+        let len = mem::size_of::<RobustListHead>();
+        unsafe { set_robust_list(&mut head, len) }.unwrap();
+        assert_eq!(get_robust_list(None).unwrap(), (&mut head as *mut RobustListHead, len));
+        assert_eq!(get_robust_list(Some(gettid())).unwrap().0, &mut head as *mut RobustListHead);
+
+        // Restore the C library's list before `head` goes out of scope.
+        unsafe { set_robust_list(orig_head, orig_len) }.unwrap();
+    }).join().unwrap();
+}
+
+#[test]
+fn test_robust_list_owner_died() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let len = mem::size_of::<RobustLock>();
+    let addr = unsafe {
+        mmap(ptr::null_mut(), len, ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+             MapFlags::MAP_SHARED | MapFlags::MAP_ANONYMOUS, -1, 0)
+    }.unwrap();
+    let lock = addr as *mut RobustLock;
+
+    // Safe: The child only makes system calls.
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            let lock = unsafe { &mut *lock };
+            let mut head = empty_robust_list_head();
+            head.list.next = &mut lock.entry;
+            lock.entry.next = &mut head.list;
+
+            // Take the lock and die holding it.
+            let tid: pid_t = gettid().into();
+            lock.word.store(tid as usize, Ordering::SeqCst);
+            if unsafe { set_robust_list(&mut head, mem::size_of::<RobustListHead>()) }.is_err() {
+                unsafe { _exit(1) }
+            }
+            let _ = kill(getpid(), Signal::SIGKILL);
+            unsafe { _exit(2) }
+        },
+        Parent { child } => {
+            assert_eq!(waitpid(child, None),
+                       Ok(WaitStatus::Signaled(child, Signal::SIGKILL, false)));
+            let word = unsafe { (*lock).word.load(Ordering::SeqCst) };
+            assert_eq!(word, FUTEX_OWNER_DIED as usize);
+        },
+    }
+
+    unsafe { munmap(addr, len) }.unwrap();
+}
@@ -0,0 +1,98 @@
+use std::io::Write;
+use std::{mem, ptr};
+use std::sync::atomic::{fence, Ordering};
+use libc::c_void;
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::eventfd::{eventfd, EfdFlags};
+use nix::sys::io_uring::*;
+use nix::sys::mman::{mmap, munmap, MapFlags, ProtFlags};
+use nix::unistd::{close, read};
+
+unsafe fn map_ring(fd: i32, len: usize, offset: ::libc::off_t) -> *mut u8 {
+    mmap(ptr::null_mut(), len, ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+         MapFlags::MAP_SHARED | MapFlags::MAP_POPULATE, fd, offset).unwrap() as *mut u8
+}
+
+// The ring indices are shared with the kernel.  `AtomicU32` needs a more
+// recent Rust, so they are accessed with volatile operations and fences.
+unsafe fn load_acquire(ring: *mut u8, offset: u32) -> u32 {
+    let val = ptr::read_volatile(ring.offset(offset as isize) as *const u32);
+    fence(Ordering::Acquire);
+    val
+}
+
+unsafe fn store_release(ring: *mut u8, offset: u32, val: u32) {
+    fence(Ordering::Release);
+    ptr::write_volatile(ring.offset(offset as isize) as *mut u32, val);
+}
+
+#[test]
+fn test_io_uring_nop() {
+    let mut params = IoUringParams::new();
+    let fd = match setup(4, &mut params) {
+        Ok(fd) => fd,
+        Err(Error::Sys(Errno::ENOSYS)) | Err(Error::Sys(Errno::EPERM)) => {
+            let stderr = ::std::io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "test_io_uring_nop requires io_uring. Skipping test.").unwrap();
+            return;
+        },
+        Err(e) => panic!("setup failed: {}", e),
+    };
+    assert_eq!(params.sq_entries(), 4);
+    assert!(params.cq_entries() >= 4);
+
+    let mut probe = Probe::new();
+    match register(fd, RegisterOp::Probe(&mut probe)) {
+        // IORING_OP_NOP
+        Ok(()) => assert!(probe.is_supported(0)),
+        // Probing needs Linux 5.6
+        Err(Error::Sys(Errno::EINVAL)) => (),
+        Err(e) => panic!("probe failed: {}", e),
+    }
+
+    let efd = eventfd(0, EfdFlags::EFD_CLOEXEC).unwrap();
+    register(fd, RegisterOp::Eventfd(efd)).unwrap();
+
+    unsafe {
+        let sq_ring = map_ring(fd, params.sq_ring_size(), IORING_OFF_SQ_RING);
+        let cq_ring = map_ring(fd, params.cq_ring_size(), IORING_OFF_CQ_RING);
+        let sqes = map_ring(fd, params.sqes_size(), IORING_OFF_SQES);
+        let sq_off = *params.sq_off();
+        let cq_off = *params.cq_off();
+        assert_eq!(load_acquire(sq_ring, sq_off.ring_entries()), 4);
+
+        // Submit a NOP with user data 42 in the first entry.
+        ptr::write_bytes(sqes, 0, SQE_SIZE);
+        *(sqes.offset(32) as *mut u64) = 42;
+        let sq_mask = load_acquire(sq_ring, sq_off.ring_mask());
+        let tail = load_acquire(sq_ring, sq_off.tail());
+        *(sq_ring.offset((sq_off.array() + (tail & sq_mask) * 4) as isize) as *mut u32) = 0;
+        store_release(sq_ring, sq_off.tail(), tail.wrapping_add(1));
+
+        assert_eq!(enter(fd, 1, 1, EnterFlags::IORING_ENTER_GETEVENTS, None), Ok(1));
+
+        let head = load_acquire(cq_ring, cq_off.head());
+        assert_eq!(load_acquire(cq_ring, cq_off.tail()), head.wrapping_add(1));
+        let cq_mask = load_acquire(cq_ring, cq_off.ring_mask());
+        let cqe = cq_ring.offset((cq_off.cqes() + (head & cq_mask) * CQE_SIZE as u32) as isize);
+        assert_eq!(*(cqe as *const u64), 42);
+        assert_eq!(*(cqe.offset(8) as *const i32), 0);
+        store_release(cq_ring, cq_off.head(), head.wrapping_add(1));
+
+        munmap(sqes as *mut c_void, params.sqes_size()).unwrap();
+        munmap(cq_ring as *mut c_void, params.cq_ring_size()).unwrap();
+        munmap(sq_ring as *mut c_void, params.sq_ring_size()).unwrap();
+    }
+
+    // The completion signalled the eventfd.
+    let mut buf = [0u8; 8];
+    assert_eq!(read(efd, &mut buf), Ok(8));
+    let count: u64 = unsafe { mem::transmute(buf) };
+    assert_eq!(count, 1);
+
+    register(fd, RegisterOp::UnregisterEventfd).unwrap();
+    close(efd).unwrap();
+    close(fd).unwrap();
+}
@@ -63,6 +63,18 @@ mod linux {
 
     }
 
+    #[test]
+    fn test_op_read_buf() {
+        // The size of a buffer ioctl is computed from the slice, so EVIOCGNAME(256)
+        // must be encoded like a plain read of 256 bytes.
+        let len = [0u8; 256].len() * ::std::mem::size_of::<u8>();
+        if cfg!(any(target_arch = "mips", target_arch = "mips64", target_arch="powerpc", target_arch="powerpc64")){
+            assert_eq!(request_code_read!(b'E', 0x06, len), 0x4100_4506);
+        } else {
+            assert_eq!(request_code_read!(b'E', 0x06, len), 0x8100_4506);
+        }
+    }
+
     #[test]
     fn test_op_read() {
         if cfg!(any(target_arch = "mips", target_arch = "mips64", target_arch="powerpc", target_arch="powerpc64")){
@@ -159,8 +171,13 @@ mod linux_ioctls {
     use std::mem;
     use std::os::unix::io::AsRawFd;
 
+    use std::fs::File;
+    use std::io::{self, Write};
+
     use tempfile::tempfile;
-    use libc::{TCGETS, TCSBRK, TCSETS, TIOCNXCL, termios};
+    use libc::{c_int, FIONREAD, TCGETS, TCSBRK, TCSETS, TIOCNXCL, termios};
+
+    use nix::unistd::{close, pipe, write};
 
     use nix::Error::Sys;
     use nix::errno::Errno::{ENOTTY, ENOSYS};
@@ -260,7 +277,49 @@ mod linux_ioctls {
         assert!(res == Err(Sys(ENOTTY)) || res == Err(Sys(ENOSYS)));
     }
 
-    // FIXME: Find a suitable example for `ioctl_read_buf`.
+    ioctl_read_bad!(fionread, FIONREAD, c_int);
+    #[test]
+    fn test_ioctl_read_bad_fionread() {
+        let (r, w) = pipe().unwrap();
+        let mut avail: c_int = 0;
+        unsafe { fionread(r, &mut avail) }.unwrap();
+        assert_eq!(avail, 0);
+
+        write(w, b"abcdef").unwrap();
+        unsafe { fionread(r, &mut avail) }.unwrap();
+        assert_eq!(avail, 6);
+
+        close(r).unwrap();
+        close(w).unwrap();
+    }
+
+    // From linux/input.h
+    ioctl_read_buf!(eviocgname, b'E', 0x06, u8);
+    #[test]
+    fn test_ioctl_read_buf() {
+        let file = tempfile().unwrap();
+        let mut name = [0u8; 256];
+        let res = unsafe { eviocgname(file.as_raw_fd(), &mut name[..]) };
+        assert!(res == Err(Sys(ENOTTY)) || res == Err(Sys(ENOSYS)));
+    }
+
+    #[test]
+    fn test_ioctl_read_buf_input_device() {
+        let dev = match File::open("/dev/input/event0") {
+            Ok(dev) => dev,
+            Err(_) => {
+                let stderr = io::stderr();
+                let mut handle = stderr.lock();
+                writeln!(handle, "test_ioctl_read_buf_input_device requires /dev/input/event0. Skipping test.").unwrap();
+                return;
+            }
+        };
+        let mut name = [0xffu8; 256];
+        // The kernel returns the number of bytes copied, including the NUL
+        let len = unsafe { eviocgname(dev.as_raw_fd(), &mut name[..]) }.unwrap();
+        assert!(len > 0 && (len as usize) <= name.len());
+        assert_eq!(name[len as usize - 1], 0);
+    }
 
     #[repr(C)]
     pub struct spi_ioc_transfer {
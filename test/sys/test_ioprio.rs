@@ -0,0 +1,36 @@
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::ioprio::{self, IoPriority, Target};
+use nix::unistd::{Pid, Uid};
+
+#[test]
+fn test_ioprio_idle() {
+    // Only changes the calling thread
+    let me = Target::Process(Pid::from_raw(0));
+    let prev = ioprio::get(me).unwrap();
+
+    ioprio::set(me, IoPriority::Idle).unwrap();
+    assert_eq!(ioprio::get(me).unwrap(), IoPriority::Idle);
+
+    ioprio::set(me, IoPriority::BestEffort(4)).unwrap();
+    assert_eq!(ioprio::get(me).unwrap(), IoPriority::BestEffort(4));
+
+    ioprio::set(me, prev).unwrap();
+}
+
+#[test]
+fn test_ioprio_invalid_level() {
+    let me = Target::Process(Pid::from_raw(0));
+    assert_eq!(ioprio::set(me, IoPriority::BestEffort(8)), Err(Error::Sys(Errno::EINVAL)));
+    assert_eq!(ioprio::set(me, IoPriority::RealTime(255)), Err(Error::Sys(Errno::EINVAL)));
+}
+
+#[test]
+fn test_ioprio_realtime_unprivileged() {
+    if Uid::current().is_root() {
+        return;
+    }
+
+    let me = Target::Process(Pid::from_raw(0));
+    assert_eq!(ioprio::set(me, IoPriority::RealTime(0)), Err(Error::Sys(Errno::EPERM)));
+}
@@ -0,0 +1,70 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::key::*;
+
+/// Add a key to the process keyring, or return `None` if the keyring is not
+/// available, as under some container seccomp profiles.
+fn add_test_key(test: &str, description: &str, payload: &[u8]) -> Option<KeySerial> {
+    match add_key("user", description, payload, KeyringId::Process) {
+        Ok(key) => Some(key),
+        Err(Error::Sys(Errno::ENOSYS)) | Err(Error::Sys(Errno::EPERM)) => {
+            let stderr = io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "{} requires the kernel keyring. Skipping test.", test).unwrap();
+            None
+        },
+        Err(e) => panic!("add_key failed: {:?}", e),
+    }
+}
+
+#[test]
+fn test_key_add_read() {
+    let payload = b"\x00secret\xff payload";
+    let key = match add_test_key("test_key_add_read", "nix:test_key_add_read", payload) {
+        Some(key) => key,
+        None => return,
+    };
+
+    assert_eq!(read(key).unwrap(), &payload[..]);
+    assert_eq!(search(KeyringId::Process, "user", "nix:test_key_add_read", None), Ok(key));
+    assert_eq!(search(KeyringId::Process, "user", "nix:no_such_key", None),
+               Err(Error::Sys(Errno::ENOKEY)));
+
+    revoke(key).unwrap();
+    assert_eq!(read(key), Err(Error::Sys(Errno::EKEYREVOKED)));
+}
+
+#[test]
+fn test_key_link_unlink() {
+    let key = match add_test_key("test_key_link_unlink", "nix:test_key_link_unlink", b"x") {
+        Some(key) => key,
+        None => return,
+    };
+    let keyring = get_keyring_id(KeyringId::Process, false).unwrap();
+
+    unlink(key, KeyringId::Process).unwrap();
+    assert_eq!(search(keyring.into(), "user", "nix:test_key_link_unlink", None),
+               Err(Error::Sys(Errno::ENOKEY)));
+    // The key can only be linked back while it's still alive, which isn't
+    // guaranteed once its last link is gone.
+    if link(key, KeyringId::Process).is_ok() {
+        assert_eq!(search(keyring.into(), "user", "nix:test_key_link_unlink", None), Ok(key));
+        unlink(key, KeyringId::Process).unwrap();
+    }
+}
+
+#[test]
+fn test_key_timeout() {
+    let key = match add_test_key("test_key_timeout", "nix:test_key_timeout", b"expiring") {
+        Some(key) => key,
+        None => return,
+    };
+
+    set_timeout(key, 1).unwrap();
+    thread::sleep(Duration::from_millis(1500));
+    assert_eq!(read(key), Err(Error::Sys(Errno::EKEYEXPIRED)));
+}
@@ -0,0 +1,38 @@
+use std::io::{self, Write};
+
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::klog::{self, KlogAction, klogctl};
+use nix::unistd::getuid;
+
+#[test]
+fn test_klog_read_all() {
+    let mut buf = Vec::new();
+    match klog::read_all(&mut buf) {
+        Ok(len) => {
+            assert_eq!(len, buf.len());
+            let size = klogctl(KlogAction::SizeBuffer, None).unwrap();
+            assert!(len <= size);
+            // Messages are newline-terminated text
+            let text = String::from_utf8_lossy(&buf);
+            assert!(text.is_empty() || text.ends_with('\n'));
+        },
+        // Unprivileged readers with kernel.dmesg_restrict set
+        Err(e) => assert_eq!(e, Error::Sys(Errno::EPERM)),
+    }
+}
+
+#[test]
+fn test_klog_clear_unprivileged() {
+    // Clearing the buffer for real would destroy the host's kernel log
+    if getuid().is_root() {
+        let stderr = io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_klog_clear_unprivileged requires an unprivileged user. Skipping test.").unwrap();
+        return;
+    }
+    assert_eq!(klog::clear(), Err(Error::Sys(Errno::EPERM)));
+    assert_eq!(klog::console_level(7), Err(Error::Sys(Errno::EPERM)));
+    assert_eq!(klog::console_on(), Err(Error::Sys(Errno::EPERM)));
+    assert_eq!(klog::console_off(), Err(Error::Sys(Errno::EPERM)));
+}
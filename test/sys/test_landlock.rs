@@ -0,0 +1,75 @@
+use std::fs::File;
+use std::io::Write;
+use libc::_exit;
+use nix::Error;
+use nix::errno::Errno;
+use nix::fcntl::{open, OFlag};
+use nix::sys::landlock::{self, AccessFs, RulesetAttr};
+use nix::sys::stat::Mode;
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{close, fork, read, ForkResult};
+use tempdir::TempDir;
+
+#[test]
+fn test_landlock_read_only_beneath() {
+    match landlock::abi_version() {
+        Ok(_) => (),
+        Err(Error::Sys(Errno::ENOSYS)) | Err(Error::Sys(Errno::EOPNOTSUPP)) => {
+            let stderr = ::std::io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "test_landlock_read_only_beneath requires Landlock. Skipping test.").unwrap();
+            return;
+        },
+        Err(e) => panic!("abi_version failed: {}", e),
+    }
+
+    let allowed = TempDir::new("nix-test_landlock_allowed").unwrap();
+    let denied = TempDir::new("nix-test_landlock_denied").unwrap();
+    let inside = allowed.path().join("inside");
+    let outside = denied.path().join("outside");
+    File::create(&inside).unwrap().write_all(b"inside").unwrap();
+    File::create(&outside).unwrap().write_all(b"outside").unwrap();
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // The restriction is irreversible, so apply it in a child
+    match fork().expect("Error: Fork Failed") {
+        ForkResult::Child => {
+            let handled = AccessFs::LANDLOCK_ACCESS_FS_READ_FILE | AccessFs::LANDLOCK_ACCESS_FS_READ_DIR |
+                          AccessFs::LANDLOCK_ACCESS_FS_WRITE_FILE;
+            let ruleset = landlock::create_ruleset(&RulesetAttr::new(handled)).unwrap();
+            let parent = open(allowed.path(), OFlag::O_PATH | OFlag::O_CLOEXEC, Mode::empty()).unwrap();
+            landlock::add_path_beneath_rule(ruleset, parent,
+                                            AccessFs::LANDLOCK_ACCESS_FS_READ_FILE |
+                                            AccessFs::LANDLOCK_ACCESS_FS_READ_DIR).unwrap();
+            landlock::restrict_self(ruleset).unwrap();
+            close(ruleset).unwrap();
+
+            let fd = match open(&inside, OFlag::O_RDONLY, Mode::empty()) {
+                Ok(fd) => fd,
+                Err(_) => unsafe { _exit(1) },
+            };
+            let mut buf = [0u8; 6];
+            if read(fd, &mut buf) != Ok(6) || &buf != b"inside" {
+                unsafe { _exit(2) };
+            }
+            if open(&inside, OFlag::O_WRONLY, Mode::empty()) != Err(Error::Sys(Errno::EACCES)) {
+                unsafe { _exit(3) };
+            }
+            if open(&outside, OFlag::O_RDONLY, Mode::empty()) != Err(Error::Sys(Errno::EACCES)) {
+                unsafe { _exit(4) };
+            }
+            unsafe { _exit(0) };
+        },
+        ForkResult::Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+}
+
+#[test]
+fn test_landlock_ruleset_attr() {
+    let handled = AccessFs::LANDLOCK_ACCESS_FS_EXECUTE | AccessFs::LANDLOCK_ACCESS_FS_MAKE_DIR;
+    assert_eq!(RulesetAttr::new(handled).handled_access_fs(), handled);
+}
@@ -0,0 +1,53 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+use std::slice;
+use libc::{c_void, off_t};
+use nix::sys::mman::{mincore, mmap, msync, munmap, MapFlags, MsyncFlags, ProtFlags};
+use nix::unistd::{ftruncate, sysconf, SysconfVar};
+use tempfile::tempfile;
+
+fn page_size() -> usize {
+    sysconf(SysconfVar::PAGE_SIZE).unwrap().unwrap() as usize
+}
+
+#[test]
+fn test_msync() {
+    let len = page_size();
+    let mut file = tempfile().unwrap();
+    ftruncate(file.as_raw_fd(), len as off_t).unwrap();
+
+    let addr = unsafe {
+        mmap(ptr::null_mut(), len, ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+             MapFlags::MAP_SHARED, file.as_raw_fd(), 0)
+    }.unwrap();
+    let data = unsafe { slice::from_raw_parts_mut(addr as *mut u8, len) };
+    data[..5].copy_from_slice(b"hello");
+    unsafe { msync(addr, len, MsyncFlags::MS_SYNC) }.unwrap();
+    unsafe { munmap(addr, len) }.unwrap();
+
+    let mut buf = [0u8; 5];
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+fn test_mincore() {
+    let len = 4 * page_size();
+    let addr = unsafe {
+        mmap(ptr::null_mut(), len, ProtFlags::PROT_READ | ProtFlags::PROT_WRITE,
+             MapFlags::MAP_PRIVATE | MapFlags::MAP_ANONYMOUS, -1, 0)
+    }.unwrap();
+    unsafe { *(addr as *mut u8) = 1 };
+
+    let resident = mincore(addr, len).unwrap();
+    assert_eq!(resident.len(), 4);
+    assert!(resident[0]);
+
+    // An unaligned range is extended to the pages it touches.
+    let unaligned = unsafe { (addr as *const u8).offset(1) } as *const c_void;
+    assert_eq!(mincore(unaligned, page_size()).unwrap().len(), 2);
+
+    unsafe { munmap(addr, len) }.unwrap();
+}
@@ -0,0 +1,37 @@
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::msg::*;
+use nix::sys::stat::Mode;
+use nix::unistd::getuid;
+
+#[test]
+fn test_msg_queue() {
+    let msqid = msgget(IPC_PRIVATE, MsggetFlag::IPC_CREAT,
+                       Mode::S_IRUSR | Mode::S_IWUSR).unwrap();
+
+    msgsnd(msqid, 1, b"first", MsgFlag::empty()).unwrap();
+    msgsnd(msqid, 2, b"second", MsgFlag::empty()).unwrap();
+
+    let ds = msg_stat(msqid).unwrap();
+    assert_eq!(ds.message_count(), 2);
+    assert_eq!(ds.uid(), getuid());
+    assert_eq!(ds.mode(), Mode::S_IRUSR | Mode::S_IWUSR);
+
+    // Receive the second message first by asking for its type
+    let mut buf = [0u8; 16];
+    assert_eq!(msgrcv(msqid, &mut buf, 2, MsgFlag::empty()).unwrap(), (2, 6));
+    assert_eq!(&buf[..6], b"second");
+
+    // A message that doesn't fit is only received when truncation is allowed
+    let mut small = [0u8; 3];
+    assert_eq!(msgrcv(msqid, &mut small, 0, MsgFlag::IPC_NOWAIT),
+               Err(Error::Sys(Errno::E2BIG)));
+    assert_eq!(msgrcv(msqid, &mut small, 0, MsgFlag::MSG_NOERROR).unwrap(), (1, 3));
+    assert_eq!(&small, b"fir");
+
+    assert_eq!(msgrcv(msqid, &mut buf, 0, MsgFlag::IPC_NOWAIT),
+               Err(Error::Sys(Errno::ENOMSG)));
+
+    msg_remove(msqid).unwrap();
+    assert_eq!(msg_stat(msqid).err(), Some(Error::Sys(Errno::EINVAL)));
+}
@@ -0,0 +1,74 @@
+use std::io::Write;
+use std::process::Command;
+
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::personality::{self, Persona};
+
+fn stack_mapping() -> String {
+    let output = Command::new("grep").arg("\\[stack\\]").arg("/proc/self/maps")
+        .output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_personality_get() {
+    personality::get().unwrap();
+}
+
+#[test]
+fn test_personality_linux32() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let orig = personality::get().unwrap();
+    let linux32 = (orig - Persona::PER_MASK) | Persona::PER_LINUX32;
+    match personality::set(linux32) {
+        Ok(prev) => assert_eq!(prev, orig),
+        Err(Error::Sys(Errno::EPERM)) => {
+            let stderr = ::std::io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "personality(PER_LINUX32) not permitted. Skipping test.")
+                .unwrap();
+            return;
+        },
+        Err(e) => panic!("personality failed: {:?}", e),
+    }
+    let current = personality::get().unwrap();
+    assert_eq!(current & Persona::PER_MASK, Persona::PER_LINUX32);
+
+    // The whole persona, including the execution domain, must round-trip
+    assert_eq!(personality::set(orig), Ok(current));
+    assert_eq!(personality::get(), Ok(orig));
+}
+
+#[test]
+fn test_personality_no_randomize() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let orig = personality::get().unwrap();
+    let prev = match personality::set(orig | Persona::ADDR_NO_RANDOMIZE) {
+        Ok(prev) => prev,
+        // Container seccomp profiles commonly only allow querying
+        Err(Error::Sys(Errno::EPERM)) => {
+            let stderr = ::std::io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "personality(ADDR_NO_RANDOMIZE) not permitted. Skipping test.")
+                .unwrap();
+            return;
+        },
+        Err(e) => panic!("personality failed: {:?}", e),
+    };
+    assert_eq!(prev, orig);
+    assert!(personality::get().unwrap().contains(Persona::ADDR_NO_RANDOMIZE));
+
+    // Without randomization every exec gets its stack at the same address
+    let first = stack_mapping();
+    let second = stack_mapping();
+
+    assert_eq!(personality::set(orig), Ok(orig | Persona::ADDR_NO_RANDOMIZE));
+    assert_eq!(personality::get(), Ok(orig));
+    assert_eq!(first, second);
+}
@@ -86,3 +86,77 @@ fn test_ptrace_cont() {
         },
     }
 }
+
+#[test]
+fn test_ptrace_get_syscall_info() {
+    use nix::sys::ptrace::SyscallInfo;
+    use nix::sys::signal::{raise, Signal};
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{close, fork, pipe, write};
+    use nix::unistd::ForkResult::*;
+    use libc::{self, _exit};
+
+    // FIXME: qemu-user doesn't implement ptrace on all architectures
+    let err = ptrace::attach(getpid()).unwrap_err();
+    if err == Error::Sys(Errno::ENOSYS) {
+        return;
+    }
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let (r, w) = pipe().unwrap();
+
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            ptrace::traceme().unwrap();
+            raise(Signal::SIGSTOP).unwrap();
+            write(w, b"hello").unwrap();
+            unsafe { _exit(0) }
+        },
+        Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Stopped(child, Signal::SIGSTOP)));
+            ptrace::setoptions(child, Options::PTRACE_O_TRACESYSGOOD |
+                                      Options::PTRACE_O_EXITKILL).unwrap();
+
+            let mut entered = false;
+            let mut supported = true;
+            loop {
+                ptrace::syscall(child).unwrap();
+                match waitpid(child, None).unwrap() {
+                    WaitStatus::PtraceSyscall(_) => (),
+                    WaitStatus::Exited(_, 0) => break,
+                    status => panic!("unexpected wait status {:?}", status),
+                }
+
+                match ptrace::get_syscall_info(child) {
+                    Err(Error::Sys(Errno::EIO)) => {
+                        // PTRACE_GET_SYSCALL_INFO needs Linux 5.3
+                        supported = false;
+                        ptrace::cont(child, None).unwrap();
+                        assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+                        break;
+                    },
+                    Ok(SyscallInfo::Entry { nr, args }) => {
+                        entered = nr == libc::SYS_write as u64 && args[0] == w as u64 &&
+                                  args[2] == 5;
+                    },
+                    Ok(SyscallInfo::Exit { rval, is_error }) => {
+                        if entered {
+                            assert_eq!((rval, is_error), (5, false));
+                            ptrace::cont(child, None).unwrap();
+                            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+                            break;
+                        }
+                    },
+                    res => panic!("unexpected syscall info {:?}", res),
+                }
+            }
+
+            assert!(entered || !supported, "never saw the write syscall");
+        },
+    }
+
+    close(r).unwrap();
+    close(w).unwrap();
+}
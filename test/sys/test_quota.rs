@@ -0,0 +1,30 @@
+use std::env;
+use std::io::{self, Write};
+
+use nix::sys::quota::{quotactl_get, QuotaType};
+use nix::unistd::getuid;
+
+/// Quota tests need a block device with quotas enabled, given by the
+/// `NIX_TEST_QUOTA_DEVICE` environment variable.
+fn quota_device(test: &str) -> Option<String> {
+    let dev = env::var("NIX_TEST_QUOTA_DEVICE").ok();
+    if dev.is_none() {
+        let stderr = io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "{} requires NIX_TEST_QUOTA_DEVICE. Skipping test.", test).unwrap();
+    }
+    dev
+}
+
+#[test]
+fn test_quotactl_get() {
+    let dev = match quota_device("test_quotactl_get") {
+        Some(dev) => dev,
+        None => return,
+    };
+
+    let uid: u32 = getuid().into();
+    let dqblk = quotactl_get(QuotaType::USRQUOTA, dev.as_str(), uid as i32).unwrap();
+    assert!(dqblk.occupied_space().is_some());
+    assert!(dqblk.allocated_inodes().is_some());
+}
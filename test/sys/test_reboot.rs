@@ -0,0 +1,34 @@
+use std::io::Write;
+
+use libc;
+
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::reboot::{set_cad_enabled, RebootMode};
+use nix::unistd::getuid;
+
+#[test]
+fn test_reboot_mode_magic() {
+    assert_eq!(RebootMode::RB_AUTOBOOT as u32, 0x0123_4567);
+    assert_eq!(RebootMode::RB_HALT_SYSTEM as u32, 0xcdef_0123);
+    assert_eq!(RebootMode::RB_POWER_OFF as u32, 0x4321_fedc);
+    assert_eq!(RebootMode::RB_SW_SUSPEND as u32, 0xd000_fce2);
+    assert_eq!(RebootMode::RB_KEXEC as u32, 0x4558_4543);
+    assert_eq!(libc::RB_ENABLE_CAD as u32, 0x89ab_cdef);
+    assert_eq!(libc::RB_DISABLE_CAD as u32, 0);
+}
+
+#[test]
+fn test_set_cad_enabled_unprivileged() {
+    // Never exercise reboot(2) with privileges: it would act on the machine
+    // running the tests.
+    if getuid().is_root() {
+        let stderr = ::std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_set_cad_enabled_unprivileged requires an unprivileged user. Skipping test.").unwrap();
+        return;
+    }
+
+    assert_eq!(set_cad_enabled(true), Err(Error::Sys(Errno::EPERM)));
+    assert_eq!(set_cad_enabled(false), Err(Error::Sys(Errno::EPERM)));
+}
@@ -1,7 +1,7 @@
 use nix::sys::select::*;
 use nix::unistd::{pipe, write};
 use nix::sys::signal::SigSet;
-use nix::sys::time::{TimeSpec, TimeValLike};
+use nix::sys::time::{TimeSpec, TimeVal, TimeValLike};
 use std::os::unix::io::RawFd;
 
 #[test]
@@ -53,3 +53,25 @@ pub fn test_pselect_nfds2() {
     assert!(fd_set.contains(r1));
     assert!(!fd_set.contains(r2));
 }
+
+#[test]
+pub fn test_select_insert_all() {
+    use std::io::Write;
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    let (mut a, b) = UnixStream::pair().unwrap();
+    let (_c, d) = UnixStream::pair().unwrap();
+    a.write_all(b"hi!").unwrap();
+    let streams = [b, d];
+
+    let mut fd_set = FdSet::new();
+    fd_set.insert_all(&streams);
+    assert!(fd_set.contains(streams[0].as_raw_fd()));
+    assert!(fd_set.contains(streams[1].as_raw_fd()));
+
+    let mut timeout = TimeVal::seconds(10);
+    assert_eq!(1, select(None, &mut fd_set, None, None, &mut timeout).unwrap());
+    assert!(fd_set.contains(streams[0].as_raw_fd()));
+    assert!(!fd_set.contains(streams[1].as_raw_fd()));
+}
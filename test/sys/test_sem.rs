@@ -0,0 +1,88 @@
+use libc::_exit;
+
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::sem::*;
+use nix::sys::stat::Mode;
+use nix::sys::time::{TimeSpec, TimeValLike};
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::fork;
+use nix::unistd::ForkResult::*;
+
+fn new_sem(nsems: i32) -> i32 {
+    semget(IPC_PRIVATE, nsems, SemgetFlag::IPC_CREAT, Mode::S_IRUSR | Mode::S_IWUSR)
+        .unwrap()
+}
+
+#[test]
+fn test_sem_p_v() {
+    let semid = new_sem(1);
+    assert_eq!(semctl(semid, GETVAL(0)), Ok(0));
+
+    // V
+    semop(semid, &[Sembuf::new(0, 1, SemFlag::empty())]).unwrap();
+    assert_eq!(semctl(semid, GETVAL(0)), Ok(1));
+
+    // P
+    semop(semid, &[Sembuf::new(0, -1, SemFlag::empty())]).unwrap();
+    assert_eq!(semctl(semid, GETVAL(0)), Ok(0));
+
+    // A nonblocking P on a zero semaphore must not block
+    let res = semop(semid, &[Sembuf::new(0, -1, SemFlag::IPC_NOWAIT)]);
+    assert_eq!(res, Err(Error::Sys(Errno::EAGAIN)));
+
+    // Nor must a timed one block forever
+    let res = semtimedop(semid, &[Sembuf::new(0, -1, SemFlag::empty())],
+                         &TimeSpec::milliseconds(10));
+    assert_eq!(res, Err(Error::Sys(Errno::EAGAIN)));
+
+    semctl(semid, IPC_RMID).unwrap();
+    assert_eq!(semctl(semid, GETVAL(0)), Err(Error::Sys(Errno::EINVAL)));
+}
+
+#[test]
+fn test_sem_setall_getall() {
+    let semid = new_sem(3);
+
+    semctl(semid, SETVAL(1, 7)).unwrap();
+    let mut vals = [0; 3];
+    semctl(semid, GETALL(&mut vals)).unwrap();
+    assert_eq!(vals, [0, 7, 0]);
+
+    semctl(semid, SETALL(&[1, 2, 3])).unwrap();
+    semctl(semid, GETALL(&mut vals)).unwrap();
+    assert_eq!(vals, [1, 2, 3]);
+
+    // Buffers that don't cover the whole set are rejected
+    let mut short = [0; 2];
+    assert_eq!(semctl(semid, GETALL(&mut short)), Err(Error::Sys(Errno::EINVAL)));
+    assert_eq!(semctl(semid, SETALL(&[1, 2, 3, 4])), Err(Error::Sys(Errno::EINVAL)));
+
+    semctl(semid, IPC_RMID).unwrap();
+}
+
+#[test]
+fn test_sem_undo() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let semid = new_sem(1);
+    semctl(semid, SETVAL(0, 1)).unwrap();
+
+    // Safe: The child only calls `semop` and `_exit`, which are async-signal-safe.
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            let op = Sembuf::new(0, -1, SemFlag::SEM_UNDO | SemFlag::IPC_NOWAIT);
+            let code = if semop(semid, &[op]).is_ok() { 0 } else { 1 };
+            unsafe { _exit(code) }
+        },
+        Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+            // The child exited while holding the semaphore; the kernel must
+            // have given it back.
+            assert_eq!(semctl(semid, GETVAL(0)), Ok(1));
+        },
+    }
+
+    semctl(semid, IPC_RMID).unwrap();
+}
@@ -0,0 +1,74 @@
+use std::slice;
+
+use libc::c_void;
+use tempfile::NamedTempFile;
+
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::shm::*;
+use nix::sys::stat::Mode;
+use nix::unistd::getuid;
+
+#[test]
+fn test_shm_private() {
+    let size = 4096;
+    let shmid = shmget(IPC_PRIVATE, size, ShmgetFlag::IPC_CREAT,
+                       Mode::S_IRUSR | Mode::S_IWUSR).unwrap();
+
+    let addr = unsafe { shmat(shmid, None, ShmatFlag::empty()) }.unwrap();
+    {
+        let buf = unsafe { slice::from_raw_parts_mut(addr as *mut u8, size) };
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+    }
+
+    // A second mapping of the same segment sees the pattern
+    let addr2 = unsafe { shmat(shmid, None, ShmatFlag::SHM_RDONLY) }.unwrap();
+    {
+        let buf = unsafe { slice::from_raw_parts(addr2 as *const u8, size) };
+        for (i, b) in buf.iter().enumerate() {
+            assert_eq!(*b, i as u8);
+        }
+    }
+
+    let ds = shm_stat(shmid).unwrap();
+    assert_eq!(ds.size(), size);
+    assert_eq!(ds.attach_count(), 2);
+    assert_eq!(ds.uid(), getuid());
+    assert_eq!(ds.mode(), Mode::S_IRUSR | Mode::S_IWUSR);
+
+    unsafe {
+        shmdt(addr2 as *const c_void).unwrap();
+        shmdt(addr as *const c_void).unwrap();
+    }
+    assert_eq!(shm_stat(shmid).unwrap().attach_count(), 0);
+
+    shm_remove(shmid).unwrap();
+    let res = unsafe { shmat(shmid, None, ShmatFlag::empty()) };
+    assert_eq!(res, Err(Error::Sys(Errno::EINVAL)));
+}
+
+#[test]
+fn test_shm_ftok() {
+    let file = NamedTempFile::new().unwrap();
+    let key = ftok(file.path(), b'n').unwrap();
+    assert_eq!(key, ftok(file.path(), b'n').unwrap());
+    assert!(key != ftok(file.path(), b'x').unwrap());
+
+    let shmid = shmget(key, 1024, ShmgetFlag::IPC_CREAT | ShmgetFlag::IPC_EXCL,
+                       Mode::S_IRUSR | Mode::S_IWUSR).unwrap();
+    // Looking the key up again must find the same segment
+    assert_eq!(shmget(key, 1024, ShmgetFlag::empty(), Mode::empty()).unwrap(), shmid);
+    let res = shmget(key, 1024, ShmgetFlag::IPC_CREAT | ShmgetFlag::IPC_EXCL,
+                     Mode::S_IRUSR | Mode::S_IWUSR);
+    assert_eq!(res, Err(Error::Sys(Errno::EEXIST)));
+
+    shm_remove(shmid).unwrap();
+}
+
+#[test]
+fn test_ftok_missing_file() {
+    let res = ftok("/this/path/does/not/exist", 1);
+    assert_eq!(res, Err(Error::Sys(Errno::ENOENT)));
+}
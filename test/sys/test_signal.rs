@@ -20,6 +20,56 @@ fn test_old_sigaction_flags() {
     let _flags = oact.flags();
 }
 
+#[test]
+fn test_thread_block_scoped() {
+    use std::sync::mpsc;
+    use std::thread;
+
+    let mut set = SigSet::empty();
+    set.add(SIGUSR1);
+
+    let (tx, rx) = mpsc::channel();
+    let other = thread::spawn(move || {
+        rx.recv().unwrap();
+        let mut mask = SigSet::empty();
+        pthread_sigmask(SigmaskHow::SIG_BLOCK, None, Some(&mut mask)).unwrap();
+        mask.contains(SIGUSR1)
+    });
+
+    {
+        let guard = set.thread_block_scoped().unwrap();
+        assert!(!guard.previous().contains(SIGUSR1));
+
+        let mut mask = SigSet::empty();
+        pthread_sigmask(SigmaskHow::SIG_BLOCK, None, Some(&mut mask)).unwrap();
+        assert!(mask.contains(SIGUSR1));
+
+        // Only the calling thread is affected
+        tx.send(()).unwrap();
+        assert!(!other.join().unwrap());
+    }
+
+    assert!(!SigSet::thread_get_mask().unwrap().contains(SIGUSR1));
+}
+
+#[test]
+fn test_thread_set_mask_scoped() {
+    let mut set = SigSet::empty();
+    set.add(SIGUSR1);
+    set.add(SIGUSR2);
+
+    {
+        let _guard = set.thread_set_mask_scoped().unwrap();
+        let mask = SigSet::thread_get_mask().unwrap();
+        assert!(mask.contains(SIGUSR1));
+        assert!(mask.contains(SIGUSR2));
+    }
+
+    let mask = SigSet::thread_get_mask().unwrap();
+    assert!(!mask.contains(SIGUSR1));
+    assert!(!mask.contains(SIGUSR2));
+}
+
 #[test]
 fn test_sigprocmask_noop() {
     sigprocmask(SigmaskHow::SIG_BLOCK, None, None)
@@ -86,6 +86,62 @@ pub fn test_abstract_uds_addr() {
     assert_eq!(internal, abstract_name.as_bytes());
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_abstract_uds_connect() {
+    use nix::sys::socket::{accept, bind, connect, listen, socket, AddressFamily, SockAddr,
+                           SockFlag, SockType};
+    use nix::unistd::{close, read, write};
+
+    let name = format!("nix-test_abstract_uds_connect-{}", ::nix::unistd::getpid());
+    let addr = SockAddr::Unix(UnixAddr::new_abstract(name.as_bytes()).unwrap());
+    assert_eq!(addr.to_str(), format!("@{}", name));
+
+    let listener = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    bind(listener, &addr).unwrap();
+    listen(listener, 1).unwrap();
+    assert_eq!(getsockname(listener).unwrap(), addr);
+
+    let client = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    connect(client, &addr).unwrap();
+    let server = accept(listener).unwrap();
+
+    write(client, b"hello").unwrap();
+    let mut buf = [0u8; 5];
+    assert_eq!(read(server, &mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    for fd in &[server, client, listener] {
+        close(*fd).unwrap();
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_uds_autobind() {
+    use nix::sys::socket::{bind, connect, listen, socket, AddressFamily, SockAddr, SockFlag,
+                           SockType};
+    use nix::unistd::close;
+
+    let listener = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    bind(listener, &SockAddr::Unix(UnixAddr::new_unnamed())).unwrap();
+    listen(listener, 1).unwrap();
+
+    let addr = match getsockname(listener).unwrap() {
+        SockAddr::Unix(addr) => addr,
+        addr => panic!("unexpected address {:?}", addr),
+    };
+    // The kernel picks a name of five hex digits
+    let name = addr.as_abstract().expect("autobound address isn't abstract").to_vec();
+    assert_eq!(name.len(), 5);
+
+    let client = socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None).unwrap();
+    connect(client, &SockAddr::Unix(UnixAddr::new_abstract(&name).unwrap())).unwrap();
+
+    close(client).unwrap();
+    close(listener).unwrap();
+}
+
 #[test]
 pub fn test_getsockname() {
     use nix::sys::socket::{socket, AddressFamily, SockType, SockFlag};
@@ -145,7 +201,7 @@ pub fn test_scm_rights() {
         let msg = recvmsg(fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
 
         for cmsg in msg.cmsgs() {
-            if let ControlMessage::ScmRights(fd) = cmsg {
+            if let Ok(ControlMessage::ScmRights(fd)) = cmsg {
                 assert_eq!(received_r, None);
                 assert_eq!(fd.len(), 1);
                 received_r = Some(fd[0]);
@@ -167,6 +223,95 @@ pub fn test_scm_rights() {
     close(w).unwrap();
 }
 
+// Send three pipe fds over `fd1`, closing the originals, and return the
+// write end of the pipe.
+fn send_three_fds(fd1: RawFd) -> RawFd {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::{close, dup, pipe};
+    use nix::sys::socket::{sendmsg, ControlMessage, MsgFlags};
+
+    let (r, w) = pipe().unwrap();
+    let fds = [r, dup(r).unwrap(), dup(r).unwrap()];
+    let iov = [IoVec::from_slice(b"hello")];
+    let cmsg = ControlMessage::ScmRights(&fds);
+    assert_eq!(sendmsg(fd1, &iov, &[cmsg], MsgFlags::empty(), None).unwrap(), 5);
+    for &fd in &fds {
+        close(fd).unwrap();
+    }
+    w
+}
+
+#[test]
+pub fn test_scm_rights_cmsg_space() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::{close, read, write};
+    use nix::sys::socket::{socketpair, recvmsg, AddressFamily, SockType, SockFlag,
+                           ControlMessage, MsgFlags};
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                     .unwrap();
+    let w = send_three_fds(fd1);
+
+    let mut buf = [0u8; 5];
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    let mut cmsgspace = cmsg_space!([RawFd; 3]);
+    let received = {
+        let msg = recvmsg(fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+        assert!(!msg.flags.intersects(MsgFlags::MSG_TRUNC | MsgFlags::MSG_CTRUNC));
+        let cmsgs: Vec<_> = msg.cmsgs().collect();
+        assert_eq!(cmsgs.len(), 1);
+        match cmsgs[0] {
+            Ok(ControlMessage::ScmRights(fds)) => fds.to_vec(),
+            _ => panic!("unexpected cmsg"),
+        }
+    };
+    assert_eq!(received.len(), 3);
+
+    write(w, b"abc").unwrap();
+    let mut buf = [0u8; 1];
+    for &fd in &received {
+        assert_eq!(read(fd, &mut buf).unwrap(), 1);
+        close(fd).unwrap();
+    }
+    close(w).unwrap();
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
+#[test]
+pub fn test_scm_rights_truncated() {
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+    use nix::sys::socket::{socketpair, recvmsg, AddressFamily, SockType, SockFlag,
+                           CmsgError, MsgFlags};
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+                     .unwrap();
+    let w = send_three_fds(fd1);
+
+    let mut buf = [0u8; 5];
+    let iov = [IoVec::from_mut_slice(&mut buf[..])];
+    let mut cmsgspace = cmsg_space!([RawFd; 1]);
+    let received = {
+        let msg = recvmsg(fd2, &iov, Some(&mut cmsgspace), MsgFlags::empty()).unwrap();
+        assert!(msg.flags.contains(MsgFlags::MSG_CTRUNC));
+        let cmsgs: Vec<_> = msg.cmsgs().collect();
+        assert_eq!(cmsgs.len(), 1);
+        match cmsgs[0] {
+            Err(CmsgError::Truncated(fds)) => fds.to_vec(),
+            _ => panic!("truncation not reported"),
+        }
+    };
+    // The fds that fit were received nonetheless.
+    assert!(!received.is_empty() && received.len() < 3);
+    for &fd in &received {
+        close(fd).unwrap();
+    }
+    close(w).unwrap();
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
 // Verify `sendmsg` builds a valid `msghdr` when passing an empty
 // `cmsgs` argument.  This should result in a msghdr with a nullptr
 // msg_control field and a msg_controllen of 0 when calling into the
@@ -255,3 +400,318 @@ pub fn test_syscontrol() {
     // requires root privileges
     // connect(fd, &sockaddr).expect("connect failed");
 }
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_ipv4_pktinfo() {
+    use std::mem;
+    use libc::{c_uint, in_pktinfo};
+    use nix::net::if_::if_nametoindex;
+    use nix::sys::socket::{bind, recvfrom, recvmsg, sendmsg, sendto, setsockopt, socket, sockopt,
+                           AddressFamily, CmsgSpace, ControlMessage, MsgFlags, SockAddr, SockFlag,
+                           SockType};
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+
+    let localhost = SocketAddr::from_str("127.0.0.1:0").unwrap();
+    let lo = if_nametoindex("lo").unwrap();
+
+    let receiver = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    setsockopt(receiver, sockopt::Ipv4PacketInfo, &true).unwrap();
+    bind(receiver, &SockAddr::new_inet(InetAddr::from_std(&SocketAddr::from_str("0.0.0.0:0").unwrap())))
+        .unwrap();
+    let port = match getsockname(receiver).unwrap() {
+        SockAddr::Inet(addr) => addr.port(),
+        addr => panic!("unexpected address {:?}", addr),
+    };
+
+    let sender = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    bind(sender, &SockAddr::new_inet(InetAddr::from_std(&localhost))).unwrap();
+    let sender_addr = getsockname(sender).unwrap();
+    let mut dest = localhost;
+    dest.set_port(port);
+    sendto(sender, b"ping", &SockAddr::new_inet(InetAddr::from_std(&dest)), MsgFlags::empty()).unwrap();
+
+    let mut buf = [0u8; 4];
+    let mut cmsgspace: CmsgSpace<in_pktinfo> = CmsgSpace::new();
+    let (info, peer) = {
+        let msg = recvmsg(receiver, &[IoVec::from_mut_slice(&mut buf)], Some(&mut cmsgspace),
+                          MsgFlags::empty()).unwrap();
+        assert_eq!(msg.bytes, 4);
+        match msg.cmsgs().next() {
+            Some(Ok(ControlMessage::Ipv4PacketInfo(&info))) => (info, msg.address.unwrap()),
+            _ => panic!("no IP_PKTINFO control message"),
+        }
+    };
+    assert_eq!(&buf, b"ping");
+    assert_eq!(peer, sender_addr);
+    assert_eq!(u32::from_be(info.ipi_addr.s_addr), 0x7f00_0001);
+    assert_eq!(u32::from_be(info.ipi_spec_dst.s_addr), 0x7f00_0001);
+    assert_eq!(info.ipi_ifindex as c_uint, lo);
+
+    // Reply from the address the datagram was sent to
+    let mut reply_info: in_pktinfo = unsafe { mem::zeroed() };
+    reply_info.ipi_ifindex = info.ipi_ifindex;
+    reply_info.ipi_spec_dst = info.ipi_spec_dst;
+    sendmsg(receiver, &[IoVec::from_slice(b"pong")], &[ControlMessage::Ipv4PacketInfo(&reply_info)],
+            MsgFlags::empty(), Some(&peer)).unwrap();
+
+    let (len, from) = recvfrom(sender, &mut buf).unwrap();
+    assert_eq!(&buf[..len], b"pong");
+    match from {
+        SockAddr::Inet(addr) => assert_eq!(addr.to_std(), dest),
+        addr => panic!("unexpected address {:?}", addr),
+    }
+
+    close(sender).unwrap();
+    close(receiver).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_ipv6_pktinfo() {
+    use std::io::Write;
+    use libc::{c_uint, in6_pktinfo};
+    use nix::net::if_::if_nametoindex;
+    use nix::sys::socket::{bind, recvmsg, setsockopt, socket, sockopt, AddressFamily, CmsgSpace,
+                           ControlMessage, MsgFlags, SockAddr, SockFlag, SockType, sendto};
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+
+    let localhost = SocketAddr::from_str("[::1]:0").unwrap();
+    let lo = if_nametoindex("lo").unwrap();
+
+    let sender = socket(AddressFamily::Inet6, SockType::Datagram, SockFlag::empty(), None);
+    let bound = sender.and_then(|fd| bind(fd, &SockAddr::new_inet(InetAddr::from_std(&localhost))));
+    let sender = match (sender, bound) {
+        (Ok(fd), Ok(())) => fd,
+        (sender, _) => {
+            if let Ok(fd) = sender {
+                close(fd).unwrap();
+            }
+            let stderr = ::std::io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "test_ipv6_pktinfo requires IPv6 on the loopback interface. Skipping test.")
+                .unwrap();
+            return;
+        },
+    };
+    let receiver = socket(AddressFamily::Inet6, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    setsockopt(receiver, sockopt::Ipv6RecvPacketInfo, &true).unwrap();
+    bind(receiver, &SockAddr::new_inet(InetAddr::from_std(&SocketAddr::from_str("[::]:0").unwrap())))
+        .unwrap();
+    let port = match getsockname(receiver).unwrap() {
+        SockAddr::Inet(addr) => addr.port(),
+        addr => panic!("unexpected address {:?}", addr),
+    };
+
+    let mut dest = localhost;
+    dest.set_port(port);
+    sendto(sender, b"ping", &SockAddr::new_inet(InetAddr::from_std(&dest)), MsgFlags::empty()).unwrap();
+
+    let mut buf = [0u8; 4];
+    let mut cmsgspace: CmsgSpace<in6_pktinfo> = CmsgSpace::new();
+    let info = {
+        let msg = recvmsg(receiver, &[IoVec::from_mut_slice(&mut buf)], Some(&mut cmsgspace),
+                          MsgFlags::empty()).unwrap();
+        match msg.cmsgs().next() {
+            Some(Ok(ControlMessage::Ipv6PacketInfo(&info))) => info,
+            _ => panic!("no IPV6_PKTINFO control message"),
+        }
+    };
+    assert_eq!(&buf, b"ping");
+    assert_eq!(info.ipi6_addr.s6_addr, Ipv6Addr::from_str("::1").unwrap().octets());
+    assert_eq!(info.ipi6_ifindex as c_uint, lo);
+
+    close(sender).unwrap();
+    close(receiver).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_ipv4_recverr() {
+    use nix::errno::Errno;
+    use nix::poll::{poll, EventFlags, PollFd};
+    use nix::sys::socket::{bind, recvmsg, sendto, setsockopt, socket, sockopt, AddressFamily,
+                           CmsgSpace, ControlMessage, MsgFlags, SockAddr, SockFlag, SockType};
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+
+    let localhost = InetAddr::from_std(&SocketAddr::from_str("127.0.0.1:0").unwrap());
+
+    // A port that nothing is bound to anymore
+    let closed = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    bind(closed, &SockAddr::new_inet(localhost)).unwrap();
+    let dest = getsockname(closed).unwrap();
+    close(closed).unwrap();
+
+    let sock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    setsockopt(sock, sockopt::Ipv4RecvErr, &true).unwrap();
+    sendto(sock, b"ping", &dest, MsgFlags::empty()).unwrap();
+
+    // The ICMP port unreachable error is reported as POLLERR
+    let mut fds = [PollFd::new(sock, EventFlags::empty())];
+    assert_eq!(poll(&mut fds, 1000), Ok(1));
+    assert!(fds[0].revents().unwrap().contains(EventFlags::POLLERR));
+
+    let mut buf = [0u8; 4];
+    let mut cmsgspace: CmsgSpace<[u8; 64]> = CmsgSpace::new();
+    {
+        let msg = recvmsg(sock, &[IoVec::from_mut_slice(&mut buf)], Some(&mut cmsgspace),
+                          MsgFlags::MSG_ERRQUEUE).unwrap();
+        // The datagram that caused the error
+        assert_eq!(msg.bytes, 4);
+        assert_eq!(msg.address, Some(dest));
+        match msg.cmsgs().next() {
+            Some(Ok(ControlMessage::Ipv4RecvErr(err))) => {
+                assert_eq!(err.errno(), Errno::ECONNREFUSED);
+                assert_eq!(err.origin(), ::libc::SO_EE_ORIGIN_ICMP);
+                // ICMP_DEST_UNREACH, ICMP_PORT_UNREACH
+                assert_eq!(err.ee_type(), 3);
+                assert_eq!(err.code(), 3);
+                assert_eq!(err.zerocopy_range(), None);
+                match err.offender() {
+                    Some(SockAddr::Inet(addr)) => {
+                        assert_eq!(addr.to_std().ip(), net::IpAddr::from([127, 0, 0, 1]))
+                    },
+                    addr => panic!("unexpected offender {:?}", addr),
+                }
+            },
+            _ => panic!("no IP_RECVERR control message"),
+        }
+    }
+    assert_eq!(&buf, b"ping");
+
+    close(sock).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+pub fn test_scm_security() {
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::os::unix::ffi::OsStrExt;
+    use nix::sys::socket::{getsockopt, recvmsg, send, setsockopt, socketpair, sockopt,
+                           AddressFamily, CmsgSpace, ControlMessage, MsgFlags, SockFlag,
+                           SockType};
+    use nix::sys::uio::IoVec;
+    use nix::unistd::close;
+
+    let mut label = Vec::new();
+    if File::open("/proc/self/attr/current").and_then(|mut f| f.read_to_end(&mut label)).is_err() {
+        let stderr = io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_scm_security requires /proc/self/attr/current. Skipping test.")
+            .unwrap();
+        return;
+    }
+    // AppArmor ends the label with a newline and SELinux with a NUL
+    while label.last() == Some(&b'\n') || label.last() == Some(&0) {
+        label.pop();
+    }
+
+    let (fd1, fd2) = socketpair(AddressFamily::Unix, SockType::Stream, None, SockFlag::empty())
+        .unwrap();
+    setsockopt(fd2, sockopt::PassSec, &true).unwrap();
+    assert!(getsockopt(fd2, sockopt::PassSec).unwrap());
+    // Succeeds with an empty label if no security module labels sockets
+    getsockopt(fd2, sockopt::PeerSec).unwrap();
+    send(fd1, b"x", MsgFlags::empty()).unwrap();
+
+    let mut buf = [0u8; 1];
+    let mut cmsgspace: CmsgSpace<[u8; 256]> = CmsgSpace::new();
+    let msg = recvmsg(fd2, &[IoVec::from_mut_slice(&mut buf)], Some(&mut cmsgspace),
+                      MsgFlags::empty()).unwrap();
+    assert_eq!(msg.bytes, 1);
+    let received = msg.cmsgs().filter_map(|cmsg| match cmsg {
+        Ok(ControlMessage::ScmSecurity(received)) => Some(received.to_owned()),
+        _ => None,
+    }).next();
+    match received {
+        Some(received) => assert_eq!(received.as_bytes(), &label[..]),
+        None => {
+            let stderr = io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "test_scm_security requires a security module labelling sockets. Skipping test.")
+                .unwrap();
+        },
+    }
+
+    close(fd1).unwrap();
+    close(fd2).unwrap();
+}
+
+#[test]
+pub fn test_ip_multicast_loopback() {
+    use std::io::Write;
+    use std::net::Ipv4Addr as StdIpv4Addr;
+    use std::thread;
+    use std::time::Duration;
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::sys::socket::{bind, recv, sendto, setsockopt, getsockopt, socket, sockopt,
+                           AddressFamily, IpMembershipRequest, Ipv4Addr, MsgFlags, SockAddr,
+                           SockFlag, SockType};
+    use nix::sys::time::{TimeVal, TimeValLike};
+    use nix::unistd::close;
+
+    let group = StdIpv4Addr::new(239, 255, 255, 250);
+    let loopback = StdIpv4Addr::new(127, 0, 0, 1);
+
+    let sock = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), None).unwrap();
+    bind(sock, &SockAddr::new_inet(InetAddr::from_std(&SocketAddr::from_str("0.0.0.0:0").unwrap())))
+        .unwrap();
+    let port = match getsockname(sock).unwrap() {
+        SockAddr::Inet(addr) => addr.port(),
+        addr => panic!("unexpected address {:?}", addr),
+    };
+    let dest = SockAddr::new_inet(InetAddr::from_std(&SocketAddr::new(group.into(), port)));
+
+    setsockopt(sock, sockopt::IpMulticastIf, &Ipv4Addr::from_std(&loopback)).unwrap();
+    assert_eq!(getsockopt(sock, sockopt::IpMulticastIf).unwrap().to_std(), loopback);
+    setsockopt(sock, sockopt::IpMulticastLoop, &true).unwrap();
+    assert!(getsockopt(sock, sockopt::IpMulticastLoop).unwrap());
+    setsockopt(sock, sockopt::IpMulticastTtl, &1).unwrap();
+    assert_eq!(getsockopt(sock, sockopt::IpMulticastTtl).unwrap(), 1);
+
+    let request = IpMembershipRequest::from_std(&group, Some(&loopback));
+    assert_eq!(request.multiaddr().to_std(), group);
+    assert_eq!(request.interface().to_std(), loopback);
+    setsockopt(sock, sockopt::IpAddMembership, &request).unwrap();
+
+    if let Err(e) = sendto(sock, b"ping", &dest, MsgFlags::empty()) {
+        let stderr = ::std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_ip_multicast_loopback requires multicast on the loopback interface \
+                          ({}). Skipping test.", e).unwrap();
+        close(sock).unwrap();
+        return;
+    }
+    // Fail rather than hang if the datagram is lost
+    setsockopt(sock, sockopt::ReceiveTimeout, &TimeVal::seconds(1)).unwrap();
+    let mut buf = [0u8; 4];
+    assert_eq!(recv(sock, &mut buf, MsgFlags::empty()), Ok(4));
+    assert_eq!(&buf, b"ping");
+
+    // Without the membership the datagram isn't delivered anymore
+    setsockopt(sock, sockopt::IpDropMembership, &request).unwrap();
+    sendto(sock, b"pong", &dest, MsgFlags::empty()).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert_eq!(recv(sock, &mut buf, MsgFlags::MSG_DONTWAIT), Err(Error::Sys(Errno::EAGAIN)));
+
+    close(sock).unwrap();
+}
+
+#[test]
+pub fn test_ipv6_membership_request() {
+    use nix::sys::socket::{Ipv6Addr, Ipv6MembershipRequest};
+
+    let group = Ipv6Addr::from_std(&net::Ipv6Addr::from_str("ff02::1:3").unwrap());
+    let request = Ipv6MembershipRequest::with_interface(group, 7);
+    assert_eq!(request.multiaddr().to_std(), group.to_std());
+    assert_eq!(request.interface(), 7);
+    assert_eq!(request, Ipv6MembershipRequest::from_std(&group.to_std(), 7));
+    assert!(request != Ipv6MembershipRequest::with_interface(group, 8));
+    // Without an interface the kernel picks one
+    assert_eq!(Ipv6MembershipRequest::new(group).interface(), 0);
+}
@@ -1,5 +1,7 @@
 use rand::{thread_rng, Rng};
 use nix::sys::socket::{socket, sockopt, getsockopt, setsockopt, AddressFamily, SockType, SockFlag, SockProtocol};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use nix::unistd::close;
 
 #[test]
 fn test_so_buf() {
@@ -13,3 +15,51 @@ fn test_so_buf() {
     let actual = getsockopt(fd, sockopt::RcvBuf).unwrap();
     assert!(actual >= bufsize);
 }
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_so_buf_doubled() {
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), SockProtocol::Tcp)
+             .unwrap();
+    // Small enough to stay below the default rmem_max and wmem_max.
+    let bufsize: usize = 64 * 1024;
+    setsockopt(fd, sockopt::RcvBuf, &bufsize).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::RcvBuf).unwrap(), 2 * bufsize);
+    setsockopt(fd, sockopt::SndBuf, &bufsize).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::SndBuf).unwrap(), 2 * bufsize);
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_so_buf_force_unprivileged() {
+    use std::io::Write;
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::unistd::geteuid;
+
+    if geteuid().is_root() {
+        let stderr = ::std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_so_buf_force_unprivileged must not run as root. Skipping test.")
+            .unwrap();
+        return;
+    }
+
+    let fd = socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(), SockProtocol::Udp)
+             .unwrap();
+    let bufsize: usize = 64 * 1024;
+    assert_eq!(setsockopt(fd, sockopt::RcvBufForce, &bufsize), Err(Error::Sys(Errno::EPERM)));
+    assert_eq!(setsockopt(fd, sockopt::SndBufForce, &bufsize), Err(Error::Sys(Errno::EPERM)));
+    close(fd).unwrap();
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_so_incoming_cpu() {
+    let fd = socket(AddressFamily::Inet, SockType::Stream, SockFlag::empty(), SockProtocol::Tcp)
+             .unwrap();
+    setsockopt(fd, sockopt::IncomingCpu, &0).unwrap();
+    assert_eq!(getsockopt(fd, sockopt::IncomingCpu).unwrap(), 0);
+    close(fd).unwrap();
+}
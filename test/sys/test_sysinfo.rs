@@ -0,0 +1,28 @@
+use std::fs::File;
+use std::io::Read;
+use std::time::Duration;
+
+use nix::sys::sysinfo::sysinfo;
+
+#[test]
+fn test_sysinfo() {
+    let info = sysinfo().unwrap();
+
+    assert!(info.uptime() > Duration::from_secs(0));
+    assert!(info.ram_total() > 0);
+    assert!(info.ram_total() >= info.ram_unused());
+    assert!(info.swap_total() >= info.swap_free());
+    assert!(info.process_count() > 0);
+}
+
+#[test]
+fn test_sysinfo_load_average() {
+    let (l1, l5, l15) = sysinfo().unwrap().load_average();
+    assert!(l1 >= 0.0 && l5 >= 0.0 && l15 >= 0.0);
+
+    let mut loadavg = String::new();
+    File::open("/proc/loadavg").unwrap().read_to_string(&mut loadavg).unwrap();
+    let proc_l15: f64 = loadavg.split_whitespace().nth(2).unwrap().parse().unwrap();
+    // The 15 minute average barely moves between the two readings
+    assert!((l15 - proc_l15).abs() < 1.0, "{} vs {}", l15, proc_l15);
+}
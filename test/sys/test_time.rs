@@ -0,0 +1,162 @@
+use std::io::Write;
+use nix::Error;
+use nix::errno::Errno;
+use nix::sys::time::{adjtimex, clock_adjtime, AdjFlags, ClockId, TimeSpec, TimeValLike, TimeX};
+use nix::sys::time::{getitimer, setitimer, ItimerVal, ItimerWhich, TimeVal};
+use nix::unistd::{getpid, Uid};
+
+#[test]
+fn test_adjtimex_query() {
+    let mut buf = TimeX::new();
+    adjtimex(&mut buf).unwrap();
+    assert!(buf.modes().is_empty());
+
+    // The kernel limits the frequency offset to 500 ppm.
+    assert!(buf.frequency().abs() <= 500 << 16);
+    assert!(buf.tolerance() > 0);
+}
+
+#[test]
+fn test_clock_adjtime_query() {
+    let mut buf = TimeX::new();
+    match clock_adjtime(ClockId::REALTIME, &mut buf) {
+        // clock_adjtime is only available since Linux 2.6.39
+        Err(Error::Sys(Errno::ENOSYS)) => (),
+        res => {
+            res.unwrap();
+            assert!(buf.frequency().abs() <= 500 << 16);
+        }
+    }
+}
+
+#[test]
+fn test_adjtimex_set_frequency() {
+    if !Uid::current().is_root() {
+        let stderr = ::std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_adjtimex_set_frequency requires root privileges. Skipping test.").unwrap();
+        return;
+    }
+
+    let mut buf = TimeX::new();
+    adjtimex(&mut buf).unwrap();
+    let orig = buf.frequency();
+    let new = if orig < 0 { orig + 1 } else { orig - 1 };
+
+    let mut adj = TimeX::new();
+    adj.set_frequency(new);
+    adjtimex(&mut adj).unwrap();
+    assert_eq!(adj.frequency(), new);
+
+    let mut restore = TimeX::new();
+    restore.set_frequency(orig);
+    adjtimex(&mut restore).unwrap();
+    assert_eq!(restore.frequency(), orig);
+}
+
+#[test]
+fn test_adjtimex_set_time_offset() {
+    if !Uid::current().is_root() {
+        let stderr = ::std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_adjtimex_set_time_offset requires root privileges. Skipping test.").unwrap();
+        return;
+    }
+
+    // Stepping the clock by nothing leaves it alone.
+    let mut adj = TimeX::new();
+    adj.set_time_offset(TimeVal::zero());
+    assert!(adj.modes().contains(AdjFlags::ADJ_SETOFFSET));
+    adjtimex(&mut adj).unwrap();
+
+    // The offset must be normalized with non-negative microseconds.
+    let mut adj = TimeX::new();
+    adj.set_time_offset(TimeVal::microseconds(-1_500_000));
+    assert_eq!(adj.time().tv_sec(), -2);
+    assert_eq!(adj.time().tv_usec(), 500_000);
+}
+
+#[test]
+fn test_clock_monotonic_now() {
+    let t1 = ClockId::MONOTONIC.now().unwrap();
+    let t2 = ClockId::MONOTONIC.now().unwrap();
+    assert!(t2 >= t1);
+}
+
+#[test]
+fn test_clock_realtime_res() {
+    let res = ClockId::REALTIME.res().unwrap();
+    assert!(res > TimeSpec::zero());
+}
+
+#[test]
+fn test_clock_realtime_set_unprivileged() {
+    if Uid::current().is_root() {
+        let stderr = ::std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_clock_realtime_set_unprivileged requires an unprivileged user. Skipping test.").unwrap();
+        return;
+    }
+
+    let now = ClockId::REALTIME.now().unwrap();
+    assert_eq!(ClockId::REALTIME.set(now), Err(Error::Sys(Errno::EPERM)));
+}
+
+#[test]
+fn test_pid_cpu_clock_id() {
+    let clk_id = ClockId::pid_cpu_clock_id(getpid()).unwrap();
+    assert!(clk_id.now().unwrap() > TimeSpec::zero());
+    assert!(ClockId::PROCESS_CPUTIME_ID.now().unwrap() > TimeSpec::zero());
+}
+
+#[test]
+fn test_setitimer_real() {
+    use std::{mem, thread};
+    use std::time::Duration;
+    use libc::_exit;
+    use nix::sys::signal::{SigSet, SIGALRM};
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{fork, ForkResult};
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // SIGALRM is sent to the whole process, so the timer is armed in a
+    // single-threaded child which blocks it.  The exit status says which
+    // check failed.
+    match fork().expect("Error: Fork Failed") {
+        ForkResult::Child => {
+            let mut alrm = SigSet::empty();
+            alrm.add(SIGALRM);
+            let interval = TimeVal::milliseconds(20);
+            let code = if alrm.thread_block().is_err() {
+                1
+            } else if setitimer(ItimerWhich::ITIMER_REAL, &ItimerVal::new(interval, interval))
+                    .map(|old| old.value()) != Ok(TimeVal::zero()) {
+                2
+            } else if alrm.wait() != Ok(SIGALRM) {
+                3
+            } else if getitimer(ItimerWhich::ITIMER_REAL)
+                    .map(|curr| curr.interval() == interval && curr.value() < interval) != Ok(true) {
+                4
+            } else if setitimer(ItimerWhich::ITIMER_REAL, &ItimerVal::disarm())
+                    .map(|old| old.interval()) != Ok(interval) {
+                5
+            } else if getitimer(ItimerWhich::ITIMER_REAL).map(|curr| curr.value()) != Ok(TimeVal::zero()) {
+                6
+            } else {
+                thread::sleep(Duration::from_millis(100));
+                let mut pending: ::libc::sigset_t = unsafe { mem::zeroed() };
+                let pending = unsafe {
+                    ::libc::sigpending(&mut pending);
+                    ::libc::sigismember(&pending, ::libc::SIGALRM)
+                };
+                if pending == 0 { 0 } else { 7 }
+            };
+            unsafe { _exit(code) }
+        },
+        ForkResult::Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+}
@@ -1,4 +1,5 @@
 use nix::Error;
+use nix::errno::Errno;
 use nix::unistd::*;
 use nix::unistd::ForkResult::*;
 use nix::sys::signal::*;
@@ -58,6 +59,65 @@ fn test_waitstatus_pid() {
     }
 }
 
+#[test]
+fn test_wait_all_nonblocking() {
+    use std::thread;
+    use std::time::Duration;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let mut children = Vec::new();
+    for i in 0..3 {
+        // Safe: Child only calls `_exit`, which is async-signal-safe.
+        match fork().expect("Error: Fork Failed") {
+            Child => unsafe { _exit(i) },
+            Parent { child } => children.push(child),
+        }
+    }
+
+    let mut statuses = Vec::new();
+    for _ in 0..100 {
+        statuses.extend(wait_all_nonblocking().unwrap());
+        if statuses.len() == children.len() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    statuses.sort_by_key(|status| status.pid().map(::libc::pid_t::from));
+    let expected: Vec<_> = children.iter().enumerate()
+        .map(|(i, &child)| WaitStatus::Exited(child, i as i32))
+        .collect();
+    assert_eq!(statuses, expected);
+
+    // Nothing is left to reap
+    assert_eq!(wait_all_nonblocking(), Ok(vec![]));
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn test_wait_clone_children() {
+    use nix::errno::Errno;
+    use nix::sched::{clone, CloneFlags};
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let mut stacks = [[0u8; 64 * 1024]; 2];
+    let mut children = Vec::new();
+    for stack in stacks.iter_mut() {
+        // Without an exit signal these are "clone" children
+        let child = clone(Box::new(|| 7), stack, CloneFlags::empty(), None).unwrap();
+        children.push(child);
+    }
+
+    for &child in &children {
+        assert_eq!(waitpid(child, None), Err(Error::Sys(Errno::ECHILD)));
+        assert_eq!(waitpid(child, Some(WaitPidFlag::__WALL)), Ok(WaitStatus::Exited(child, 7)));
+    }
+}
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 // FIXME: qemu-user doesn't implement ptrace on most arches
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
@@ -105,3 +165,66 @@ mod ptrace {
         }
     }
 }
+
+#[test]
+fn test_child_guard_kills_on_drop() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // Safe: The child only calls `pause` and `_exit`, which are async-signal-safe.
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            pause();
+            unsafe { _exit(123) }
+        },
+        Parent { child } => {
+            {
+                let guard = ChildGuard::from(child);
+                assert_eq!(guard.pid(), child);
+            }
+            // The child was killed and reaped
+            assert_eq!(kill(child, None), Err(Error::Sys(Errno::ESRCH)));
+        },
+    }
+}
+
+#[test]
+fn test_child_guard_forget() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // Safe: The child only calls `pause` and `_exit`, which are async-signal-safe.
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            pause();
+            unsafe { _exit(123) }
+        },
+        Parent { child } => {
+            let guard = ChildGuard::new(child, SIGTERM);
+            assert_eq!(guard.forget(), child);
+            // The child is still running
+            assert_eq!(kill(child, None), Ok(()));
+
+            kill(child, SIGKILL).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Signaled(child, SIGKILL, false)));
+        },
+    }
+}
+
+#[test]
+fn test_child_guard_wait() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // Safe: Child only calls `_exit`, which is async-signal-safe.
+    match fork().expect("Error: Fork Failed") {
+        Child => unsafe { _exit(12) },
+        Parent { child } => {
+            let mut guard = ChildGuard::from(child);
+            assert_eq!(guard.wait(), Ok(WaitStatus::Exited(child, 12)));
+            drop(guard);
+            // The child was only reaped once
+            assert_eq!(waitpid(child, None), Err(Error::Sys(Errno::ECHILD)));
+        },
+    }
+}
@@ -0,0 +1,76 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Write;
+use std::os::unix::fs::symlink;
+use std::os::unix::io::AsRawFd;
+
+use nix::Error;
+use nix::errno::{self, Errno};
+use nix::sys::xattr::*;
+use tempdir::TempDir;
+
+#[test]
+fn test_xattr() {
+    let tempdir = TempDir::new("nix-test_xattr").unwrap();
+    let path = tempdir.path().join("file");
+    let file = File::create(&path).unwrap();
+
+    match setxattr(&path, "user.test", b"hello", XattrFlags::empty()) {
+        Err(Error::Sys(e)) if e == errno::ENOTSUP => {
+            let stderr = ::std::io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "test_xattr requires user extended attributes. Skipping test.").unwrap();
+            return;
+        },
+        res => res.unwrap(),
+    }
+
+    assert!(listxattr(&path).unwrap().contains(&OsString::from("user.test")));
+    assert_eq!(getxattr(&path, "user.test").unwrap(), b"hello");
+    assert_eq!(fgetxattr(file.as_raw_fd(), "user.test").unwrap(), b"hello");
+    assert_eq!(getxattr(&path, "user.missing"), Err(Error::Sys(Errno::ENODATA)));
+
+    // XATTR_CREATE only creates and XATTR_REPLACE only replaces
+    assert_eq!(setxattr(&path, "user.test", b"again", XattrFlags::XATTR_CREATE),
+               Err(Error::Sys(Errno::EEXIST)));
+    assert_eq!(setxattr(&path, "user.other", b"x", XattrFlags::XATTR_REPLACE),
+               Err(Error::Sys(Errno::ENODATA)));
+    fsetxattr(file.as_raw_fd(), "user.test", b"replaced", XattrFlags::XATTR_REPLACE).unwrap();
+    assert_eq!(getxattr(&path, "user.test").unwrap(), b"replaced");
+
+    // An empty value is distinct from a missing attribute
+    setxattr(&path, "user.empty", b"", XattrFlags::XATTR_CREATE).unwrap();
+    assert_eq!(getxattr(&path, "user.empty").unwrap(), b"");
+    let names = flistxattr(file.as_raw_fd()).unwrap();
+    assert!(names.contains(&OsString::from("user.test")));
+    assert!(names.contains(&OsString::from("user.empty")));
+
+    removexattr(&path, "user.test").unwrap();
+    fremovexattr(file.as_raw_fd(), "user.empty").unwrap();
+    assert_eq!(removexattr(&path, "user.test"), Err(Error::Sys(Errno::ENODATA)));
+    assert!(!listxattr(&path).unwrap().contains(&OsString::from("user.test")));
+}
+
+#[test]
+fn test_lxattr() {
+    let tempdir = TempDir::new("nix-test_lxattr").unwrap();
+    let path = tempdir.path().join("file");
+    let link = tempdir.path().join("link");
+    File::create(&path).unwrap();
+    symlink(&path, &link).unwrap();
+
+    match setxattr(&link, "user.test", b"target", XattrFlags::empty()) {
+        Err(Error::Sys(e)) if e == errno::ENOTSUP => return,
+        res => res.unwrap(),
+    }
+
+    // The l variants operate on the link itself, which can't have user.*
+    // attributes, and don't see those of the target.  Modifying them is
+    // refused outright.
+    assert_eq!(getxattr(&path, "user.test").unwrap(), b"target");
+    assert_eq!(lgetxattr(&link, "user.test"), Err(Error::Sys(Errno::ENODATA)));
+    assert!(!llistxattr(&link).unwrap().contains(&OsString::from("user.test")));
+    assert_eq!(lsetxattr(&link, "user.test", b"link", XattrFlags::empty()),
+               Err(Error::Sys(Errno::EPERM)));
+    assert_eq!(lremovexattr(&link, "user.test"), Err(Error::Sys(Errno::EPERM)));
+}
@@ -11,7 +11,10 @@ extern crate tempdir;
 extern crate tempfile;
 
 mod sys;
+mod test_error;
 mod test_fcntl;
+mod test_fd;
+mod test_job;
 #[cfg(any(target_os = "dragonfly",
           target_os = "freebsd",
           target_os = "fushsia",
@@ -21,18 +24,35 @@ mod test_mq;
 mod test_net;
 mod test_nix_path;
 mod test_poll;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod test_process;
 mod test_pty;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod test_sched;
 #[cfg(any(target_os = "android",
           target_os = "freebsd",
           target_os = "ios",
           target_os = "linux",
           target_os = "macos"))]
 mod test_sendfile;
+#[cfg(any(target_os = "freebsd",
+          target_os = "ios",
+          target_os = "linux",
+          target_os = "macos"))]
+mod test_spawn;
 mod test_stat;
 mod test_unistd;
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::io::{self, Write};
 use std::os::unix::io::RawFd;
 use std::sync::Mutex;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use nix::sched::{unshare, CloneFlags};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use nix::sys::wait::{waitpid, WaitStatus};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use nix::unistd::{fork, ForkResult};
 use nix::unistd::read;
 
 /// Helper function analogous to `std::io::Read::read_exact`, but for `RawFD`s
@@ -45,6 +65,44 @@ fn read_exact(f: RawFd, buf: &mut  [u8]) {
     }
 }
 
+/// Run `f` in a forked child, in a new user namespace and the other new
+/// namespaces in `flags`.
+///
+/// `f` returns the exit code of the child, where anything but 0 fails the
+/// test.  Since the test harness is multithreaded, `f` shouldn't allocate or
+/// panic; prepare everything it needs beforehand and report failures through
+/// the exit code instead.  2 is taken for failing to create the namespaces,
+/// in which case `test` is reported as skipped and `false` is returned.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn in_user_namespace<F>(test: &str, flags: CloneFlags, f: F) -> bool
+    where F: FnOnce() -> i32
+{
+    #[allow(unused_variables)]
+    let m = FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    match fork().expect("Error: Fork Failed") {
+        ForkResult::Child => {
+            let code = match unshare(CloneFlags::CLONE_NEWUSER | flags) {
+                Ok(()) => f(),
+                Err(_) => 2,
+            };
+            unsafe { libc::_exit(code) }
+        },
+        ForkResult::Parent { child } => {
+            match waitpid(child, None) {
+                Ok(WaitStatus::Exited(_, 0)) => true,
+                Ok(WaitStatus::Exited(_, 2)) => {
+                    let stderr = io::stderr();
+                    let mut handle = stderr.lock();
+                    writeln!(handle, "{} requires user namespaces. Skipping test.", test).unwrap();
+                    false
+                },
+                status => panic!("{} failed in the child: {:?}", test, status),
+            }
+        },
+    }
+}
+
 lazy_static! {
     /// Any test that changes the process's current working directory must grab
     /// this mutex
@@ -59,4 +117,6 @@ lazy_static! {
     pub static ref PTSNAME_MTX: Mutex<()> = Mutex::new(());
     /// Any test that alters signal handling must grab this mutex.
     pub static ref SIGNAL_MTX: Mutex<()> = Mutex::new(());
+    /// Any test that changes the process's umask must grab this mutex.
+    pub static ref UMASK_MTX: Mutex<()> = Mutex::new(());
 }
@@ -0,0 +1,54 @@
+use std::error::Error as StdError;
+use std::io;
+
+use nix::Error;
+use nix::errno::Errno;
+use nix::unistd::close;
+
+#[test]
+fn test_display() {
+    let err = Error::Sys(Errno::ENOENT);
+    let msg = format!("{}", err);
+    assert!(msg.contains("ENOENT"));
+    assert!(msg.contains(Errno::ENOENT.desc()));
+}
+
+#[test]
+fn test_as_errno() {
+    assert_eq!(Error::Sys(Errno::EBADF).as_errno(), Some(Errno::EBADF));
+    assert_eq!(Error::InvalidPath.as_errno(), None);
+    assert_eq!(Error::InvalidUtf8.as_errno(), None);
+    assert_eq!(Error::UnsupportedOperation.as_errno(), None);
+}
+
+#[test]
+fn test_into_io_error() {
+    let err: io::Error = close(-1).unwrap_err().into();
+    assert_eq!(err.raw_os_error(), Some(Errno::EBADF as i32));
+
+    let err: io::Error = Error::InvalidPath.into();
+    assert_eq!(err.raw_os_error(), None);
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+fn boxed_close(fd: i32) -> Result<(), Box<StdError + Send + Sync>> {
+    close(fd)?;
+    Ok(())
+}
+
+#[test]
+fn test_boxed_error() {
+    let err = boxed_close(-1).unwrap_err();
+    assert_eq!(err.description(), Errno::EBADF.desc());
+}
+
+fn io_close(fd: i32) -> io::Result<()> {
+    close(fd)?;
+    Ok(())
+}
+
+#[test]
+fn test_question_mark_io() {
+    let err = io_close(-1).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(Errno::EBADF as i32));
+}
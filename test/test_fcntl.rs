@@ -1,10 +1,24 @@
+use nix::Error;
+use nix::errno::Errno;
 use nix::fcntl::{openat, open, OFlag, readlink, readlinkat};
+use nix::fcntl::{fcntl, set_cloexec, set_nonblocking, FcntlArg, FdFlag, FileLock};
+use nix::fcntl::{atomic_write, fsync_parent_dir};
+use nix::sys::signal::{kill, SIGKILL};
 use nix::sys::stat::Mode;
-use nix::unistd::{close, read};
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{close, fork, pipe, read, write, ForkResult};
+use libc::_exit;
+use rand::{thread_rng, Rng};
 use tempdir::TempDir;
 use tempfile::NamedTempFile;
+use std::fs::{self as std_fs, File};
 use std::io::prelude::*;
 use std::os::unix::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::panic;
+use std::thread;
+use std::time::Duration;
 
 #[test]
 fn test_openat() {
@@ -47,14 +61,314 @@ fn test_readlink() {
                src.to_str().unwrap());
 }
 
+#[test]
+fn test_set_cloexec() {
+    let (r, w) = pipe().unwrap();
+    let getfd = |fd| FdFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFD).unwrap());
+
+    set_cloexec(r, true).unwrap();
+    assert!(getfd(r).contains(FdFlag::FD_CLOEXEC));
+    // Setting it again is a no-op
+    set_cloexec(r, true).unwrap();
+    assert!(getfd(r).contains(FdFlag::FD_CLOEXEC));
+    set_cloexec(r, false).unwrap();
+    assert!(!getfd(r).contains(FdFlag::FD_CLOEXEC));
+
+    close(r).unwrap();
+    close(w).unwrap();
+}
+
+#[test]
+fn test_set_nonblocking() {
+    let (r, w) = pipe().unwrap();
+    let getfl = |fd| OFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFL).unwrap());
+    let mode = getfl(r) & OFlag::O_ACCMODE;
+    let mut buf = [0u8; 4];
+
+    set_nonblocking(r, true).unwrap();
+    assert!(getfl(r).contains(OFlag::O_NONBLOCK));
+    assert_eq!(getfl(r) & OFlag::O_ACCMODE, mode);
+    assert_eq!(read(r, &mut buf), Err(Error::Sys(Errno::EAGAIN)));
+
+    // Once blocking again, the read must wait for the writer
+    set_nonblocking(r, false).unwrap();
+    assert!(!getfl(r).contains(OFlag::O_NONBLOCK));
+    let writer = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        write(w, b"abcd").unwrap();
+        w
+    });
+    assert_eq!(read(r, &mut buf), Ok(4));
+    assert_eq!(&buf, b"abcd");
+
+    close(writer.join().unwrap()).unwrap();
+    close(r).unwrap();
+}
+
+#[test]
+fn test_file_lock_contended() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let tmp = NamedTempFile::new().unwrap();
+    let file = File::open(tmp.path()).unwrap();
+    let lock = FileLock::exclusive(&file).unwrap();
+    let (locked_r, locked_w) = pipe().unwrap();
+    let (unlocked_r, unlocked_w) = pipe().unwrap();
+
+    match fork().expect("Error: Fork Failed") {
+        ForkResult::Child => {
+            // flock locks belong to the open file description, so the child
+            // has to open the file again to contend with its parent
+            let file = File::open(tmp.path()).unwrap();
+            let mut buf = [0u8; 1];
+            let code = match FileLock::try_exclusive(&file) {
+                Ok(None) => {
+                    write(locked_w, b"x").unwrap();
+                    read(unlocked_r, &mut buf).unwrap();
+                    match FileLock::try_exclusive(&file) {
+                        Ok(Some(_)) => 0,
+                        _ => 2,
+                    }
+                },
+                _ => 1,
+            };
+            unsafe { _exit(code) }
+        },
+        ForkResult::Parent { child } => {
+            let mut buf = [0u8; 1];
+            assert_eq!(read(locked_r, &mut buf), Ok(1));
+            drop(lock);
+            write(unlocked_w, b"x").unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+
+    for &fd in &[locked_r, locked_w, unlocked_r, unlocked_w] {
+        close(fd).unwrap();
+    }
+}
+
+#[test]
+fn test_file_lock_shared() {
+    let tmp = NamedTempFile::new().unwrap();
+    let file = File::open(tmp.path()).unwrap();
+    let other = File::open(tmp.path()).unwrap();
+
+    let lock = FileLock::shared(&file).unwrap();
+    assert!(FileLock::try_shared(&other).unwrap().is_some());
+    assert!(FileLock::try_exclusive(&other).unwrap().is_none());
+    lock.unlock().unwrap();
+    assert!(FileLock::try_exclusive(&other).unwrap().is_some());
+}
+
+#[test]
+fn test_file_lock_released_on_panic() {
+    let tmp = NamedTempFile::new().unwrap();
+    let file = File::open(tmp.path()).unwrap();
+
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _lock = FileLock::exclusive(&file).unwrap();
+        panic!("while holding the lock");
+    }));
+    assert!(res.is_err());
+
+    let other = File::open(tmp.path()).unwrap();
+    assert!(FileLock::try_exclusive(&other).unwrap().is_some());
+}
+
+#[test]
+fn test_fsync_parent_dir() {
+    let tempdir = TempDir::new("nix-test_fsync_parent_dir").unwrap();
+    let path = tempdir.path().join("file");
+
+    // The file itself needn't exist
+    fsync_parent_dir(&path).unwrap();
+    assert_eq!(fsync_parent_dir(&tempdir.path().join("dir/file")),
+               Err(Error::Sys(Errno::ENOENT)));
+}
+
+fn dir_entries(dir: &Path) -> Vec<String> {
+    let mut entries: Vec<String> = std_fs::read_dir(dir).unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    entries.sort();
+    entries
+}
+
+#[test]
+fn test_atomic_write() {
+    let tempdir = TempDir::new("nix-test_atomic_write").unwrap();
+    let path = tempdir.path().join("file");
+
+    atomic_write(&path, b"old", Mode::from_bits_truncate(0o600)).unwrap();
+    atomic_write(&path, b"new contents", Mode::from_bits_truncate(0o640)).unwrap();
+
+    let mut contents = Vec::new();
+    File::open(&path).unwrap().read_to_end(&mut contents).unwrap();
+    assert_eq!(contents, b"new contents");
+    let metadata = std_fs::metadata(&path).unwrap();
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o640);
+    assert_eq!(dir_entries(tempdir.path()), vec!["file"]);
+}
+
+#[test]
+fn test_atomic_write_symlink() {
+    let tempdir = TempDir::new("nix-test_atomic_write_symlink").unwrap();
+    let target_dir = tempdir.path().join("target");
+    std_fs::create_dir(&target_dir).unwrap();
+    let link = tempdir.path().join("link");
+    fs::symlink("target/file", &link).unwrap();
+
+    // The dangling link's target is created in its directory
+    atomic_write(&link, b"contents", Mode::from_bits_truncate(0o600)).unwrap();
+
+    assert!(std_fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+    let mut contents = Vec::new();
+    File::open(target_dir.join("file")).unwrap().read_to_end(&mut contents).unwrap();
+    assert_eq!(contents, b"contents");
+    assert_eq!(dir_entries(tempdir.path()), vec!["link", "target"]);
+    assert_eq!(dir_entries(&target_dir), vec!["file"]);
+}
+
+#[test]
+fn test_atomic_write_failure() {
+    let tempdir = TempDir::new("nix-test_atomic_write_failure").unwrap();
+    let path = tempdir.path().join("dir");
+    std_fs::create_dir(&path).unwrap();
+    File::create(path.join("file")).unwrap();
+
+    // Renaming over a non-empty directory fails after the temporary file
+    // was written
+    assert!(atomic_write(&path, b"contents", Mode::from_bits_truncate(0o600)).is_err());
+    assert_eq!(dir_entries(tempdir.path()), vec!["dir"]);
+
+    // Writing fails if the file size limit is exceeded
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let path = tempdir.path().join("file");
+    atomic_write(&path, b"old", Mode::from_bits_truncate(0o600)).unwrap();
+    match fork().expect("Error: Fork Failed") {
+        ForkResult::Child => {
+            let limit = ::libc::rlimit { rlim_cur: 4, rlim_max: 4 };
+            let code = unsafe {
+                ::libc::signal(::libc::SIGXFSZ, ::libc::SIG_IGN);
+                ::libc::setrlimit(::libc::RLIMIT_FSIZE, &limit)
+            };
+            let code = match atomic_write(&path, b"new contents", Mode::from_bits_truncate(0o600)) {
+                _ if code != 0 => 2,
+                Err(Error::Sys(Errno::EFBIG)) => 0,
+                _ => 1,
+            };
+            unsafe { _exit(code) }
+        },
+        ForkResult::Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+
+    let mut contents = Vec::new();
+    File::open(&path).unwrap().read_to_end(&mut contents).unwrap();
+    assert_eq!(contents, b"old");
+    assert_eq!(dir_entries(tempdir.path()), vec!["dir", "file"]);
+}
+
+#[test]
+fn test_atomic_write_killed() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let tempdir = TempDir::new("nix-test_atomic_write_killed").unwrap();
+    let path = tempdir.path().join("file");
+    let a = vec![b'a'; 64 * 1024];
+    let b = vec![b'b'; 32 * 1024];
+    atomic_write(&path, &a, Mode::from_bits_truncate(0o600)).unwrap();
+
+    let mut children = Vec::new();
+    for _ in 0..10 {
+        match fork().expect("Error: Fork Failed") {
+            ForkResult::Child => {
+                loop {
+                    let _ = atomic_write(&path, &b, Mode::from_bits_truncate(0o600));
+                    let _ = atomic_write(&path, &a, Mode::from_bits_truncate(0o600));
+                }
+            },
+            ForkResult::Parent { child } => {
+                thread::sleep(Duration::from_millis(thread_rng().gen_range(0, 20)));
+                kill(child, SIGKILL).unwrap();
+                assert_eq!(waitpid(child, None), Ok(WaitStatus::Signaled(child, SIGKILL, false)));
+                children.push(child);
+            },
+        }
+
+        let mut contents = Vec::new();
+        File::open(&path).unwrap().read_to_end(&mut contents).unwrap();
+        assert!(contents == a || contents == b);
+    }
+
+    // A child killed while its temporary file had a name leaves behind at
+    // most that one file, and nothing else is left in the directory.
+    let mut entries = dir_entries(tempdir.path());
+    assert_eq!(entries.remove(0), "file");
+    let mut owners: Vec<String> = entries.iter()
+        .map(|entry| {
+            let parts: Vec<&str> = entry.split('.').collect();
+            assert_eq!(parts.len(), 5, "unexpected file {}", entry);
+            assert_eq!((parts[0], parts[1], parts[4]), ("", "file", "tmp"));
+            parts[2].to_owned()
+        })
+        .collect();
+    for owner in &owners {
+        assert!(children.iter().any(|child| &format!("{}", child) == owner));
+    }
+    let leftovers = owners.len();
+    owners.dedup();
+    assert_eq!(owners.len(), leftovers);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn test_atomic_write_without_proc() {
+    use nix::mount::{mount, MsFlags};
+    use nix::sched::CloneFlags;
+
+    let tempdir = TempDir::new("nix-test_atomic_write_without_proc").unwrap();
+    let path = tempdir.path().join("file");
+    atomic_write(&path, b"old", Mode::from_bits_truncate(0o600)).unwrap();
+
+    // `atomic_write` itself allocates, unlike the rest of the child.
+    let written = ::in_user_namespace("test_atomic_write_without_proc", CloneFlags::CLONE_NEWNS, || {
+        // Hide /proc, so that an `O_TMPFILE` file can't be linked
+        const NONE: Option<&'static [u8]> = None;
+        if mount(NONE, "/proc", Some(b"tmpfs".as_ref()), MsFlags::empty(), NONE).is_err() {
+            1
+        } else if atomic_write(&path, b"new contents", Mode::from_bits_truncate(0o600)).is_err() {
+            3
+        } else {
+            0
+        }
+    });
+    if !written {
+        return;
+    }
+
+    let mut contents = Vec::new();
+    File::open(&path).unwrap().read_to_end(&mut contents).unwrap();
+    assert_eq!(contents, b"new contents");
+    assert_eq!(dir_entries(tempdir.path()), vec!["file"]);
+}
+
 #[cfg(any(target_os = "linux", target_os = "android"))]
 mod linux_android {
+    use std::fs::OpenOptions;
     use std::io::prelude::*;
+    use std::mem;
     use std::os::unix::prelude::*;
 
     use libc::loff_t;
 
     use nix::fcntl::{SpliceFFlags, FallocateFlags, fallocate, splice, tee, vmsplice};
+    use nix::fcntl::{fcntl, FcntlArg, FileLock};
     use nix::sys::uio::IoVec;
     use nix::unistd::{close, pipe, read, write};
 
@@ -142,4 +456,186 @@ mod linux_android {
         let mut buf = [0u8; 200];
         assert_eq!(100, read(fd, &mut buf).unwrap());
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sync_file_range() {
+        use nix::Error;
+        use nix::errno::Errno;
+        use nix::fcntl::{sync_file_range, SyncFileRangeFlags};
+        use nix::fcntl::{open, OFlag};
+        use nix::sys::stat::Mode;
+        use nix::unistd::syncfs;
+        use tempdir::TempDir;
+
+        let dir = TempDir::new("nix-test_sync_file_range").unwrap();
+        let mut file = ::std::fs::File::create(dir.path().join("data")).unwrap();
+        file.write_all(&[0xa5; 8192]).unwrap();
+
+        let flags = SyncFileRangeFlags::SYNC_FILE_RANGE_WRITE |
+                    SyncFileRangeFlags::SYNC_FILE_RANGE_WAIT_AFTER;
+        sync_file_range(file.as_raw_fd(), 0, 4096, flags).unwrap();
+
+        let dirfd = open(dir.path(), OFlag::O_RDONLY | OFlag::O_DIRECTORY, Mode::empty()).unwrap();
+        syncfs(dirfd).unwrap();
+        close(dirfd).unwrap();
+
+        // Other tests may reuse the numbers of closed descriptors, so use one
+        // that can never be valid.
+        assert_eq!(syncfs(-1), Err(Error::Sys(Errno::EBADF)));
+        assert_eq!(sync_file_range(-1, 0, 4096, flags), Err(Error::Sys(Errno::EBADF)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_openat2() {
+        use std::io;
+        use nix::Error;
+        use nix::errno::Errno;
+        use nix::fcntl::{open, openat, openat2, OFlag, OpenHow, ResolveFlags};
+        use nix::sys::stat::Mode;
+        use tempdir::TempDir;
+
+        let tempdir = TempDir::new("nix-test_openat2").unwrap();
+        ::std::fs::create_dir(tempdir.path().join("dir")).unwrap();
+        ::std::fs::File::create(tempdir.path().join("dir/file")).unwrap();
+        ::std::os::unix::fs::symlink("/etc/passwd", tempdir.path().join("dir/link")).unwrap();
+
+        let dirfd = open(&tempdir.path().join("dir"), OFlag::O_PATH | OFlag::O_DIRECTORY,
+                         Mode::empty()).unwrap();
+        let beneath = OpenHow::new()
+            .flags(OFlag::O_RDONLY | OFlag::O_CLOEXEC)
+            .resolve(ResolveFlags::RESOLVE_BENEATH);
+
+        match openat2(dirfd, "file", beneath) {
+            Err(Error::Sys(Errno::ENOSYS)) => {
+                let stderr = io::stderr();
+                let mut handle = stderr.lock();
+                writeln!(handle, "openat2 is not supported. Skipping test.").unwrap();
+            },
+            res => {
+                close(res.unwrap()).unwrap();
+
+                // The symlink escapes the directory
+                assert_eq!(openat2(dirfd, "link", beneath), Err(Error::Sys(Errno::EXDEV)));
+                assert_eq!(openat2(dirfd, "../dir/file", beneath), Err(Error::Sys(Errno::EXDEV)));
+                let no_symlinks = beneath.resolve(ResolveFlags::RESOLVE_NO_SYMLINKS);
+                assert_eq!(openat2(dirfd, "link", no_symlinks), Err(Error::Sys(Errno::ELOOP)));
+
+                // A mode without O_CREAT is invalid
+                let bad_mode = beneath.mode(Mode::S_IRUSR);
+                assert_eq!(openat2(dirfd, "file", bad_mode), Err(Error::Sys(Errno::EINVAL)));
+
+                // O_PATH descriptors can be opened beneath the directory too
+                let path_how = OpenHow::new().flags(OFlag::O_PATH).resolve(ResolveFlags::RESOLVE_BENEATH);
+                close(openat2(dirfd, "file", path_how).unwrap()).unwrap();
+            },
+        }
+
+        // Without restrictions the link is followed
+        let fd = openat(dirfd, "link", OFlag::O_RDONLY, Mode::empty()).unwrap();
+        close(fd).unwrap();
+        close(dirfd).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_openat2_size() {
+        use std::io;
+        use nix::Error;
+        use nix::errno::Errno;
+        use nix::fcntl::{openat2, OFlag, OpenHow};
+
+        // Whether the kernel is older or newer than libc's `open_how`, the
+        // zeroed fields of an `OpenHow` must never be rejected with `E2BIG`.
+        let how = OpenHow::new().flags(OFlag::O_RDONLY | OFlag::O_DIRECTORY | OFlag::O_CLOEXEC);
+        match openat2(::libc::AT_FDCWD, "/", how) {
+            Ok(fd) => close(fd).unwrap(),
+            Err(Error::Sys(Errno::ENOSYS)) => {
+                let stderr = io::stderr();
+                let mut handle = stderr.lock();
+                writeln!(handle, "openat2 is not supported. Skipping test.").unwrap();
+            },
+            Err(e) => panic!("openat2 failed: {:?}", e),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_preload_file_range() {
+        use std::ptr;
+        use libc::{self, c_void};
+        use nix::Error;
+        use nix::errno::Errno;
+        use nix::fcntl::{preload_file_range, readahead};
+        use nix::sys::mman::{mincore, mmap, munmap, MapFlags, ProtFlags};
+        use nix::unistd::{sysconf, SysconfVar};
+        use std::thread;
+        use std::time::Duration;
+
+        const LEN: usize = 1 << 20;
+        let mut file = tempfile().unwrap();
+        file.write_all(&[0xa5; LEN]).unwrap();
+        file.sync_all().unwrap();
+        let fd = file.as_raw_fd();
+
+        // Evict the now clean pages where the filesystem allows it.
+        unsafe { libc::posix_fadvise(fd, 0, LEN as libc::off_t, libc::POSIX_FADV_DONTNEED) };
+
+        let addr = unsafe {
+            mmap(ptr::null_mut(), LEN, ProtFlags::PROT_READ, MapFlags::MAP_SHARED, fd, 0)
+        }.unwrap();
+        let resident = |addr: *mut c_void| {
+            mincore(addr, LEN).unwrap().iter().filter(|&&r| r).count()
+        };
+        preload_file_range(fd, 0, LEN).unwrap();
+        // The reads are only started, so give them some time to complete
+        // before checking that the whole range is in the page cache.
+        let pages = LEN / sysconf(SysconfVar::PAGE_SIZE).unwrap().unwrap() as usize;
+        let mut after = resident(addr);
+        for _ in 0..100 {
+            if after == pages {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+            after = resident(addr);
+        }
+        unsafe { munmap(addr, LEN) }.unwrap();
+
+        assert_eq!(after, pages);
+
+        let (rd, wr) = pipe().unwrap();
+        assert_eq!(readahead(rd, 0, 4096), Err(Error::Sys(Errno::EINVAL)));
+        assert_eq!(preload_file_range(rd, 0, 4096), Err(Error::Sys(Errno::EINVAL)));
+        assert_eq!(readahead(wr, 0, 4096), Err(Error::Sys(Errno::EBADF)));
+        close(rd).unwrap();
+        close(wr).unwrap();
+    }
+
+    #[test]
+    fn test_file_lock_range() {
+        let tmp = NamedTempFile::new().unwrap();
+        // Write locks need a file open for writing
+        let file = OpenOptions::new().write(true).open(tmp.path()).unwrap();
+        let other = OpenOptions::new().write(true).open(tmp.path()).unwrap();
+        let getlk = |start, len| {
+            let mut lock: ::libc::flock = unsafe { mem::zeroed() };
+            lock.l_type = ::libc::F_WRLCK as _;
+            lock.l_whence = ::libc::SEEK_SET as _;
+            lock.l_start = start;
+            lock.l_len = len;
+            fcntl(other.as_raw_fd(), FcntlArg::F_OFD_GETLK(&mut lock)).unwrap();
+            lock.l_type as ::libc::c_int
+        };
+
+        let lock = FileLock::exclusive_range(&file, 10, 10).unwrap();
+        assert_eq!(getlk(15, 1), ::libc::F_WRLCK);
+        assert_eq!(getlk(0, 10), ::libc::F_UNLCK);
+        assert_eq!(getlk(20, 0), ::libc::F_UNLCK);
+        // Byte-range locks don't interact with whole-file ones
+        assert!(FileLock::try_exclusive(&other).unwrap().is_some());
+
+        lock.unlock().unwrap();
+        assert_eq!(getlk(15, 1), ::libc::F_UNLCK);
+    }
 }
@@ -0,0 +1,50 @@
+use nix::fcntl::{fcntl, FcntlArg, FdFlag, OFlag};
+use nix::fd::OwnedFd;
+use nix::unistd::{pipe2_owned, read, write};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+#[test]
+fn test_try_clone() {
+    // A child forked by another test would keep the write end open.
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let (reader, writer) = pipe2_owned(OFlag::O_CLOEXEC).unwrap();
+    let clone = writer.try_clone().unwrap();
+    assert!(clone.as_raw_fd() != writer.as_raw_fd());
+    let flags = fcntl(clone.as_raw_fd(), FcntlArg::F_GETFD).unwrap();
+    assert!(FdFlag::from_bits_truncate(flags).contains(FdFlag::FD_CLOEXEC));
+
+    // The clone keeps the pipe open after the original is closed.
+    drop(writer);
+    assert_eq!(write(clone.as_raw_fd(), b"abc").unwrap(), 3);
+    let mut buf = [0u8; 3];
+    assert_eq!(read(reader.as_raw_fd(), &mut buf).unwrap(), 3);
+    assert_eq!(&buf, b"abc");
+
+    // Closing the last write end makes the read end see end of file.
+    drop(clone);
+    assert_eq!(read(reader.as_raw_fd(), &mut buf).unwrap(), 0);
+}
+
+#[test]
+fn test_into_raw_fd() {
+    // A child forked by another test would keep the write end open.
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let (reader, writer) = pipe2_owned(OFlag::O_CLOEXEC).unwrap();
+    let fd = writer.into_raw_fd();
+
+    // The file descriptor must still be open.
+    assert_eq!(write(fd, b"x").unwrap(), 1);
+    let writer = unsafe { OwnedFd::from_raw_fd(fd) };
+    drop(writer);
+    let mut buf = [0u8; 2];
+    assert_eq!(read(reader.as_raw_fd(), &mut buf).unwrap(), 1);
+    assert_eq!(read(reader.as_raw_fd(), &mut buf).unwrap(), 0);
+}
+
+#[test]
+fn test_drop_invalid() {
+    // EBADF from close is ignored.
+    drop(unsafe { OwnedFd::from_raw_fd(-1) });
+}
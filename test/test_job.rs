@@ -0,0 +1,77 @@
+use libc::_exit;
+use nix::job::give_terminal_to;
+use nix::pty::openpty;
+use nix::sys::termios::tiocsctty;
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{close, fork, getpgid, getpgrp, getsid, pipe, read, setpgid, setsid, tcgetpgrp,
+                  write, Pid};
+use nix::unistd::ForkResult::*;
+use std::os::unix::io::RawFd;
+
+#[test]
+fn test_give_terminal_to() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    #[allow(unused_variables)]
+    let p = ::PTSNAME_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let pty = openpty(None, None).unwrap();
+
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            let code = if setsid().is_err() || tiocsctty(pty.slave, false).is_err() {
+                1
+            } else {
+                shell(pty.slave)
+            };
+            unsafe { _exit(code) }
+        },
+        Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+
+    close(pty.master).unwrap();
+    close(pty.slave).unwrap();
+
+    // Runs a job in the session of the terminal `tty` like a shell would.
+    // The exit status says which check failed.
+    fn shell(tty: RawFd) -> i32 {
+        let (r, w) = match pipe() {
+            Ok(fds) => fds,
+            Err(_) => return 2,
+        };
+        let job = match fork() {
+            Ok(Child) => {
+                let _ = setpgid(Pid::from_raw(0), Pid::from_raw(0));
+                let mut buf = [0u8; 1];
+                let _ = read(r, &mut buf);
+                unsafe { _exit(0) }
+            },
+            Ok(Parent { child }) => child,
+            Err(_) => return 3,
+        };
+
+        // The job may or may not have set its group already
+        if setpgid(job, job).is_err() || getpgid(Some(job)) != Ok(job) {
+            return 4;
+        }
+        if getsid(Some(job)) != getsid(None) {
+            return 5;
+        }
+        if give_terminal_to(job, tty).is_err() || tcgetpgrp(tty) != Ok(job) {
+            return 6;
+        }
+        // In the background now, so taking the terminal back must not stop
+        // the shell
+        if give_terminal_to(getpgrp(), tty).is_err() || tcgetpgrp(tty) != Ok(getpgrp()) {
+            return 7;
+        }
+
+        let _ = write(w, b"x");
+        match waitpid(job, None) {
+            Ok(WaitStatus::Exited(_, 0)) => 0,
+            _ => 8,
+        }
+    }
+}
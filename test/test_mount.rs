@@ -18,10 +18,13 @@ mod test_mount {
     use libc::{EACCES, EROFS};
 
     use nix::errno::Errno;
-    use nix::mount::{mount, umount, MsFlags};
+    use nix::mount::{bind, bind_readonly, make_private_recursive, make_rshared, mount, umount,
+                     MsFlags};
     use nix::sched::{unshare, CloneFlags};
     use nix::sys::stat::{self, Mode};
-    use nix::unistd::getuid;
+    use nix::sys::statvfs::{statvfs, FsFlags};
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{close, fork, getuid, pipe, read, write, ForkResult};
 
     use tempdir::TempDir;
 
@@ -182,6 +185,94 @@ exit 23";
         assert_eq!(buf, SCRIPT_CONTENTS);
     }
 
+    pub fn test_bind_readonly_disallows_write() {
+        let tempdir = TempDir::new("nix-test_mount")
+                          .unwrap_or_else(|e| panic!("tempdir failed: {}", e));
+        let mount_point = TempDir::new("nix-test_mount")
+                              .unwrap_or_else(|e| panic!("tempdir failed: {}", e));
+        File::create(tempdir.path().join("test"))
+            .and_then(|mut f| f.write(SCRIPT_CONTENTS))
+            .unwrap_or_else(|e| panic!("write failed: {}", e));
+
+        bind_readonly(tempdir.path(), mount_point.path())
+            .unwrap_or_else(|e| panic!("bind_readonly failed: {}", e));
+
+        // EROFS: Read-only file system
+        assert_eq!(EROFS as i32,
+                   File::create(mount_point.path().join("new")).unwrap_err().raw_os_error().unwrap());
+        assert!(statvfs(mount_point.path()).unwrap().flags().contains(FsFlags::ST_RDONLY));
+
+        let mut buf = Vec::new();
+        File::open(mount_point.path().join("test"))
+            .and_then(|mut f| f.read_to_end(&mut buf))
+            .unwrap_or_else(|e| panic!("read failed: {}", e));
+        assert_eq!(buf, SCRIPT_CONTENTS);
+
+        // The source is still writable
+        File::create(tempdir.path().join("new")).unwrap_or_else(|e| panic!("create failed: {}", e));
+
+        umount(mount_point.path()).unwrap_or_else(|e| panic!("umount failed: {}", e));
+    }
+
+    /// Whether a mount below a shared mount shows up in a new mount namespace,
+    /// optionally after making its copy of the mount private.
+    fn mount_propagates(make_private: bool) -> bool {
+        let tempdir = TempDir::new("nix-test_mount")
+                          .unwrap_or_else(|e| panic!("tempdir failed: {}", e));
+        let sub = tempdir.path().join("sub");
+        fs::create_dir(&sub).unwrap_or_else(|e| panic!("mkdir failed: {}", e));
+
+        bind(tempdir.path(), tempdir.path(), false).unwrap_or_else(|e| panic!("bind failed: {}", e));
+        make_rshared(tempdir.path()).unwrap_or_else(|e| panic!("make_rshared failed: {}", e));
+
+        let (ready_reader, ready_writer) = pipe().unwrap();
+        let (mounted_reader, mounted_writer) = pipe().unwrap();
+        let mut buf = [0u8; 1];
+
+        let propagated = match fork().unwrap_or_else(|e| panic!("fork failed: {}", e)) {
+            ForkResult::Child => {
+                unshare(CloneFlags::CLONE_NEWNS).unwrap_or_else(|e| panic!("unshare failed: {}", e));
+                if make_private {
+                    make_private_recursive(tempdir.path())
+                        .unwrap_or_else(|e| panic!("make_private_recursive failed: {}", e));
+                }
+                write(ready_writer, b"r").unwrap();
+                read(mounted_reader, &mut buf).unwrap();
+
+                let code = if stat::stat(&sub).unwrap().st_dev == stat::stat(tempdir.path()).unwrap().st_dev {
+                    0
+                } else {
+                    1
+                };
+                process::exit(code);
+            },
+            ForkResult::Parent { child } => {
+                read(ready_reader, &mut buf).unwrap();
+                mount(NONE, &sub, Some(b"tmpfs".as_ref()), MsFlags::empty(), NONE)
+                    .unwrap_or_else(|e| panic!("mount failed: {}", e));
+                write(mounted_writer, b"m").unwrap();
+
+                match waitpid(child, None) {
+                    Ok(WaitStatus::Exited(_, code)) => code == 1,
+                    status => panic!("child failed: {:?}", status),
+                }
+            },
+        };
+
+        for fd in &[ready_reader, ready_writer, mounted_reader, mounted_writer] {
+            close(*fd).unwrap();
+        }
+        umount(&sub).unwrap_or_else(|e| panic!("umount failed: {}", e));
+        umount(tempdir.path()).unwrap_or_else(|e| panic!("umount failed: {}", e));
+
+        propagated
+    }
+
+    pub fn test_make_private_recursive_stops_propagation() {
+        assert!(mount_propagates(false), "mounts should propagate between shared peers");
+        assert!(!mount_propagates(true), "mounts shouldn't propagate to a private mount");
+    }
+
     pub fn setup_namespaces() {
         // Hold on to the uid in the parent namespace.
         let uid = getuid();
@@ -229,13 +320,16 @@ macro_rules! run_tests {
 fn main() {
     use test_mount::{setup_namespaces, test_mount_tmpfs_without_flags_allows_rwx,
                      test_mount_rdonly_disallows_write, test_mount_noexec_disallows_exec,
-                     test_mount_bind};
+                     test_mount_bind, test_bind_readonly_disallows_write,
+                     test_make_private_recursive_stops_propagation};
     setup_namespaces();
 
     run_tests!(test_mount_tmpfs_without_flags_allows_rwx,
                test_mount_rdonly_disallows_write,
                test_mount_noexec_disallows_exec,
-               test_mount_bind);
+               test_mount_bind,
+               test_bind_readonly_disallows_write,
+               test_make_private_recursive_stops_propagation);
 }
 
 #[cfg(not(target_os = "linux"))]
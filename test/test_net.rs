@@ -10,3 +10,89 @@ const LOOPBACK: &[u8] = b"lo0";
 fn test_if_nametoindex() {
     assert!(if_nametoindex(&LOOPBACK[..]).is_ok());
 }
+
+#[test]
+fn test_if_indextoname() {
+    let index = if_nametoindex(&LOOPBACK[..]).unwrap();
+    assert_eq!(if_indextoname(index).unwrap().as_bytes(), LOOPBACK);
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::net::if_::*;
+
+    #[test]
+    fn test_get_interface_mtu() {
+        let mtu = get_interface_mtu("lo").unwrap();
+        assert!(mtu > 0);
+    }
+
+    #[test]
+    fn test_get_interface_hwaddr() {
+        assert_eq!(get_interface_hwaddr("lo"), Ok([0; 6]));
+    }
+
+    #[test]
+    fn test_interface_ioctl_errors() {
+        assert_eq!(get_interface_mtu("nixbogus0"), Err(Error::Sys(Errno::ENODEV)));
+        assert_eq!(get_interface_hwaddr("nixbogus0"), Err(Error::Sys(Errno::ENODEV)));
+        // Names that don't fit into an ifreq are rejected rather than truncated
+        assert_eq!(get_interface_mtu("lo_with_a_very_long_name"), Err(Error::Sys(Errno::EINVAL)));
+        assert_eq!(get_flags("nixbogus0"), Err(Error::Sys(Errno::ENODEV)));
+        assert_eq!(get_flags("lo_with_a_very_long_name"), Err(Error::Sys(Errno::EINVAL)));
+        assert_eq!(set_flags("lo_with_a_very_long_name", InterfaceFlags::IFF_UP),
+                   Err(Error::Sys(Errno::EINVAL)));
+    }
+
+    #[test]
+    fn test_get_flags() {
+        let flags = get_flags("lo").unwrap();
+        assert!(flags.contains(InterfaceFlags::IFF_LOOPBACK));
+    }
+
+    #[test]
+    fn test_bring_up_in_net_namespace() {
+        use std::net::SocketAddr;
+        use std::str::FromStr;
+        use nix::sched::CloneFlags;
+        use nix::sys::socket::{bind, connect, getsockname, recv, send, socket, AddressFamily,
+                               InetAddr, MsgFlags, SockAddr, SockFlag, SockType};
+        use nix::unistd::close;
+
+        let addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+        let localhost = SockAddr::new_inet(InetAddr::from_std(&addr));
+
+        ::in_user_namespace("test_bring_up_in_net_namespace", CloneFlags::CLONE_NEWNET, || {
+            // The loopback interface of a new namespace starts out down
+            match get_flags("lo") {
+                Ok(flags) if !flags.contains(InterfaceFlags::IFF_UP) => (),
+                _ => return 1,
+            }
+            if bring_up("lo").is_err() {
+                return 3;
+            }
+            let running = InterfaceFlags::IFF_UP | InterfaceFlags::IFF_RUNNING;
+            match get_flags("lo") {
+                Ok(flags) if flags.contains(running) => (),
+                _ => return 4,
+            }
+
+            // Datagrams can be sent over it now
+            let sock = match socket(AddressFamily::Inet, SockType::Datagram, SockFlag::empty(),
+                                    None) {
+                Ok(sock) => sock,
+                Err(_) => return 5,
+            };
+            let mut buf = [0u8; 4];
+            let works = bind(sock, &localhost).is_ok() &&
+                getsockname(sock).and_then(|addr| connect(sock, &addr)).is_ok() &&
+                send(sock, b"ping", MsgFlags::empty()) == Ok(4) &&
+                recv(sock, &mut buf, MsgFlags::empty()) == Ok(4) &&
+                &buf == b"ping";
+            let _ = close(sock);
+            if works { 0 } else { 6 }
+        });
+    }
+}
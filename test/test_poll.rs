@@ -21,6 +21,21 @@ fn test_poll() {
     assert!(fds[0].revents().unwrap().contains(EventFlags::POLLIN));
 }
 
+#[test]
+fn test_poll_from_fd() {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let (mut a, b) = UnixStream::pair().unwrap();
+    let mut fds = [PollFd::from_fd(&b, EventFlags::POLLIN)];
+
+    assert_eq!(poll(&mut fds, 100).unwrap(), 0);
+
+    a.write_all(b".").unwrap();
+    assert_eq!(poll(&mut fds, 100).unwrap(), 1);
+    assert!(fds[0].revents().unwrap().contains(EventFlags::POLLIN));
+}
+
 #[test]
 fn test_poll_debug() {
     assert_eq!(format!("{:?}", PollFd::new(0, EventFlags::empty())),
@@ -0,0 +1,78 @@
+use libc::_exit;
+use nix::fcntl::{open, OFlag};
+use nix::process::set_proc_title;
+use nix::sys::stat::Mode;
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{close, fork, read};
+use nix::unistd::ForkResult::*;
+
+// Read the command line into `buf`, without the NUL padding left after the
+// title.  Returns `None` if it can't be read or doesn't fit.
+fn cmdline(buf: &mut [u8]) -> Option<&[u8]> {
+    let fd = match open("/proc/self/cmdline", OFlag::O_RDONLY, Mode::empty()) {
+        Ok(fd) => fd,
+        Err(_) => return None,
+    };
+    let mut len = 0;
+    while len < buf.len() {
+        match read(fd, &mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(_) => break,
+        }
+    }
+    let full = len == buf.len();
+    if close(fd).is_err() || full {
+        return None;
+    }
+    while len > 0 && buf[len - 1] == 0 {
+        len -= 1;
+    }
+    Some(&buf[..len])
+}
+
+#[test]
+fn test_set_proc_title() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // The child starts with the same arguments as this process.
+    let mut buf = vec![0u8; 64 * 1024];
+    let original_len = cmdline(&mut buf).expect("Can't read /proc/self/cmdline").len();
+    let long = "x".repeat(original_len + 4096);
+
+    // Change the title in a child to leave the arguments of the test harness
+    // alone.  The exit status says which check failed.  Apart from the first
+    // `set_proc_title` reading `/proc/self/stat` into a `Vec`, the child
+    // doesn't allocate.
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            if set_proc_title("nix-test: busy").is_err() {
+                unsafe { _exit(1) }
+            }
+            if cmdline(&mut buf) != Some(&b"nix-test: busy"[..]) {
+                unsafe { _exit(2) }
+            }
+
+            // No trace of the longer title may remain.
+            if set_proc_title("idle").is_err() || cmdline(&mut buf) != Some(&b"idle"[..]) {
+                unsafe { _exit(3) }
+            }
+
+            // An overlong title is truncated to the original arguments.
+            if set_proc_title(&long).is_err() {
+                unsafe { _exit(4) }
+            }
+            match cmdline(&mut buf) {
+                Some(truncated) if truncated.len() <= original_len &&
+                    long.as_bytes().starts_with(truncated) => (),
+                _ => unsafe { _exit(5) },
+            }
+
+            unsafe { _exit(0) }
+        },
+        Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+}
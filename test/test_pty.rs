@@ -207,3 +207,64 @@ fn test_openpty_with_termios() {
     close(pty.master).unwrap();
     close(pty.slave).unwrap();
 }
+
+#[test]
+fn test_controlling_terminal() {
+    use libc::_exit;
+    use nix::errno::Errno;
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{fork, getsid, setsid};
+    use nix::unistd::ForkResult::*;
+    use nix::Error;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    #[allow(unused_variables)]
+    let p = ::PTSNAME_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let pty = openpty(None, None).unwrap();
+
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            let sid = setsid().unwrap();
+            let code = if tiocsctty(pty.slave, false).is_err() {
+                1
+            } else if tcgetsid(pty.slave) != Ok(sid) || getsid(None) != Ok(sid) {
+                2
+            } else {
+                vhangup_child(pty.slave)
+            };
+            unsafe { _exit(code) }
+        },
+        Parent { child } => {
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+
+    close(pty.master).unwrap();
+    close(pty.slave).unwrap();
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    fn vhangup_child(slave: RawFd) -> i32 {
+        use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+        use nix::unistd::{geteuid, vhangup};
+
+        if !geteuid().is_root() {
+            return if vhangup() == Err(Error::Sys(Errno::EPERM)) { 0 } else { 3 };
+        }
+
+        // The hangup also sends SIGHUP to the foreground process group.
+        let ignore = SigAction::new(SigHandler::SigIgn, SaFlags::empty(), SigSet::empty());
+        unsafe { sigaction(Signal::SIGHUP, &ignore) }.unwrap();
+        if vhangup().is_err() {
+            return 4;
+        }
+        // Descriptors of the terminal are unusable after a hangup.
+        if write(slave, b"x") == Err(Error::Sys(Errno::EIO)) { 0 } else { 5 }
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    fn vhangup_child(_slave: RawFd) -> i32 {
+        0
+    }
+}
@@ -0,0 +1,124 @@
+use nix::mount::{mount, MsFlags};
+use nix::sched::{CloneCb, NamespaceBuilder, NamespaceError, NamespaceStep};
+use nix::sched::{pin_to_cpu, sched_getaffinity, sched_getcpu, CloneFlags, CloneStack, CpuSet};
+use nix::sys::signal::SIGSEGV;
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::fcntl::{open, OFlag};
+use nix::sys::stat::Mode;
+use nix::sys::utsname::uname;
+use nix::unistd::{close, getuid, Pid};
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use tempdir::TempDir;
+
+#[test]
+fn test_namespace_builder() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let tempdir = TempDir::new("nix-test_namespace_builder").unwrap();
+    let mnt = tempdir.path().join("mnt");
+    fs::create_dir(&mnt).unwrap();
+
+    let child_mnt = mnt.clone();
+    let inside = mnt.join("inside");
+    let child_inside = inside.clone();
+    // The callback runs in the cloned child, so it only reports which check
+    // failed through its exit status and doesn't allocate.
+    let cb: CloneCb = Box::new(move || {
+        if !getuid().is_root() {
+            return 2;
+        }
+        if mount(Some("tmpfs"), &child_mnt, Some("tmpfs"), MsFlags::empty(),
+                 None::<&'static str>).is_err() {
+            return 3;
+        }
+        match open(&child_inside, OFlag::O_CREAT | OFlag::O_WRONLY, Mode::S_IRUSR) {
+            Ok(fd) => if close(fd).is_err() {
+                return 4;
+            },
+            Err(_) => return 4,
+        }
+        match uname() {
+            Ok(uts) => if uts.nodename() != OsStr::new("nix-test") {
+                return 5;
+            },
+            Err(_) => return 5,
+        }
+        0
+    });
+
+    let builder = NamespaceBuilder::new().user(true).mount().uts(Some("nix-test"));
+    let child = match builder.spawn(cb, 1024 * 1024) {
+        Ok(child) => child,
+        Err(NamespaceError { step: NamespaceStep::Clone, error }) => {
+            let stderr = io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "clone failed: {}. Are unprivileged user namespaces available?",
+                     error).unwrap();
+            writeln!(handle, "test_namespace_builder requires user namespaces. Skipping test.")
+                .unwrap();
+            return;
+        }
+        Err(e) => panic!("spawn failed: {}", e),
+    };
+
+    assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+    // The tmpfs was only mounted in the child's mount namespace.
+    assert!(!inside.exists());
+}
+
+fn cpus(cpuset: &CpuSet) -> Vec<usize> {
+    (0..8 * ::std::mem::size_of::<CpuSet>()).filter(|&cpu| cpuset.is_set(cpu).unwrap()).collect()
+}
+
+#[test]
+fn test_pin_to_cpu() {
+    let original = sched_getaffinity(Pid::from_raw(0)).unwrap();
+    if !original.is_set(0).unwrap() {
+        let stderr = io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_pin_to_cpu requires being allowed to run on CPU 0. Skipping test.")
+            .unwrap();
+        return;
+    }
+
+    {
+        let guard = pin_to_cpu(0).unwrap();
+        assert_eq!(cpus(guard.original()), cpus(&original));
+        assert_eq!(cpus(&sched_getaffinity(Pid::from_raw(0)).unwrap()), vec![0]);
+        for _ in 0..10000 {
+            assert_eq!(sched_getcpu(), Ok(0));
+        }
+    }
+
+    assert_eq!(cpus(&sched_getaffinity(Pid::from_raw(0)).unwrap()), cpus(&original));
+}
+
+#[test]
+fn test_clone_stack_guard_page() {
+    use std::ptr;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // Each frame holds a buffer that the callee reads, so that the optimizer
+    // can neither remove the buffers nor turn the recursion into a loop.
+    fn recurse(n: usize, caller: &[u8; 1024]) -> usize {
+        let buf = [n as u8; 1024];
+        let byte = unsafe { ptr::read_volatile(&caller[0]) } as usize;
+        if n == 0 { byte } else { recurse(n - 1, &buf) + byte }
+    }
+
+    let mut stack = CloneStack::new(64 * 1024).unwrap();
+    assert_eq!(stack.as_mut_slice().len(), 64 * 1024);
+    let cb: CloneCb = Box::new(|| recurse(1024 * 1024, &[0; 1024]) as isize);
+    let (child, stack) = stack.spawn(cb, CloneFlags::empty(), Some(::libc::SIGCHLD)).unwrap();
+
+    match waitpid(child, None) {
+        Ok(WaitStatus::Signaled(pid, SIGSEGV, _)) => assert_eq!(pid, child),
+        status => panic!("child overflowing its stack ended with {:?}", status),
+    }
+    unsafe { stack.release() }.unwrap();
+}
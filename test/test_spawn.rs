@@ -0,0 +1,129 @@
+use std::ffi::CString;
+
+use nix::spawn::*;
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{close, pipe};
+
+fn cstrings(args: &[&str]) -> Vec<CString> {
+    args.iter().map(|s| CString::new(*s).unwrap()).collect()
+}
+
+#[test]
+fn test_posix_spawn_dup2_stdout() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let (r, w) = pipe().unwrap();
+    let mut file_actions = PosixSpawnFileActions::new().unwrap();
+    file_actions.add_dup2(w, 1).unwrap();
+    file_actions.add_close(r).unwrap();
+    file_actions.add_close(w).unwrap();
+    let attr = PosixSpawnAttr::new().unwrap();
+
+    let args = cstrings(&["sh", "-c", "echo hello"]);
+    let path = CString::new("/bin/sh").unwrap();
+    let pid = posix_spawn(&path, &file_actions, &attr, &args, &[]).unwrap();
+    close(w).unwrap();
+
+    let mut buf = [0u8; 6];
+    ::read_exact(r, &mut buf);
+    assert_eq!(&buf, b"hello\n");
+    close(r).unwrap();
+
+    assert_eq!(waitpid(pid, None), Ok(WaitStatus::Exited(pid, 0)));
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+#[test]
+fn test_posix_spawn_chdir() {
+    use std::fs;
+    use std::io::{self, Read, Write};
+    use std::os::unix::io::FromRawFd;
+    use nix::Error;
+    use nix::errno::Errno;
+    use tempdir::TempDir;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let tempdir = TempDir::new("nix-test_posix_spawn_chdir").unwrap();
+    let mut file_actions = PosixSpawnFileActions::new().unwrap();
+    match file_actions.add_chdir(tempdir.path()) {
+        Ok(()) => (),
+        Err(Error::Sys(Errno::ENOSYS)) => {
+            let stderr = io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "add_chdir requires glibc 2.29. Skipping test.").unwrap();
+            return;
+        },
+        Err(e) => panic!("add_chdir failed: {}", e),
+    }
+    let (r, w) = pipe().unwrap();
+    file_actions.add_dup2(w, 1).unwrap();
+    file_actions.add_close(r).unwrap();
+    file_actions.add_close(w).unwrap();
+    let attr = PosixSpawnAttr::new().unwrap();
+
+    let args = cstrings(&["pwd", "-P"]);
+    let pid = posix_spawnp(&args[0], &file_actions, &attr, &args, &[]).unwrap();
+    close(w).unwrap();
+
+    let mut output = String::new();
+    unsafe { fs::File::from_raw_fd(r) }.read_to_string(&mut output).unwrap();
+    assert_eq!(waitpid(pid, None), Ok(WaitStatus::Exited(pid, 0)));
+
+    let expected = fs::canonicalize(tempdir.path()).unwrap();
+    assert_eq!(output.trim_right(), expected.to_str().unwrap());
+}
+
+#[test]
+fn test_posix_spawnp_exit_status() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let file_actions = PosixSpawnFileActions::new().unwrap();
+    let attr = PosixSpawnAttr::new().unwrap();
+    let args = cstrings(&["sh", "-c", "exit 3"]);
+    let pid = posix_spawnp(&args[0], &file_actions, &attr, &args, &[]).unwrap();
+
+    assert_eq!(waitpid(pid, None), Ok(WaitStatus::Exited(pid, 3)));
+}
+
+#[test]
+fn test_posix_spawnattr_flags() {
+    let mut attr = PosixSpawnAttr::new().unwrap();
+    assert_eq!(attr.flags(), Ok(PosixSpawnFlags::empty()));
+    let flags = PosixSpawnFlags::POSIX_SPAWN_SETPGROUP | PosixSpawnFlags::POSIX_SPAWN_SETSIGMASK;
+    attr.set_flags(flags).unwrap();
+    assert_eq!(attr.flags(), Ok(flags));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_posix_spawn_setsid() {
+    use nix::unistd::{getsid, write};
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // The child blocks reading stdin until we are done inspecting it
+    let (r, w) = pipe().unwrap();
+    let mut file_actions = PosixSpawnFileActions::new().unwrap();
+    file_actions.add_dup2(r, 0).unwrap();
+    file_actions.add_close(r).unwrap();
+    file_actions.add_close(w).unwrap();
+    let mut attr = PosixSpawnAttr::new().unwrap();
+    attr.set_flags(PosixSpawnFlags::POSIX_SPAWN_SETSID).unwrap();
+
+    let args = cstrings(&["sh", "-c", "read x"]);
+    let path = CString::new("/bin/sh").unwrap();
+    let pid = posix_spawn(&path, &file_actions, &attr, &args, &[]).unwrap();
+    close(r).unwrap();
+
+    assert_eq!(getsid(Some(pid)), Ok(pid));
+    assert!(getsid(None).unwrap() != pid);
+
+    write(w, b"\n").unwrap();
+    close(w).unwrap();
+    assert_eq!(waitpid(pid, None), Ok(WaitStatus::Exited(pid, 0)));
+}
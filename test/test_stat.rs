@@ -5,9 +5,9 @@ use std::os::unix::prelude::AsRawFd;
 use libc::{S_IFMT, S_IFLNK};
 
 use nix::fcntl;
-use nix::sys::stat::{self, fchmod, fchmodat, fstat, lstat, stat};
-use nix::sys::stat::{FileStat, Mode, FchmodatFlags};
-use nix::unistd::chdir;
+use nix::sys::stat::{self, fchmod, fchmodat, fstat, lstat, stat, umask};
+use nix::sys::stat::{FileStat, Mode, FchmodatFlags, UmaskGuard};
+use nix::unistd::{chdir, close};
 use nix::Result;
 use tempdir::TempDir;
 
@@ -152,3 +152,25 @@ fn test_fchmodat() {
     let file_stat2 = stat(&fullpath).unwrap();
     assert_eq!(file_stat2.st_mode & 0o7777, mode2.bits());
 }
+
+#[test]
+fn test_umask_guard() {
+    #[allow(unused_variables)]
+    let m = ::UMASK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let tempdir = TempDir::new("nix-test_umask_guard").unwrap();
+    let path = tempdir.path().join("foo.txt");
+    let original = umask(Mode::from_bits_truncate(0o022));
+
+    {
+        let guard = UmaskGuard::new(Mode::from_bits_truncate(0o077));
+        assert_eq!(guard.previous(), Mode::from_bits_truncate(0o022));
+
+        let fd = fcntl::open(&path, fcntl::OFlag::O_CREAT | fcntl::OFlag::O_WRONLY,
+                             Mode::from_bits_truncate(0o666)).unwrap();
+        close(fd).unwrap();
+        assert_eq!(stat(&path).unwrap().st_mode & 0o7777, 0o600);
+    }
+
+    assert_eq!(umask(original), Mode::from_bits_truncate(0o022));
+}
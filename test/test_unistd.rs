@@ -1,5 +1,7 @@
 extern crate tempdir;
 
+use nix::Error;
+use nix::errno::Errno;
 use nix::fcntl::{fcntl, FcntlArg, FdFlag, OFlag};
 use nix::unistd::*;
 use nix::unistd::ForkResult::*;
@@ -43,6 +45,59 @@ fn test_fork_and_waitpid() {
     }
 }
 
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn test_dup3() {
+    let (r, w) = pipe().unwrap();
+
+    let fd = dup3(r, 42, OFlag::O_CLOEXEC).unwrap();
+    assert_eq!(fd, 42);
+    let flags = FdFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFD).unwrap());
+    assert!(flags.contains(FdFlag::FD_CLOEXEC));
+
+    // The duplicate refers to the same pipe
+    write(w, b"x").unwrap();
+    let mut buf = [0u8; 1];
+    assert_eq!(read(fd, &mut buf), Ok(1));
+    assert_eq!(&buf, b"x");
+
+    assert_eq!(dup3(r, r, OFlag::O_CLOEXEC), Err(Error::Sys(Errno::EINVAL)));
+
+    close(fd).unwrap();
+    close(r).unwrap();
+    close(w).unwrap();
+}
+
+#[test]
+fn test_fork_result_accessors() {
+    let child = Parent { child: Pid::from_raw(42) };
+    assert!(child.is_parent());
+    assert!(!child.is_child());
+    assert_eq!(child.child_pid(), Some(Pid::from_raw(42)));
+    assert!(Child.is_child());
+    assert_eq!(Child.child_pid(), None);
+}
+
+#[test]
+fn test_fork_and_run() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let child = fork_and_run(|| 7).unwrap();
+    assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 7)));
+}
+
+#[test]
+fn test_fork_and_run_panic() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    // If the child returned to the test harness after panicking, it would
+    // report the test result a second time and exit with a different code.
+    let child = fork_and_run(|| panic!("child panicked")).unwrap();
+    assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 101)));
+}
+
 #[test]
 fn test_wait() {
     // Grab FORK_MTX so wait doesn't reap a different test's child process
@@ -61,6 +116,614 @@ fn test_wait() {
     }
 }
 
+#[cfg(target_os = "linux")]
+mod pidfd {
+    use std::ffi::CString;
+    use std::io::Write;
+    use std::mem;
+
+    use libc::_exit;
+
+    use nix::Error;
+    use nix::errno::Errno;
+    use nix::poll::{poll, EventFlags, PollFd};
+    use nix::sys::memfd::{memfd_create, MemFdCreateFlag};
+    use nix::sys::uio::pread;
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::*;
+    use nix::unistd::ForkResult::*;
+
+    fn skip(name: &str, err: Error) {
+        let stderr = ::std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "{} failed with {:?}. Skipping test.", name, err).unwrap();
+    }
+
+    #[test]
+    fn test_pidfd_open() {
+        #[allow(unused_variables)]
+        let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+        let (r, w) = pipe().unwrap();
+        // Safe: Child only calls `close`, `read` and `_exit`, which are
+        // async-signal-safe.
+        match fork().expect("Error: Fork Failed") {
+            Child => {
+                // Wait until the parent closes its end of the pipe.
+                let mut buf = [0u8; 1];
+                let _ = close(w);
+                let _ = read(r, &mut buf);
+                unsafe { _exit(7) }
+            },
+            Parent { child } => {
+                close(r).unwrap();
+                let pidfd = match pidfd_open(child, 0) {
+                    Ok(fd) => fd,
+                    Err(e) => {
+                        close(w).unwrap();
+                        waitpid(child, None).unwrap();
+                        return skip("pidfd_open", e);
+                    }
+                };
+
+                // Not readable while the child is still running
+                let mut fds = [PollFd::new(pidfd, EventFlags::POLLIN)];
+                assert_eq!(poll(&mut fds, 0), Ok(0));
+
+                close(w).unwrap();
+                assert_eq!(poll(&mut fds, 10000), Ok(1));
+                assert!(fds[0].revents().unwrap().contains(EventFlags::POLLIN));
+
+                assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 7)));
+                close(pidfd).unwrap();
+            },
+        }
+    }
+
+    #[test]
+    fn test_pidfd_open_nonblock() {
+        match pidfd_open(getpid(), PIDFD_NONBLOCK) {
+            Ok(fd) => close(fd).unwrap(),
+            Err(e) => return skip("pidfd_open", e),
+        }
+        assert_eq!(pidfd_open(getpid(), 0xdead_0000), Err(Error::Sys(Errno::EINVAL)));
+    }
+
+    #[test]
+    fn test_pidfd_getfd() {
+        #[allow(unused_variables)]
+        let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+        let (fd_r, fd_w) = pipe().unwrap();
+        let (wait_r, wait_w) = pipe().unwrap();
+        let name = CString::new("test_pidfd_getfd").unwrap();
+        // Safe: Child only calls `close`, `memfd_create`, `write`, `read` and
+        // `_exit`, which are async-signal-safe.
+        match fork().expect("Error: Fork Failed") {
+            Child => {
+                let _ = close(wait_w);
+                let memfd = match memfd_create(&name, MemFdCreateFlag::empty()) {
+                    Ok(memfd) => memfd,
+                    Err(_) => unsafe { _exit(1) },
+                };
+                let raw: [u8; 4] = unsafe { mem::transmute(memfd) };
+                if write(memfd, b"stolen") != Ok(6) || write(fd_w, &raw) != Ok(4) {
+                    unsafe { _exit(1) }
+                }
+                // Keep the memfd open until the parent closes its end of the
+                // pipe.
+                let mut buf = [0u8; 1];
+                let _ = read(wait_r, &mut buf);
+                unsafe { _exit(0) }
+            },
+            Parent { child } => {
+                close(fd_w).unwrap();
+                close(wait_r).unwrap();
+                let mut raw = [0u8; 4];
+                ::read_exact(fd_r, &mut raw);
+                let targetfd: i32 = unsafe { mem::transmute(raw) };
+
+                let res = pidfd_open(child, 0)
+                    .and_then(|pidfd| {
+                        let res = pidfd_getfd(pidfd, targetfd, 0);
+                        close(pidfd).unwrap();
+                        res
+                    });
+                close(wait_w).unwrap();
+                assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+                close(fd_r).unwrap();
+
+                let fd = match res {
+                    Ok(fd) => fd,
+                    Err(e) => return skip("pidfd_getfd", e),
+                };
+                let mut buf = [0u8; 6];
+                assert_eq!(pread(fd, &mut buf, 0), Ok(6));
+                assert_eq!(&buf, b"stolen");
+                close(fd).unwrap();
+            },
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_close_range() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+    let pipes: Vec<_> = (0..4).map(|_| pipe().unwrap()).collect();
+
+    // Safe: Child only calls `close_range`, `fcntl` and `_exit`, which are
+    // async-signal-safe.
+    match fork().expect("Error: Fork Failed") {
+        Child => {
+            let code = match close_range(3, RawFd::max_value(), CloseRangeFlags::empty()) {
+                Err(Error::Sys(Errno::ENOSYS)) => 2,
+                Err(_) => 3,
+                Ok(()) => {
+                    let stdio_open = (0..3).all(|fd| fcntl(fd, FcntlArg::F_GETFD).is_ok());
+                    let rest_closed = (3..1024).all(|fd| {
+                        fcntl(fd, FcntlArg::F_GETFD) == Err(Error::Sys(Errno::EBADF))
+                    });
+                    if stdio_open && rest_closed { 0 } else { 1 }
+                }
+            };
+            unsafe { _exit(code) }
+        },
+        Parent { child } => {
+            match waitpid(child, None) {
+                Ok(WaitStatus::Exited(_, 0)) => (),
+                Ok(WaitStatus::Exited(_, 2)) => {
+                    let stderr = std::io::stderr();
+                    let mut handle = stderr.lock();
+                    writeln!(handle, "close_range is not supported. Skipping test.").unwrap();
+                },
+                status => panic!("child failed: {:?}", status),
+            }
+        },
+    }
+
+    for &(r, w) in &pipes {
+        close(r).unwrap();
+        close(w).unwrap();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_close_range_cloexec() {
+    let (r, w) = pipe().unwrap();
+
+    match close_range(r, r, CloseRangeFlags::CLOSE_RANGE_CLOEXEC) {
+        Err(Error::Sys(Errno::ENOSYS)) | Err(Error::Sys(Errno::EINVAL)) => (),
+        res => {
+            res.unwrap();
+            let flags = FdFlag::from_bits_truncate(fcntl(r, FcntlArg::F_GETFD).unwrap());
+            assert!(flags.contains(FdFlag::FD_CLOEXEC));
+        }
+    }
+
+    close(r).unwrap();
+    close(w).unwrap();
+}
+
+#[test]
+fn test_gethostname() {
+    let hostname = gethostname().unwrap();
+    assert!(!hostname.is_empty());
+    assert!(!hostname.as_bytes().contains(&0));
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_sethostname_in_uts_namespace() {
+    use std::ffi::OsStr;
+    use nix::sched::CloneFlags;
+    use nix::sys::utsname::uname;
+
+    let parent_hostname = gethostname().unwrap();
+
+    ::in_user_namespace("test_sethostname_in_uts_namespace", CloneFlags::CLONE_NEWUTS, || {
+        let name = OsStr::new("nix-test");
+        if sethostname(name).is_err() || setdomainname(name).is_err() {
+            return 1;
+        }
+        // Unlike `gethostname`, `uname` doesn't allocate.
+        match uname() {
+            Ok(ref uts) if uts.nodename() == name && uts.domainname() == name => 0,
+            _ => 3,
+        }
+    });
+
+    assert_eq!(gethostname().unwrap(), parent_hostname);
+}
+
+#[test]
+fn test_stdio_redirect() {
+    use std::io::Read;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let path = CString::new("/bin/echo").unwrap();
+    let args = [CString::new("echo").unwrap(), CString::new("hello").unwrap()];
+    let (reader, writer) = pipe().unwrap();
+
+    // Apart from `execv` allocating the argument pointers, the child only
+    // calls async-signal-safe functions before `exec`.
+    match fork().unwrap() {
+        Child => {
+            if stdio_redirect(None, Some(writer), None).is_ok() {
+                let _ = execv(&path, &args);
+            }
+            unsafe { _exit(1) }
+        },
+        Parent { child } => {
+            close(writer).unwrap();
+            let mut output = String::new();
+            unsafe { File::from_raw_fd(reader) }.read_to_string(&mut output).unwrap();
+            assert_eq!(output, "hello\n");
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+}
+
+#[test]
+fn test_stdio_redirect_swap() {
+    use std::io::Read;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let path = CString::new("/bin/sh").unwrap();
+    let args = [CString::new("sh").unwrap(), CString::new("-c").unwrap(),
+                CString::new("echo out; echo err >&2").unwrap()];
+    let (out_reader, out_writer) = pipe().unwrap();
+    let (err_reader, err_writer) = pipe().unwrap();
+
+    // Apart from `execv` allocating the argument pointers, the child only
+    // calls async-signal-safe functions before `exec`.
+    match fork().unwrap() {
+        Child => {
+            // The sources are the stdout and stderr being replaced
+            if dup2(out_writer, 1).is_ok() && dup2(err_writer, 2).is_ok() &&
+                stdio_redirect(None, Some(2), Some(1)).is_ok()
+            {
+                let _ = execv(&path, &args);
+            }
+            unsafe { _exit(1) }
+        },
+        Parent { child } => {
+            close(out_writer).unwrap();
+            close(err_writer).unwrap();
+            let mut out = String::new();
+            let mut err = String::new();
+            unsafe { File::from_raw_fd(out_reader) }.read_to_string(&mut out).unwrap();
+            unsafe { File::from_raw_fd(err_reader) }.read_to_string(&mut err).unwrap();
+            assert_eq!(out, "err\n");
+            assert_eq!(err, "out\n");
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+}
+
+#[test]
+fn test_prepare_inherited_fds() {
+    use std::io::Read;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let pipes = [pipe().unwrap(), pipe().unwrap(), pipe().unwrap()];
+    let sh = CString::new("/bin/sh").unwrap();
+    let args = [CString::new("sh").unwrap(), CString::new("-c").unwrap(),
+                CString::new("echo one >&3 && echo two >&4 && echo three >&5").unwrap()];
+
+    // Apart from `execv` allocating the argument pointers, the child only
+    // calls async-signal-safe functions before `exec`.  It reports failures
+    // through its exit code.
+    match fork().unwrap() {
+        Child => {
+            // Move the write ends into the target range in the wrong order, so
+            // that every one of them collides with another's target.
+            let mut scrambled = true;
+            let mut high = [0; 3];
+            for (fd, &(_, w)) in high.iter_mut().zip(&pipes) {
+                match fcntl(w, FcntlArg::F_DUPFD_CLOEXEC(100)) {
+                    Ok(copy) => *fd = copy,
+                    Err(_) => scrambled = false,
+                }
+            }
+            for (&fd, &target) in high.iter().zip(&[5, 3, 4]) {
+                scrambled = scrambled && dup2(fd, target).is_ok();
+            }
+            if !scrambled {
+                unsafe { _exit(2) }
+            }
+
+            let mut scratch = [0; 3];
+            let mut targets = [0; 3];
+            if prepare_inherited_fds(&[5, 3, 4], &mut scratch, &mut targets).is_err() ||
+                targets != [3, 4, 5]
+            {
+                unsafe { _exit(3) }
+            }
+            let _ = execv(&sh, &args);
+            unsafe { _exit(1) }
+        },
+        Parent { child } => {
+            // The parent's fds are unaffected
+            for &(_, writer) in &pipes {
+                close(writer).unwrap();
+            }
+            let mut outputs = Vec::new();
+            for &(reader, _) in &pipes {
+                let mut output = String::new();
+                unsafe { File::from_raw_fd(reader) }.read_to_string(&mut output).unwrap();
+                outputs.push(output);
+            }
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+            assert_eq!(outputs, ["one\n", "two\n", "three\n"]);
+        },
+    }
+}
+
+#[test]
+fn test_prepare_inherited_fds_short_buffers() {
+    let mut scratch = [0; 1];
+    let mut targets = [0; 2];
+    assert_eq!(prepare_inherited_fds(&[0, 1], &mut scratch, &mut targets),
+               Err(Error::Sys(Errno::EINVAL)));
+    assert_eq!(prepare_inherited_fds(&[0, 1], &mut targets, &mut scratch),
+               Err(Error::Sys(Errno::EINVAL)));
+}
+
+#[test]
+fn test_read_listen_fds() {
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let (reader, writer) = pipe().unwrap();
+    let exe = env::current_exe().unwrap().into_os_string().into_vec();
+    let sh = CString::new("/bin/sh").unwrap();
+    // The shell keeps its pid when it execs the test binary, so `$$` is the
+    // pid `LISTEN_PID` has to match.
+    let args = [CString::new("sh").unwrap(), CString::new("-c").unwrap(),
+                CString::new("LISTEN_PID=$$ exec \"$0\" --exact \
+                              test_unistd::read_listen_fds_in_new_program -q >/dev/null")
+                    .unwrap(),
+                CString::new(exe).unwrap()];
+    let env = [CString::new("LISTEN_FDS=2").unwrap(),
+               CString::new("NIX_TEST_READ_LISTEN_FDS=1").unwrap()];
+
+    // Apart from `execve` allocating the argument and environment pointers,
+    // the child only calls async-signal-safe functions before `exec`.
+    match fork().unwrap() {
+        Child => {
+            let mut scratch = [0; 2];
+            let mut targets = [0; 2];
+            if prepare_inherited_fds(&[reader, writer], &mut scratch, &mut targets).is_ok() {
+                let _ = execve(&sh, &args, &env);
+            }
+            unsafe { _exit(1) }
+        },
+        Parent { child } => {
+            close(reader).unwrap();
+            close(writer).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+        },
+    }
+}
+
+/// Run by `test_read_listen_fds` in the program it execs, where
+/// `LISTEN_FDS` is 2; does nothing otherwise.
+#[test]
+fn read_listen_fds_in_new_program() {
+    if env::var_os("NIX_TEST_READ_LISTEN_FDS").is_none() {
+        return;
+    }
+
+    assert_eq!(read_listen_fds(), Ok(vec![3, 4]));
+    for fd in 3..5 {
+        let flags = FdFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFD).unwrap());
+        assert!(flags.contains(FdFlag::FD_CLOEXEC));
+    }
+    // They are the two ends of the parent's pipe, in order
+    assert_eq!(write(4, b"ok"), Ok(2));
+    let mut buf = [0; 2];
+    assert_eq!(read(3, &mut buf), Ok(2));
+    assert_eq!(&buf, b"ok");
+
+    // Nothing else runs in this program, so changing the environment is fine.
+    let pid = format!("{}", getpid());
+    env::set_var("LISTEN_PID", "1");
+    let other = read_listen_fds();
+    env::set_var("LISTEN_PID", &pid);
+    env::set_var("LISTEN_FDS", "0");
+    let none = read_listen_fds();
+    env::set_var("LISTEN_FDS", "two");
+    let invalid = read_listen_fds();
+    env::remove_var("LISTEN_FDS");
+    let unset = read_listen_fds();
+
+    // Fds meant for another process are ignored
+    assert_eq!(other, Ok(vec![]));
+    assert_eq!(none, Ok(vec![]));
+    assert_eq!(invalid, Err(Error::Sys(Errno::EINVAL)));
+    assert_eq!(unset, Ok(vec![]));
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_chroot_and_chdir() {
+    use std::fs;
+    use nix::fcntl::open;
+    use nix::sched::CloneFlags;
+
+    let tempdir = TempDir::new("nix-test_chroot_and_chdir").unwrap();
+    fs::create_dir(tempdir.path().join("sub")).unwrap();
+    File::create(tempdir.path().join("file")).unwrap();
+
+    ::in_user_namespace("test_chroot_and_chdir", CloneFlags::CLONE_NEWNS, || {
+        let exists = |path: &str| stat::stat(path).is_ok();
+        let escaped = |path: &str| {
+            open(path, OFlag::O_RDONLY, Mode::empty()) != Err(Error::Sys(Errno::ENOENT))
+        };
+        let same_file = |a: &str, b: &str| match (stat::stat(a), stat::stat(b)) {
+            (Ok(a), Ok(b)) => (a.st_dev, a.st_ino) == (b.st_dev, b.st_ino),
+            _ => false,
+        };
+
+        if chroot_and_chdir(tempdir.path()).is_err() {
+            1
+        } else if !same_file(".", "/") {
+            3
+        } else if !exists("/file") || !exists("/sub") || exists("/etc") {
+            4
+        } else if escaped("../../etc/passwd") || escaped("/etc/passwd") {
+            5
+        } else if chdir("sub").is_err() || escaped("../../../etc/passwd") {
+            6
+        } else {
+            0
+        }
+    });
+}
+
+#[cfg(target_os = "linux")]
+mod acct {
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::process::Command;
+
+    use tempdir::TempDir;
+
+    use nix::unistd::*;
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_acct_record_parse() {
+        let records = AcctRecord::parse_all(include_bytes!("acct_v3.sample")).unwrap();
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(records[0].comm, "true");
+        assert_eq!(records[0].exit_code, 0);
+        assert_eq!(records[0].uid, Uid::from_raw(1000));
+        assert_eq!(records[0].gid, Gid::from_raw(1000));
+        assert_eq!(records[0].pid, Pid::from_raw(4242));
+        assert_eq!(records[0].ppid, Pid::from_raw(4241));
+        assert_eq!(records[0].btime, 1530000000);
+        assert_eq!(records[0].etime, 12);
+        assert_eq!((records[0].utime, records[0].stime), (1, 2));
+        assert_eq!(records[0].mem, 800);
+        assert_eq!((records[0].minflt, records[0].majflt), (75, 0));
+
+        assert_eq!(records[1].comm, "sleep");
+        assert_eq!(records[1].flags, 0x10);
+        assert_eq!(records[1].exit_code, 9);
+        assert_eq!(records[1].etime, 250);
+        assert_eq!(records[1].utime, 192);
+        assert_eq!(records[1].mem, 4096);
+    }
+
+    #[test]
+    fn test_acct_record_invalid() {
+        assert!(AcctRecord::from_bytes(&[0u8; 32]).is_err());
+        // Version 2 records are not supported
+        let mut buf = [0u8; 64];
+        buf[1] = 2;
+        assert!(AcctRecord::from_bytes(&buf).is_err());
+    }
+
+    #[test]
+    fn test_acct() {
+        #[allow(unused_variables)]
+        let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+
+        // Skip this test when not run as root as `acct()` requires CAP_SYS_PACCT.
+        if !geteuid().is_root() {
+            let stderr = io::stderr();
+            let mut handle = stderr.lock();
+            writeln!(handle, "test_acct requires root privileges. Skipping test.").unwrap();
+            return;
+        }
+
+        let dir = TempDir::new("nix-test_acct").unwrap();
+        let path = dir.path().join("pacct");
+        File::create(&path).unwrap();
+
+        acct(Some(&path)).unwrap();
+        let status = Command::new("true").status();
+        acct::<str>(None).unwrap();
+        assert!(status.unwrap().success());
+
+        let mut data = Vec::new();
+        File::open(&path).unwrap().read_to_end(&mut data).unwrap();
+        let records = AcctRecord::parse_all(&data).unwrap();
+        assert!(records.iter().any(|r| r.comm == "true"));
+    }
+}
+
+#[test]
+fn test_fchownat() {
+    let tempdir = TempDir::new("nix-test_fchownat").unwrap();
+    let target = tempdir.path().join("target");
+    File::create(&target).unwrap();
+    std::os::unix::fs::symlink(&target, tempdir.path().join("link")).unwrap();
+    let dirfd = nix::fcntl::open(tempdir.path(), OFlag::empty(), Mode::empty()).unwrap();
+
+    // Changing to the current owner is always allowed.
+    let (uid, gid) = (getuid(), getgid());
+    fchownat(Some(dirfd), "link", Some(uid), Some(gid), FchownatFlags::NoFollowSymlink).unwrap();
+    fchownat(Some(dirfd), "link", Some(uid), None, FchownatFlags::FollowSymlink).unwrap();
+    let (raw_uid, raw_gid): (::libc::uid_t, ::libc::gid_t) = (uid.into(), gid.into());
+    assert_eq!(stat::lstat(&tempdir.path().join("link")).unwrap().st_uid, raw_uid);
+    assert_eq!(stat::stat(&target).unwrap().st_gid, raw_gid);
+    close(dirfd).unwrap();
+}
+
+#[test]
+fn test_chown_recursive() {
+    use std::os::unix::fs::symlink;
+    use std::thread;
+    use std::time::Duration;
+
+    fn ctime(path: &::std::path::Path) -> (i64, i64) {
+        let st = stat::lstat(path).unwrap();
+        (st.st_ctime as i64, st.st_ctime_nsec as i64)
+    }
+
+    let tempdir = TempDir::new("nix-test_chown_recursive").unwrap();
+    let outside = tempdir.path().join("outside");
+    File::create(&outside).unwrap();
+    let tree = tempdir.path().join("tree");
+    std::fs::create_dir_all(tree.join("sub/subsub")).unwrap();
+    File::create(tree.join("a")).unwrap();
+    File::create(tree.join("sub/b")).unwrap();
+    symlink(&outside, tree.join("sub/link")).unwrap();
+    symlink("../../outside", tree.join("sub/subsub/rellink")).unwrap();
+
+    let outside_ctime = ctime(&outside);
+    let inside_ctime = ctime(&tree.join("sub/b"));
+    // Leave time for the clock used for ctime to advance.
+    thread::sleep(Duration::from_millis(50));
+
+    // Changing to the current owner changes nothing but the ctime of the
+    // files walked.
+    let count = chown_recursive(None, &tree, Some(getuid()), Some(getgid()), false).unwrap();
+    // tree, a, sub, sub/b, sub/link, sub/subsub and sub/subsub/rellink
+    assert_eq!(count, 7);
+    assert!(ctime(&tree.join("sub/b")) != inside_ctime);
+    assert_eq!(ctime(&outside), outside_ctime);
+
+    // Deeply nested directories are walked too.
+    let deep = tempdir.path().join("deep");
+    let mut leaf = deep.clone();
+    for _ in 0..100 {
+        leaf.push("d");
+    }
+    std::fs::create_dir_all(&leaf).unwrap();
+    let count = chown_recursive(None, &deep, Some(getuid()), Some(getgid()), false).unwrap();
+    assert_eq!(count, 101);
+}
+
 #[test]
 fn test_mkstemp() {
     let mut path = env::temp_dir();
@@ -82,6 +745,57 @@ fn test_mkstemp_directory() {
     assert!(mkstemp(&env::temp_dir()).is_err());
 }
 
+#[test]
+fn test_mkdtemp() {
+    let template = env::temp_dir().join("nix_tempdir.XXXXXX");
+    let path = mkdtemp(&template).unwrap();
+    let name = path.file_name().unwrap().to_str().unwrap().to_owned();
+    assert!(name.starts_with("nix_tempdir."));
+    assert!(!name.ends_with("XXXXXX"));
+
+    let stats = stat::stat(&path).unwrap();
+    assert_eq!(stats.st_mode & 0o7777, 0o700);
+    assert!(SFlag::from_bits_truncate(stats.st_mode).contains(SFlag::S_IFDIR));
+    std::fs::remove_dir(&path).unwrap();
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "linux"))]
+#[test]
+fn test_mkostemps() {
+    let template = env::temp_dir().join("nix_tempfile.XXXXXX.log");
+    let (fd, path) = mkostemps(&template, 4, OFlag::empty()).unwrap();
+    let name = path.file_name().unwrap().to_str().unwrap().to_owned();
+    assert!(name.starts_with("nix_tempfile."));
+    assert!(name.ends_with(".log"));
+    assert!(!name.contains("XXXXXX"));
+
+    let fd_flags = FdFlag::from_bits_truncate(fcntl(fd, FcntlArg::F_GETFD).unwrap());
+    assert!(fd_flags.contains(FdFlag::FD_CLOEXEC));
+    let stats = stat::fstat(fd).unwrap();
+    assert_eq!(stats.st_mode & 0o7777, 0o600);
+
+    close(fd).unwrap();
+    unlink(path.as_path()).unwrap();
+
+    // The XXXXXX must come right before the suffix
+    let dir = TempDir::new("nix-test_mkostemps").unwrap();
+    let einval = Error::Sys(Errno::EINVAL);
+    assert_eq!(mkostemps(&dir.path().join("tempfile.XXXXXX.log"), 0, OFlag::empty()),
+               Err(einval));
+    assert_eq!(mkostemps(&dir.path().join("XXXXXX"), 7, OFlag::empty()), Err(einval));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_mktemp_invalid_template() {
+    let dir = TempDir::new("nix-test_mktemp_invalid_template").unwrap();
+    let einval = Error::Sys(Errno::EINVAL);
+    assert_eq!(mkdtemp(&dir.path().join("tempdir")), Err(einval));
+    assert_eq!(mkdtemp(&dir.path().join("XXXXX")), Err(einval));
+    // Nothing was created
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
 #[test]
 fn test_mkfifo() {
     let tempdir = TempDir::new("nix-test_mkfifo").unwrap();
@@ -127,6 +841,38 @@ mod linux_android {
     }
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_setfsuid() {
+    use nix::fcntl::open;
+
+    let euid = geteuid();
+    if !euid.is_root() {
+        // Unprivileged processes may only switch to one of their own IDs
+        assert_eq!(setfsuid(euid), Ok(euid));
+        assert_eq!(setfsuid(Uid::from_raw(0)), Err(Error::Sys(Errno::EPERM)));
+        assert_eq!(setfsgid(getegid()), Ok(getegid()));
+
+        let stderr = std::io::stderr();
+        let mut handle = stderr.lock();
+        writeln!(handle, "test_setfsuid requires root privileges. Skipping test.").unwrap();
+        return;
+    }
+
+    let tempdir = TempDir::new("nix-test_setfsuid").unwrap();
+    let path = tempdir.path().join("secret");
+    let fd = open(&path, OFlag::O_CREAT | OFlag::O_WRONLY, Mode::S_IRUSR | Mode::S_IWUSR).unwrap();
+    close(fd).unwrap();
+
+    let nobody = Uid::from_raw(65534);
+    assert_eq!(setfsuid(nobody), Ok(euid));
+    let res = open(&path, OFlag::O_RDONLY, Mode::empty());
+    assert_eq!(setfsuid(euid), Ok(nobody));
+
+    assert_eq!(res, Err(Error::Sys(Errno::EACCES)));
+    close(open(&path, OFlag::O_RDONLY, Mode::empty()).unwrap()).unwrap();
+}
+
 #[test]
 // `getgroups()` and `setgroups()` do not behave as expected on Apple platforms
 #[cfg(not(any(target_os = "ios", target_os = "macos")))]
@@ -280,6 +1026,92 @@ cfg_if!{
     }
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[test]
+fn test_execvpe_env_builder() {
+    use std::io::Read;
+
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let env = EnvBuilder::new()
+        .set("NIX_TEST_EXECVPE", "overridden")
+        .remove("NIX_TEST_NOT_SET")
+        .build()
+        .unwrap();
+    let args = [CString::new("env").unwrap()];
+    let (reader, writer) = pipe().unwrap();
+
+    // Apart from `execvpe` allocating the argument and environment pointers,
+    // the child only calls async-signal-safe functions before `exec`.
+    match fork().unwrap() {
+        Child => {
+            dup2(writer, 1).unwrap();
+            let _ = execvpe(&args[0], &args, &env);
+            unsafe { _exit(1) }
+        },
+        Parent { child } => {
+            close(writer).unwrap();
+            let mut output = String::new();
+            unsafe { File::from_raw_fd(reader) }.read_to_string(&mut output).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+            assert!(output.lines().any(|l| l == "NIX_TEST_EXECVPE=overridden"));
+            // The rest of the environment is passed on
+            if let Some(path) = env::var_os("PATH") {
+                let path = format!("PATH={}", path.to_string_lossy());
+                assert!(output.lines().any(|l| l == path));
+            }
+        },
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios", target_os = "macos")))]
+#[test]
+fn test_execv_current_env() {
+    use std::io::Read;
+
+    // Tests that modify the environment hold the lock as well, so it doesn't
+    // change while the child is started.
+    #[allow(unused_variables)]
+    let m = ::FORK_MTX.lock().expect("Mutex got poisoned by another test");
+    let expected: Vec<String> = env::vars_os()
+        .filter_map(|(key, value)| match (key.to_str(), value.to_str()) {
+            (Some(key), Some(value)) if !value.contains('\n') => Some(format!("{}={}", key, value)),
+            _ => None,
+        })
+        .collect();
+    let path = CString::new("/usr/bin/env").unwrap();
+    let args = [CString::new("env").unwrap()];
+    let (reader, writer) = pipe().unwrap();
+
+    // Apart from `execv_current_env` allocating the argument pointers, the
+    // child only calls async-signal-safe functions before `exec`.
+    match fork().unwrap() {
+        Child => {
+            dup2(writer, 1).unwrap();
+            let _ = unsafe { execv_current_env(&path, &args) };
+            unsafe { _exit(1) }
+        },
+        Parent { child } => {
+            close(writer).unwrap();
+            let mut output = String::new();
+            unsafe { File::from_raw_fd(reader) }.read_to_string(&mut output).unwrap();
+            assert_eq!(waitpid(child, None), Ok(WaitStatus::Exited(child, 0)));
+            for var in &expected {
+                assert!(output.lines().any(|l| l == var), "{} wasn't passed on", var);
+            }
+        },
+    }
+}
+
+#[test]
+fn test_env_builder_invalid() {
+    let einval = Err(Error::Sys(Errno::EINVAL));
+    assert_eq!(EnvBuilder::new().set("NIX\0TEST", "value").build(), einval);
+    assert_eq!(EnvBuilder::new().set("NIX_TEST", "val\0ue").build(), einval);
+    assert_eq!(EnvBuilder::new().set("NIX=TEST", "value").build(), einval);
+    assert_eq!(EnvBuilder::new().set("", "value").build(), einval);
+}
+
 #[test]
 fn test_fchdir() {
     // fchdir changes the process's cwd